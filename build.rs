@@ -0,0 +1,42 @@
+// Embeds the git commit so `nebula --version` can name the exact build
+use std::path::Path;
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=NEBULA_GIT_COMMIT");
+    // Packagers building from a tarball can pass the commit in directly
+    let commit = std::env::var("NEBULA_GIT_COMMIT")
+        .ok()
+        .filter(|commit| !commit.trim().is_empty())
+        .or_else(git_commit);
+    if let Some(commit) = commit {
+        println!("cargo:rustc-env=NEBULA_GIT_COMMIT={}", commit.trim());
+    }
+    if Path::new(".git/HEAD").exists() {
+        println!("cargo:rerun-if-changed=.git/HEAD");
+        println!("cargo:rerun-if-changed=.git/refs");
+    }
+}
+
+// Short hash of HEAD, with a `-dirty` suffix when the tree has local changes
+fn git_commit() -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let commit = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if commit.is_empty() {
+        return None;
+    }
+    let dirty = Command::new("git")
+        .args(["status", "--porcelain", "--untracked-files=no"])
+        .output()
+        .ok()
+        .is_some_and(|output| !output.stdout.is_empty());
+    Some(if dirty {
+        format!("{}-dirty", commit)
+    } else {
+        commit
+    })
+}
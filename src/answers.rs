@@ -0,0 +1,76 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::installer::SwapMode;
+use crate::packages::AurHelper;
+use crate::selection::{InstallProfile, Mitigations};
+
+// Setup choices saved from the review screen so an install can be repeated unattended.
+// Passwords are never written; the file only names where they should come from.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnswerFile {
+    pub disk: String, // Device path, e.g. /dev/nvme0n1
+    pub btrfs_layout: String,
+    pub keymap: String,
+    pub locale: String,
+    pub timezone: String,
+    pub hostname: String,
+    pub username: String,
+    pub encrypt_disk: bool,
+    pub nvidia_driver: Option<String>, // `open` or `proprietary`
+    pub swap_enabled: bool,
+    pub zram_size: String,
+    pub swap_mode: SwapMode,
+    pub grub_password: bool, // A GRUB password was set and must be supplied again
+    pub mitigations: Mitigations,
+    pub boot_splash: Option<String>,
+    pub profile: InstallProfile,
+    pub compositors: Vec<String>,
+    pub browsers: Vec<String>,
+    pub editors: Vec<String>,
+    pub terminals: Vec<String>,
+    pub extra_packages: Vec<String>,
+    pub services: Vec<String>,
+    pub aur_helper: AurHelper,
+    pub pacman_packages: Vec<String>,
+    pub aur_packages: Vec<String>,
+}
+
+impl AnswerFile {
+    // Commented-out password file references for the secrets that were left out
+    fn password_placeholders(&self) -> String {
+        let mut lines = vec![
+            "# Passwords are not exported. Put each one in a root-only file and uncomment:",
+            "# user_password_file = \"/root/nebula-user-password\"",
+        ];
+        if self.encrypt_disk {
+            lines.push("# luks_password_file = \"/root/nebula-luks-password\"");
+        }
+        if self.grub_password {
+            lines.push("# grub_password_file = \"/root/nebula-grub-password\"");
+        }
+        lines.iter().map(|line| format!("{}\n", line)).collect()
+    }
+}
+
+// Writes the answer file as TOML, creating the parent directory if needed
+pub fn write_answer_file(path: &Path, answers: &AnswerFile) -> Result<()> {
+    let body = toml::to_string(answers).context("serialize answer file")?;
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent).with_context(|| format!("create {}", parent.display()))?;
+    }
+    let contents = format!(
+        "# Nebula installer selections exported from the review screen\n{}\n{}",
+        answers.password_placeholders(),
+        body
+    );
+    fs::write(path, contents).with_context(|| format!("write {}", path.display()))?;
+    Ok(())
+}
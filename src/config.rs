@@ -6,6 +6,10 @@ use serde::Deserialize;
 pub struct Config {
     pub packages: PackagesConfig,
     pub selections: SelectionsConfig,
+    #[serde(default)]
+    pub hooks: HooksConfig,
+    #[serde(default)]
+    pub gnome: GnomeConfig,
 }
 
 #[derive(Debug, Deserialize)]
@@ -13,6 +17,29 @@ pub struct PackagesConfig {
     pub required: Vec<String>,
 }
 
+// Commands run inside the target system at the start of the final step
+#[derive(Debug, Default, Deserialize)]
+pub struct HooksConfig {
+    #[serde(default)]
+    pub post_install: Vec<String>,
+    #[serde(default)]
+    pub abort_on_failure: bool,
+}
+
+// gsettings keys seeded on the first GNOME login
+#[derive(Debug, Default, Deserialize)]
+pub struct GnomeConfig {
+    #[serde(default)]
+    pub settings: Vec<GsettingConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GsettingConfig {
+    pub schema: String,
+    pub key: String,
+    pub value: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct SelectionsConfig {
     pub compositors: Vec<ChoiceConfig>,
@@ -63,6 +90,29 @@ fn validate_config(cfg: &Config) -> Result<(), String> {
     validate_choices("selections.editors", &cfg.selections.editors)?;
     validate_choices("selections.terminals", &cfg.selections.terminals)?;
 
+    for (idx, command) in cfg.hooks.post_install.iter().enumerate() {
+        if command.trim().is_empty() {
+            return Err(format!("hooks.post_install[{idx}] must not be empty"));
+        }
+    }
+
+    for (idx, setting) in cfg.gnome.settings.iter().enumerate() {
+        if setting.schema.trim().is_empty() || setting.key.trim().is_empty() {
+            return Err(format!(
+                "gnome.settings[{idx}] must include a schema and a key"
+            ));
+        }
+        if setting.schema.contains(char::is_whitespace) || setting.key.contains(char::is_whitespace)
+        {
+            return Err(format!(
+                "gnome.settings[{idx}] schema and key must not contain spaces"
+            ));
+        }
+        if setting.value.trim().is_empty() || setting.value.contains('\n') {
+            return Err(format!("gnome.settings[{idx}].value must be a single line"));
+        }
+    }
+
     Ok(())
 }
 
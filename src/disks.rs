@@ -1,11 +1,46 @@
 use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
 use std::process::Command;
+use std::sync::OnceLock;
 
-#[derive(Clone, Debug)]
+// Where archiso mounts the boot media
+const LIVE_MEDIA_MOUNT: &str = "/run/archiso/bootmnt";
+
+static LIVE_MEDIA_DISK: OnceLock<Option<String>> = OnceLock::new();
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct DiskInfo {
     pub name: String,
     pub size: String,
     pub model: String,
+    #[serde(default)]
+    pub rotational: bool,
+    #[serde(default)]
+    pub transport: String,
+    #[serde(default)]
+    pub rotation_rate: Option<u32>,
+    #[serde(default)]
+    pub health: DiskHealth,
+}
+
+// SMART overall health; Unknown when smartctl is missing or the disk doesn't report it
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DiskHealth {
+    #[default]
+    Unknown,
+    Passed,
+    Failed,
+}
+
+impl DiskHealth {
+    pub fn label(self) -> &'static str {
+        match self {
+            DiskHealth::Unknown => "SMART n/a",
+            DiskHealth::Passed => "SMART OK",
+            DiskHealth::Failed => "SMART FAILING",
+        }
+    }
 }
 
 impl DiskInfo {
@@ -13,6 +48,7 @@ impl DiskInfo {
         format!("/dev/{}", self.name)
     }
 
+    // Names ending in a digit take a `p` separator: nvme0n1p1 and mmcblk0p1, but sda1 and vda1
     pub fn partition_path(&self, index: u8) -> String {
         let needs_p = self
             .name
@@ -27,6 +63,11 @@ impl DiskInfo {
         }
     }
 
+    // Loop device backed by NEBULA_TARGET_IMAGE rather than a real disk
+    pub fn is_image(&self) -> bool {
+        self.transport == "loop"
+    }
+
     pub fn label(&self) -> String {
         if self.model.is_empty() {
             format!("{} ({})", self.name, self.size)
@@ -34,11 +75,29 @@ impl DiskInfo {
             format!("{} ({}) {}", self.name, self.size, self.model)
         }
     }
+
+    pub fn kind(&self) -> &'static str {
+        if self.transport == "nvme" || self.name.starts_with("nvme") {
+            "NVMe"
+        } else if self.rotational {
+            "HDD"
+        } else {
+            "SSD"
+        }
+    }
+
+    // Drive type plus spindle speed when smartctl reported one, e.g. "HDD 7200 rpm"
+    pub fn kind_label(&self) -> String {
+        match self.rotation_rate {
+            Some(rpm) if self.rotational => format!("{} {} rpm", self.kind(), rpm),
+            _ => self.kind().to_string(),
+        }
+    }
 }
 
 pub fn list_disks() -> Result<Vec<DiskInfo>> {
     let output = Command::new("lsblk")
-        .args(["-dn", "-P", "-o", "NAME,SIZE,TYPE,MODEL"])
+        .args(["-dn", "-P", "-o", "NAME,SIZE,TYPE,MODEL,ROTA,TRAN"])
         .output()
         .context("lsblk")?;
 
@@ -63,12 +122,159 @@ pub fn list_disks() -> Result<Vec<DiskInfo>> {
         if name.is_empty() {
             continue;
         }
-        disks.push(DiskInfo { name, size, model });
+        let rotational = fields.get("ROTA").map(|v| v.trim()) == Some("1");
+        let transport = fields.get("TRAN").cloned().unwrap_or_default();
+        let (health, rotation_rate) = read_smart(&format!("/dev/{}", name));
+        disks.push(DiskInfo {
+            name,
+            size,
+            model,
+            rotational,
+            transport,
+            rotation_rate,
+            health,
+        });
     }
 
     Ok(disks)
 }
 
+// Disk image attached as a loop device so the install never touches real hardware.
+// The loop device is detached when this is dropped.
+pub struct ImageTarget {
+    pub disk: DiskInfo,
+}
+
+impl ImageTarget {
+    // Creates a sparse image of `size` (e.g. `20G`) if the file is missing, then attaches it
+    // with partition scanning so the installer's partitions show up as loopNp1 and loopNp2
+    pub fn attach(path: &str, size: &str) -> Result<Self> {
+        if !Path::new(path).exists() {
+            let status = Command::new("truncate")
+                .args(["-s", size, path])
+                .status()
+                .context("truncate")?;
+            if !status.success() {
+                anyhow::bail!("Could not create a {} image at {}", size, path);
+            }
+        }
+        let output = Command::new("losetup")
+            .args(["--find", "--show", "--partscan", path])
+            .output()
+            .context("losetup")?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("losetup failed: {}", stderr.trim());
+        }
+        let device = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        let name = device.trim_start_matches("/dev/").to_string();
+        let size = Command::new("lsblk")
+            .args(["-dn", "-o", "SIZE", &device])
+            .output()
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+            .unwrap_or_default();
+        Ok(Self {
+            disk: DiskInfo {
+                name,
+                size,
+                model: format!("image {}", path),
+                transport: "loop".to_string(),
+                ..DiskInfo::default()
+            },
+        })
+    }
+}
+
+impl Drop for ImageTarget {
+    fn drop(&mut self) {
+        let _ = Command::new("losetup")
+            .args(["-d", &self.disk.device_path()])
+            .status();
+    }
+}
+
+// Queries smartctl for health and rotation rate; missing smartmontools just means Unknown
+fn read_smart(device: &str) -> (DiskHealth, Option<u32>) {
+    // smartctl's exit status is a bitmask that is non-zero even for readable disks, so only stdout matters
+    let Ok(output) = Command::new("smartctl").args(["-H", "-i", device]).output() else {
+        return (DiskHealth::Unknown, None);
+    };
+    parse_smartctl(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn parse_smartctl(output: &str) -> (DiskHealth, Option<u32>) {
+    let mut health = DiskHealth::Unknown;
+    let mut rotation_rate = None;
+    for line in output.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let key = key.trim();
+        let value = value.trim();
+        // ATA and NVMe report "overall-health", SCSI reports "Health Status"
+        if key.starts_with("SMART overall-health") || key == "SMART Health Status" {
+            health = if value.starts_with("PASSED") || value == "OK" {
+                DiskHealth::Passed
+            } else {
+                DiskHealth::Failed
+            };
+        } else if key == "Rotation Rate" {
+            rotation_rate = value
+                .split_whitespace()
+                .next()
+                .and_then(|rpm| rpm.parse().ok());
+        }
+    }
+    (health, rotation_rate)
+}
+
+// Devices on the disk that hold a LUKS container, e.g. `sda2`; the disk itself when it is one
+pub fn luks_containers(disk: &DiskInfo) -> Vec<String> {
+    let Ok(output) = Command::new("lsblk")
+        .args(["-nr", "-o", "NAME,FSTYPE", &disk.device_path()])
+        .output()
+    else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| match line.split_once(' ') {
+            Some((name, "crypto_LUKS")) => Some(name.to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+// Whether the disk holds the media the live system booted from
+pub fn is_live_media(disk: &DiskInfo) -> bool {
+    LIVE_MEDIA_DISK
+        .get_or_init(detect_live_media_disk)
+        .as_deref()
+        == Some(disk.name.as_str())
+}
+
+// Follows the archiso boot mount back to its whole disk; None with copytoram or outside the ISO
+fn detect_live_media_disk() -> Option<String> {
+    let output = Command::new("findmnt")
+        .args(["-no", "SOURCE", LIVE_MEDIA_MOUNT])
+        .output()
+        .ok()?;
+    let source = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if !output.status.success() || source.is_empty() {
+        return None;
+    }
+    let output = Command::new("lsblk")
+        .args(["-nrs", "-o", "NAME,TYPE", &source])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| match line.split_once(' ') {
+            Some((name, "disk")) => Some(name.to_string()),
+            _ => None,
+        })
+}
+
 fn parse_lsblk_kv(line: &str) -> std::collections::HashMap<String, String> {
     let mut map = std::collections::HashMap::new();
     let mut rest = line.trim();
@@ -87,3 +293,26 @@ fn parse_lsblk_kv(line: &str) -> std::collections::HashMap<String, String> {
     }
     map
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partition_path_separator() {
+        let cases = [
+            ("nvme0n1", "/dev/nvme0n1p1"),
+            ("sda", "/dev/sda1"),
+            ("mmcblk0", "/dev/mmcblk0p1"),
+            ("vda", "/dev/vda1"),
+            ("loop0", "/dev/loop0p1"),
+        ];
+        for (name, expected) in cases {
+            let disk = DiskInfo {
+                name: name.to_string(),
+                ..DiskInfo::default()
+            };
+            assert_eq!(disk.partition_path(1), expected, "{}", name);
+        }
+    }
+}
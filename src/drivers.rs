@@ -4,6 +4,7 @@ use std::fs;
 use std::process::Command;
 
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 
 // GPU manufacturers
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -21,6 +22,27 @@ pub enum NvidiaVariant {
     Nouveau,     // Open-source Nouveau driver
 }
 
+// Which linux-firmware packages pacstrap installs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FirmwareChoice {
+    #[default]
+    Full, // The whole linux-firmware package
+    Minimal,    // No firmware, e.g. for virtual machines
+    VendorOnly, // Only the linux-firmware splits for the detected GPU and network vendors
+}
+
+impl FirmwareChoice {
+    // Parses NEBULA_FIRMWARE values: `full`, `minimal` or `vendor`
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "full" => Some(FirmwareChoice::Full),
+            "minimal" => Some(FirmwareChoice::Minimal),
+            "vendor" => Some(FirmwareChoice::VendorOnly),
+            _ => None,
+        }
+    }
+}
+
 // Detects the GPU vendors present in the system
 pub fn detect_gpu_vendors() -> Result<HashSet<GpuVendor>> {
     let mut vendors = HashSet::new();
@@ -143,6 +165,90 @@ pub fn driver_packages(
     packages
 }
 
+// Firmware packages for the choice; VendorOnly returns an empty list if nothing was recognized
+pub fn firmware_packages(
+    choice: FirmwareChoice,
+    gpu_vendors: &HashSet<GpuVendor>,
+) -> Vec<&'static str> {
+    match choice {
+        FirmwareChoice::Full => vec!["linux-firmware"],
+        FirmwareChoice::Minimal => Vec::new(),
+        FirmwareChoice::VendorOnly => {
+            let mut packages = Vec::new();
+            let mut add = |pkg: &'static str| {
+                if !packages.contains(&pkg) {
+                    packages.push(pkg);
+                }
+            };
+            if gpu_vendors.contains(&GpuVendor::Amd) {
+                add("linux-firmware-amdgpu");
+                add("linux-firmware-radeon");
+            }
+            if gpu_vendors.contains(&GpuVendor::Intel) {
+                add("linux-firmware-intel");
+            }
+            if gpu_vendors.contains(&GpuVendor::Nvidia) {
+                add("linux-firmware-nvidia");
+            }
+            for pkg in network_firmware_packages() {
+                add(pkg);
+            }
+            packages
+        }
+    }
+}
+
+// linux-firmware splits for the PCI network adapters in /sys/class/net
+fn network_firmware_packages() -> Vec<&'static str> {
+    let mut packages = Vec::new();
+    let Ok(entries) = fs::read_dir("/sys/class/net") else {
+        return packages;
+    };
+    for entry in entries.flatten() {
+        let Ok(vendor) = fs::read_to_string(entry.path().join("device/vendor")) else {
+            continue;
+        };
+        let pkg = match vendor.trim().trim_start_matches("0x") {
+            "8086" => "linux-firmware-intel",
+            "10ec" => "linux-firmware-realtek",
+            "168c" | "17cb" => "linux-firmware-atheros",
+            "14e4" => "linux-firmware-broadcom",
+            "14c3" => "linux-firmware-mediatek",
+            _ => continue,
+        };
+        if !packages.contains(&pkg) {
+            packages.push(pkg);
+        }
+    }
+    packages
+}
+
+// 32-bit Vulkan drivers that Steam and Wine need for the detected GPUs
+pub fn lib32_driver_packages(
+    vendors: &HashSet<GpuVendor>,
+    nvidia_variant: Option<NvidiaVariant>,
+) -> Vec<String> {
+    let mut packages = Vec::new();
+    if vendors.contains(&GpuVendor::Amd) {
+        extend_unique(&mut packages, &["lib32-mesa", "lib32-vulkan-radeon"]);
+    }
+    if vendors.contains(&GpuVendor::Intel) {
+        extend_unique(&mut packages, &["lib32-mesa", "lib32-vulkan-intel"]);
+    }
+    if vendors.contains(&GpuVendor::Nvidia) {
+        match nvidia_variant {
+            Some(NvidiaVariant::Open) | Some(NvidiaVariant::Proprietary) => {
+                extend_unique(&mut packages, &["lib32-nvidia-utils"])
+            }
+            Some(NvidiaVariant::Nouveau) => {
+                extend_unique(&mut packages, &["lib32-mesa", "lib32-vulkan-nouveau"])
+            }
+            None => {}
+        }
+    }
+    packages
+}
+
 // Summary of detected GPUs and the chosen Nvidia driver
 pub fn format_gpu_summary(
     vendors: &HashSet<GpuVendor>,
@@ -1,8 +1,10 @@
+use std::fs::{self, File};
 use std::io::{BufRead, BufReader, Write};
+use std::os::unix::fs::PermissionsExt;
 use std::process::{Command, Stdio};
 use std::sync::{
     atomic::{AtomicBool, Ordering},
-    Arc,
+    Arc, Mutex,
 };
 use std::thread;
 use std::time::Duration;
@@ -11,7 +13,10 @@ use anyhow::{Context, Result};
 
 use crate::model::InstallerEvent;
 
-use super::{send_event, TMP_INSTALLER_LOG};
+use super::{send_event, STEP_NAMES, TMP_INSTALLER_LOG};
+
+// Log file of the step that is currently running
+static STEP_LOG: Mutex<Option<File>> = Mutex::new(None);
 
 // Appends a line to the temporary installer log
 pub(crate) fn append_temp_installer_log(line: &str) {
@@ -24,6 +29,52 @@ pub(crate) fn append_temp_installer_log(line: &str) {
     }
 }
 
+// Per-step log path, e.g. /tmp/nebula-step-5-installing-base-system.log
+pub fn step_log_path(index: usize) -> String {
+    let name = STEP_NAMES.get(index).copied().unwrap_or("step");
+    let slug = name
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    format!("/tmp/nebula-step-{}-{}.log", index, slug)
+}
+
+// Routes log lines to a fresh file for the step until `finish_step_log`
+pub(crate) fn start_step_log(index: usize) {
+    let file = File::create(step_log_path(index)).ok();
+    if let Ok(mut current) = STEP_LOG.lock() {
+        *current = file;
+    }
+}
+
+pub(crate) fn finish_step_log() {
+    if let Ok(mut current) = STEP_LOG.lock() {
+        *current = None;
+    }
+}
+
+// Appends a line to the running step's log, if any
+pub(crate) fn append_step_log(line: &str) {
+    if let Ok(mut current) = STEP_LOG.lock() {
+        if let Some(file) = current.as_mut() {
+            let _ = writeln!(file, "{}", line);
+        }
+    }
+}
+
+// True when `name` is an executable file in one of the PATH directories
+pub(crate) fn command_exists(name: &str) -> bool {
+    let Some(paths) = std::env::var_os("PATH") else {
+        return false;
+    };
+    std::env::split_paths(&paths).any(|dir| {
+        fs::metadata(dir.join(name))
+            .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+    })
+}
+
 // Helper to run a command inside the arch-chroot environment
 pub(crate) fn run_chroot(
     tx: &crossbeam_channel::Sender<InstallerEvent>,
@@ -176,15 +227,15 @@ pub(crate) fn run_command_stream(
 
     let status = child.wait().context("wait")?;
     running.store(false, Ordering::Relaxed);
-    if let Some(handle) = out_handle {
-        let _ = handle.join();
-    }
-    if let Some(handle) = err_handle {
-        let _ = handle.join();
+    let mut errors = Vec::new();
+    for handle in [out_handle, err_handle].into_iter().flatten() {
+        errors.extend(handle.join().unwrap_or_default());
     }
 
     if !status.success() {
-        anyhow::bail!("Command failed: {}", cmdline);
+        // The `error:` lines stay in the chain so callers can tell why it failed
+        let details = anyhow::anyhow!("{}", errors.join("; "));
+        return Err(details.context(format!("Command failed: {}", cmdline)));
     }
     Ok(())
 }
@@ -216,14 +267,17 @@ pub(crate) fn run_command_capture(
 }
 
 // Streams the output of a command, sending each line as a log event
+// Returns the `error:` lines it saw
 fn stream_command_output<R: std::io::Read>(
     reader: R,
     tx: &crossbeam_channel::Sender<InstallerEvent>,
-) {
+) -> Vec<String> {
     let mut buffer = [0u8; 4096];
     let mut line = String::new();
     let mut pending_cr = false;
     let mut reader = reader;
+    let mut progress = PacmanProgress::default();
+    let mut errors = Vec::new();
     loop {
         let count = match reader.read(&mut buffer) {
             Ok(0) => break,
@@ -236,7 +290,7 @@ fn stream_command_output<R: std::io::Read>(
                 if ch == '\n' {
                     let trimmed = sanitize_log_line(&line);
                     if !trimmed.is_empty() {
-                        send_event(tx, InstallerEvent::Log(trimmed));
+                        send_progress_line(tx, &mut progress, &mut errors, trimmed);
                     }
                     line.clear();
                     pending_cr = false;
@@ -252,7 +306,7 @@ fn stream_command_output<R: std::io::Read>(
             if ch == '\n' {
                 let trimmed = sanitize_log_line(&line);
                 if !trimmed.is_empty() {
-                    send_event(tx, InstallerEvent::Log(trimmed));
+                    send_progress_line(tx, &mut progress, &mut errors, trimmed);
                 }
                 line.clear();
             } else {
@@ -263,14 +317,84 @@ fn stream_command_output<R: std::io::Read>(
     if pending_cr {
         let trimmed = sanitize_log_line(&line);
         if !trimmed.is_empty() {
-            send_event(tx, InstallerEvent::Log(trimmed));
+            send_progress_line(tx, &mut progress, &mut errors, trimmed);
         }
-        return;
+        return errors;
     }
     let trimmed = sanitize_log_line(&line);
     if !trimmed.is_empty() {
-        send_event(tx, InstallerEvent::Log(trimmed));
+        send_progress_line(tx, &mut progress, &mut errors, trimmed);
+    }
+    errors
+}
+
+// Logs a streamed line and reports pacman progress it reveals
+fn send_progress_line(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    progress: &mut PacmanProgress,
+    errors: &mut Vec<String>,
+    line: String,
+) {
+    if let Some(fraction) = progress.update(&line) {
+        send_event(tx, InstallerEvent::StepProgress(fraction));
+    }
+    if line.starts_with("error:") {
+        errors.push(line.clone());
+    }
+    send_event(tx, InstallerEvent::Log(line));
+}
+
+// Estimates how far a pacman transaction has got from its non-interactive output.
+// Downloads fill the first half of the step and the `(x/y) installing` counters the second.
+#[derive(Default)]
+struct PacmanProgress {
+    total: usize,
+    downloaded: usize,
+    last: f64,
+}
+
+impl PacmanProgress {
+    fn update(&mut self, line: &str) -> Option<f64> {
+        let fraction = if let Some(rest) = line.strip_prefix("Packages (") {
+            // A new transaction starts, e.g. `Packages (120) base-3-2 ...`
+            self.total = rest.split(')').next()?.trim().parse().ok()?;
+            self.downloaded = 0;
+            self.last = 0.0;
+            return None;
+        } else if line.starts_with("downloading ") && self.total > 0 {
+            self.downloaded += 1;
+            0.5 * self.downloaded.min(self.total) as f64 / self.total as f64
+        } else if let Some((current, total)) = parse_transaction_counter(line) {
+            0.5 + 0.5 * current.min(total) as f64 / total as f64
+        } else {
+            return None;
+        };
+        if fraction > self.last {
+            self.last = fraction;
+            Some(fraction)
+        } else {
+            None
+        }
+    }
+}
+
+// Parses pacman counters such as `( 3/120) installing linux`
+fn parse_transaction_counter(line: &str) -> Option<(usize, usize)> {
+    let (counter, action) = line.strip_prefix('(')?.split_once(')')?;
+    let action = action.trim_start();
+    if !["installing", "upgrading", "reinstalling"]
+        .iter()
+        .any(|verb| action.starts_with(verb))
+    {
+        return None;
+    }
+    let (current, total) = counter.split_once('/')?;
+    let current = current.trim().parse().ok()?;
+    let total = total.trim().parse().ok()?;
+    if total == 0 {
+        return None;
     }
+    Some((current, total))
 }
 
 // Removes ANSI escape codes and other control characters from log lines
@@ -3,59 +3,467 @@
 ////////
 mod commands;
 mod pacman;
+mod report;
+mod state;
 mod system;
 mod themes;
 
 use std::fs::{self, OpenOptions};
 use std::io::Write;
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
 use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
 
 use crate::disks::DiskInfo;
+use crate::drivers::{detect_gpu_vendors, firmware_packages, FirmwareChoice};
 use crate::model::{InstallerEvent, StepStatus};
+use crate::network::is_network_ready;
+use crate::packages::AurHelper;
+use crate::selection::{InstallProfile, Mitigations};
 
-use commands::{append_temp_installer_log, run_chroot, run_command, run_command_capture};
+use commands::{
+    append_step_log, append_temp_installer_log, command_exists, finish_step_log, run_chroot,
+    run_command, run_command_capture, start_step_log,
+};
 use pacman::{
-    configure_mirrorlist, dedup_packages, ensure_nebula_repo_configured,
-    import_nebula_repo_key, install_optional_packages_best_effort, install_pacman_packages,
-    run_pacstrap, sync_pacman_databases, validate_offline_base_package,
-    validate_offline_packages, write_failed_packages_log, write_hybrid_pacman_conf,
+    check_offline_repo_freshness, configure_mirrorlist, configure_target_mirrorlist,
+    dedup_packages, download_packages, download_packages_best_effort,
+    ensure_nebula_repo_configured, import_nebula_repo_key, install_optional_packages_best_effort,
+    install_pacman_packages_with_retry, package_installable, rank_mirrorlist, run_pacstrap,
+    seed_target_package_cache, set_multilib, set_parallel_downloads, sync_pacman_databases,
+    sync_prefetch_databases, validate_offline_base_package, validate_offline_packages,
+    verify_offline_packages, verify_pacstrap, write_failed_packages_log, write_hybrid_pacman_conf,
     write_offline_pacman_conf,
 };
+use report::write_install_report;
+use state::{clear_install_state, remount_for_resume, save_install_state, teardown_after_cancel};
 use system::{
-    close_cryptroot_with_retries, configure_hypr_monitors, configure_zram,
-    copy_installer_log, detect_microcode_package, get_uuid, install_caelestia,
-    install_nebula_hypr, schedule_caelestia_init, schedule_nebula_init, schedule_nebula_theme,
-    write_file, write_os_release,
+    add_luks_keyfile, close_cryptroot_with_retries, configure_fingerprint_pam, configure_firewall,
+    configure_hypr_input, configure_hypr_monitors, configure_locales, configure_mdns_nsswitch,
+    configure_wireless_regdom, configure_zram, copy_installer_log, copy_installer_to_target,
+    create_swapfile, detect_microcode_package, existing_home_uid, get_uuid, install_caelestia,
+    install_nebula_hypr, is_mountpoint, luks_header_location, mkinitcpio_array_contains,
+    mount_existing_home, mount_subvolumes, passwd_has_uid, scan_bad_blocks,
+    schedule_caelestia_init, schedule_fingerprint_reminder, schedule_flatpak_apps,
+    schedule_nebula_init, schedule_nebula_theme, secure_wipe_disk, set_grub_password,
+    swapfile_resume_params, target_user_exists, tune_fstab_for_ssd, verify_partition, write_file,
+    write_makepkg_dropin, write_os_release, write_sudoers_dropin, LUKS_KEYFILE, SWAPFILE_PATH,
 };
 use themes::{
-    ensure_grub_cmdline_params, install_grub_theme, install_sddm_theme,
-    remove_grub_cmdline_params, set_grub_distributor, set_grub_gfx, update_grub_cmdline,
+    enable_grub_cryptodisk, ensure_grub_cmdline_params, install_themes, remove_grub_cmdline_params,
+    set_grub_distributor, set_grub_gfx, set_plymouth_theme, update_grub_cmdline,
+};
+
+pub use commands::step_log_path;
+pub use state::{find_resumable_install, InstallState};
+pub use system::{
+    default_swapfile_size_mib, existing_home_disk, luks_header_disk, parse_existing_home,
+    parse_swapfile_size, validate_luks_pbkdf, validate_zram_size, SWAP_MOUNTPOINT, SWAP_SUBVOLUME,
 };
+pub use themes::{available_plymouth_themes, parse_display_resolution, parse_display_scale};
 
 // Configuration choices made by the user
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub struct InstallConfig {
     pub disk: DiskInfo,
     pub keymap: String,
+    #[serde(default)]
+    pub console_font: Option<String>, // FONT= in vconsole.conf, e.g. ter-132n; None keeps the kernel font
+    #[serde(default)]
+    pub locale: String, // Empty means en_US.UTF-8
     pub timezone: String,
     pub hostname: String,
     pub username: String,
+    #[serde(default)]
+    pub full_name: Option<String>, // GECOS comment that greeters show as the display name
+    #[serde(default)]
+    pub shell: Shell,
+    #[serde(skip)]
     pub user_password: String,
+    #[serde(skip)]
     pub luks_password: String,
+    // Detached LUKS header file, kept off the install disk (e.g. on a USB key)
+    pub luks_detached_header: Option<String>,
     pub encrypt_disk: bool,
+    // LUKS1 root with /boot inside it, unlocked by GRUB; the ESP moves to /efi
+    #[serde(default)]
+    pub full_disk_encryption: bool,
+    #[serde(default)]
+    pub luks_version: LuksVersion,
+    #[serde(default)]
+    pub luks_pbkdf: LuksPbkdf,
     pub swap_enabled: bool,
+    pub zram_size: String, // zram-generator size expression, e.g. `ram`, `ram/2`, `8192`
+    #[serde(default)]
+    pub swap_mode: SwapMode,
+    #[serde(default = "default_subvolumes")]
+    pub subvolumes: Vec<SubvolSpec>,
+    #[serde(default)]
+    pub existing_home: Option<ExistingHome>, // Mounted on /home instead of an @home subvolume
     pub driver_packages: Vec<String>,
+    #[serde(default)]
+    pub firmware: FirmwareChoice,
     pub kernel_package: String,
     pub kernel_headers: String,
     pub base_packages: Vec<String>,
     pub extra_pacman_packages: Vec<String>,
     pub extra_aur_packages: Vec<String>,
+    #[serde(default)]
+    pub optional_failure_policy: FailurePolicy,
+    pub aur_helper: AurHelper,
     pub compositor_label: String,
     pub selected_browsers: Vec<String>,
     pub selected_editors: Vec<String>,
     pub offline_only: bool,
     pub hyprland_selected: bool,
+    pub enable_printing: bool,
+    pub enable_bluetooth: bool,
+    pub enable_flatpak: bool,
+    pub enable_mdns: bool,
+    pub enable_tlp: bool,
+    pub enable_power_profiles: bool,
+    #[serde(default)]
+    pub enable_firewall: FirewallChoice,
+    pub enable_fingerprint: bool,
+    pub enable_multilib: bool, // Keeps the 32-bit [multilib] repo enabled in pacman.conf
+    // Adds the [nebula] repo and its key to the target; forced on when a package needs it
+    #[serde(default = "default_true")]
+    pub configure_nebula_repo: bool,
+    pub secure_wipe: bool,
+    #[serde(default)]
+    pub scan_bad_blocks: bool, // Read-only badblocks pass before the disk is touched
+    #[serde(default)]
+    pub ssd_tuning: bool, // noatime and async discard on the btrfs mounts, plus fstrim.timer
+    #[serde(default)]
+    pub package_cache: Option<String>, // Every package is downloaded here before partitioning
+    #[serde(default)]
+    pub copy_installer: bool, // Leave the installer binary in /usr/local/bin on the target
+    #[serde(default)]
+    pub compress_logs: bool, // gzip the logs copied to /var/log on the target
+    // Leave /mnt mounted after the last step; the frontend calls release_target when it is done
+    #[serde(default)]
+    pub keep_target_mounted: bool,
+    pub profile: InstallProfile,
+    pub verbose_boot: bool, // Show kernel and systemd messages instead of the splash screen
+    #[serde(default)]
+    pub mitigations: Mitigations,
+    // None keeps the automatic nebula-splash/nebula-luks choice; PLYMOUTH_DISABLED drops Plymouth
+    #[serde(default)]
+    pub plymouth_theme: Option<String>,
+    #[serde(default)]
+    pub display_resolution: Option<(u32, u32)>, // Replaces the detected resolution for GRUB and SDDM
+    #[serde(default)]
+    pub display_scale: Option<f32>, // Replaces the SDDM scale derived from the resolution
+    #[serde(default)]
+    pub hypr_input: HyprInput,
+    #[serde(default)]
+    pub sudo_policy: SudoPolicy,
+    #[serde(default)]
+    pub makepkg: MakepkgTuning,
+    pub flatpak_apps: Vec<String>,
+    #[serde(skip)]
+    pub grub_password: Option<String>,
+    pub parallel_downloads: u8,
+    pub rank_mirrors: bool,
+    pub ntp_server: Option<String>, // Replaces the default timesyncd servers when set
+    #[serde(default)]
+    pub wifi_country: Option<String>, // Wireless regulatory domain, an ISO 3166 code like `DE`
+    pub post_install_commands: Vec<String>,
+    pub abort_on_hook_failure: bool,
+    #[serde(skip)]
+    pub resume_from: usize, // First step to run when resuming an interrupted install
+}
+
+impl InstallConfig {
+    // Where the ESP is mounted in the target; /boot is encrypted with full disk encryption
+    pub(crate) fn esp_mountpoint(&self) -> &'static str {
+        if self.full_disk_encryption {
+            "/efi"
+        } else {
+            "/boot"
+        }
+    }
+
+    // Swap is on and backed by the btrfs swapfile
+    pub(crate) fn swapfile_enabled(&self) -> bool {
+        self.swap_enabled && matches!(self.swap_mode, SwapMode::File { .. })
+    }
+
+    pub(crate) fn plymouth_disabled(&self) -> bool {
+        self.plymouth_theme.as_deref() == Some(PLYMOUTH_DISABLED)
+    }
+
+    // Terminus fonts come from terminus-font; the rest ship with kbd in base
+    fn console_font_package(&self) -> Option<&'static str> {
+        let font = self.console_font.as_deref()?;
+        font.starts_with("ter-").then_some("terminus-font")
+    }
+}
+
+// A partition kept from a previous install, optionally a btrfs subvolume on it; never formatted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExistingHome {
+    pub device: String,
+    pub subvolume: Option<String>,
+}
+
+// What backs swap when it is enabled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SwapMode {
+    #[default]
+    Zram, // Compressed swap in RAM sized by `zram_size`
+    // Swapfile in the @swap subvolume; allows hibernation
+    File {
+        size_mib: u64,
+    },
+}
+
+// What happens when optional packages fail to install
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FailurePolicy {
+    #[default]
+    Continue, // Log the failures and carry on with the install
+    Abort, // Stop the install so the selection can be fixed
+}
+
+impl FailurePolicy {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "continue" => Some(FailurePolicy::Continue),
+            "abort" => Some(FailurePolicy::Abort),
+            _ => None,
+        }
+    }
+}
+
+// A btrfs subvolume created in step 3 and where it gets mounted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubvolSpec {
+    pub name: String,
+    pub mountpoint: String,
+    pub options: String,
+}
+
+impl SubvolSpec {
+    pub fn new(name: &str, mountpoint: &str, options: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            mountpoint: mountpoint.to_string(),
+            options: options.to_string(),
+        }
+    }
+
+    // Mount target below /mnt
+    pub(crate) fn target(&self) -> String {
+        format!("/mnt{}", self.mountpoint.trim_end_matches('/'))
+    }
+}
+
+// Keyboard and touchpad defaults written to ~/.config/hypr/input.conf
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HyprInput {
+    pub kb_layout: String,
+    pub kb_variant: String, // Empty for the layout's default variant
+    pub repeat_rate: u32,   // Repeats per second
+    pub repeat_delay: u32,  // Milliseconds before a held key repeats
+    pub natural_scroll: bool,
+}
+
+// Hyprland's own defaults
+impl Default for HyprInput {
+    fn default() -> Self {
+        Self {
+            kb_layout: "us".to_string(),
+            kb_variant: String::new(),
+            repeat_rate: 25,
+            repeat_delay: 600,
+            natural_scroll: false,
+        }
+    }
+}
+
+// Login shell of the created user
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Shell {
+    #[default]
+    Zsh, // With the Nebula .zshrc and oh-my-zsh plugins from /etc/skel
+    Bash,
+    Fish,
+}
+
+impl Shell {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "zsh" => Some(Shell::Zsh),
+            "bash" => Some(Shell::Bash),
+            "fish" => Some(Shell::Fish),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Shell::Zsh => "zsh",
+            Shell::Bash => "bash",
+            Shell::Fish => "fish",
+        }
+    }
+
+    fn path(self) -> &'static str {
+        match self {
+            Shell::Zsh => "/bin/zsh",
+            Shell::Bash => "/bin/bash",
+            Shell::Fish => "/usr/bin/fish",
+        }
+    }
+
+    // Package pacstrap adds for the shell; bash already comes with base
+    fn package(self) -> Option<&'static str> {
+        match self {
+            Shell::Zsh => Some("zsh"),
+            Shell::Bash => None,
+            Shell::Fish => Some("fish"),
+        }
+    }
+}
+
+// On-disk LUKS format; GRUB can only unlock LUKS1, so full disk encryption requires it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LuksVersion {
+    Luks1,
+    #[default]
+    Luks2,
+}
+
+impl LuksVersion {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "luks1" | "1" => Some(LuksVersion::Luks1),
+            "luks2" | "2" => Some(LuksVersion::Luks2),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LuksVersion::Luks1 => "LUKS1",
+            LuksVersion::Luks2 => "LUKS2",
+        }
+    }
+
+    // Value for `cryptsetup luksFormat --type`
+    fn format_type(self) -> &'static str {
+        match self {
+            LuksVersion::Luks1 => "luks1",
+            LuksVersion::Luks2 => "luks2",
+        }
+    }
+}
+
+// Key derivation for luksFormat; empty fields keep cryptsetup's defaults (argon2id on LUKS2)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LuksPbkdf {
+    pub algorithm: Option<String>, // pbkdf2, argon2i or argon2id
+    pub memory_kib: Option<u32>,   // Argon2 memory cost, which the machine needs free at unlock
+}
+
+// Firewall set up on the installed system; none by default so nothing is blocked unexpectedly
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FirewallChoice {
+    #[default]
+    None,
+    Ufw,
+    Firewalld,
+}
+
+impl FirewallChoice {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "none" => Some(FirewallChoice::None),
+            "ufw" => Some(FirewallChoice::Ufw),
+            "firewalld" => Some(FirewallChoice::Firewalld),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            FirewallChoice::None => "None",
+            FirewallChoice::Ufw => "ufw",
+            FirewallChoice::Firewalld => "firewalld",
+        }
+    }
+
+    fn package(self) -> Option<&'static str> {
+        match self {
+            FirewallChoice::None => None,
+            FirewallChoice::Ufw => Some("ufw"),
+            FirewallChoice::Firewalld => Some("firewalld"),
+        }
+    }
+}
+
+// Console font presets; `large` is 32px Terminus so the TTY and LUKS prompt stay readable on 4K
+pub const CONSOLE_FONT_PRESETS: [(&str, Option<&str>); 2] =
+    [("normal", None), ("large", Some("ter-132n"))];
+
+// Font for a preset name or a console font name like `ter-124b`; the outer None means invalid
+pub fn parse_console_font(value: &str) -> Option<Option<String>> {
+    let value = value.trim();
+    if let Some((_, font)) = CONSOLE_FONT_PRESETS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(value))
+    {
+        return Some(font.map(str::to_string));
+    }
+    let valid = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    valid.then(|| Some(value.to_string()))
+}
+
+// sudo rules for the wheel group; the default asks for the password like stock sudo
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SudoPolicy {
+    pub nopasswd: bool,                 // wheel members run sudo without a password
+    pub timestamp_timeout: Option<i32>, // Minutes a password is remembered; -1 never expires
+    pub insults: bool,                  // Insult the user after a wrong password
+}
+
+// makepkg.conf overrides for AUR builds on the installed system
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MakepkgTuning {
+    pub parallel: bool, // make -j$(nproc) and multithreaded package compression
+    pub ccache: bool,   // Installs ccache and enables it in BUILDENV
+}
+
+impl Default for MakepkgTuning {
+    fn default() -> Self {
+        Self {
+            parallel: true,
+            ccache: false,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+// The layout the installer has always used: `@` on / and `@home` on /home
+pub fn default_subvolumes() -> Vec<SubvolSpec> {
+    vec![
+        SubvolSpec::new("@", "/", "compress=zstd"),
+        SubvolSpec::new("@home", "/home", "compress=zstd"),
+    ]
 }
 
 // Installation steps
@@ -64,7 +472,7 @@ pub const STEP_NAMES: [&str; 11] = [
     "Encrypting Disk",
     "Creating File System",
     "Mounting File System",
-    "Configuring Zram Swap",
+    "Configuring Swap",
     "Installing Base System",
     "Generating Fstab",
     "Configuring Base System",
@@ -74,16 +482,43 @@ pub const STEP_NAMES: [&str; 11] = [
 ];
 
 const STEP_COUNT: f64 = STEP_NAMES.len() as f64;
+// `plymouth_theme` value that leaves Plymouth out of the initramfs
+pub const PLYMOUTH_DISABLED: &str = "none";
+// Prefetch cache used when NEBULA_PREFETCH_PACKAGES is just switched on; /tmp is RAM on the
+// live ISO, so the download is checked against its free space first
+pub const DEFAULT_PACKAGE_CACHE_DIR: &str = "/tmp/nebula-pkgcache";
 pub(crate) const TMP_INSTALLER_LOG: &str = "/tmp/nebula-installer.log";
 pub(crate) const OFFLINE_PACMAN_CONF_PATH: &str = "/tmp/nebula-pacman.offline.conf";
 pub(crate) const TARGET_OFFLINE_PACMAN_CONF_PATH: &str = "/mnt/etc/pacman.offline.conf";
 pub(crate) const TARGET_HYBRID_PACMAN_CONF_PATH: &str = "/mnt/etc/pacman.hybrid.conf";
 pub(crate) const NEBULA_REPO_KEY_PATH: &str = "/usr/share/nebula/nebula-repo.gpg";
 
+// Last step that can still be cancelled; later steps touch the bootloader
+pub const LAST_CANCELLABLE_STEP: usize = 8;
+
 // The main entry point for the installer logic
+// Blocks until the install finishes; progress is reported on `tx` and always ends
+// with exactly one `InstallerEvent::Done`. Setting `cancel` stops before the next step
 pub fn run_installer(
     tx: crossbeam_channel::Sender<InstallerEvent>,
     config: &InstallConfig,
+    cancel: Arc<AtomicBool>,
+) -> Result<()> {
+    let result = run_install_steps(tx.clone(), config, &cancel);
+    if let Err(err) = &result {
+        if cancel.load(Ordering::Relaxed) {
+            send_event(&tx, InstallerEvent::Log("Cleaning up...".to_string()));
+            teardown_after_cancel(&tx, config.encrypt_disk);
+        }
+        let _ = tx.send(InstallerEvent::Done(Some(err.to_string())));
+    }
+    result
+}
+
+fn run_install_steps(
+    tx: crossbeam_channel::Sender<InstallerEvent>,
+    config: &InstallConfig,
+    cancel: &AtomicBool,
 ) -> Result<()> {
     let disk_path = config.disk.device_path();
     let efi_part = config.disk.partition_path(1);
@@ -99,10 +534,68 @@ pub fn run_installer(
         root_part.clone()
     };
     let offline_repo_available = Path::new("/opt/nebula-repo").exists();
-    let mut offline_repo_mounted = false;
+    preflight(&tx, config)?;
+    // Set once the live pacman is ready; holds whether the mirrorlist was ranked
+    let mut live_pacman_ranked: Option<bool> = None;
+    if config.resume_from > 0 {
+        remount_for_resume(&tx, config, &root_part, &root_device, &efi_part)?;
+    }
 
     // Step 0: Partition the disk
-    run_step(&tx, 0, || {
+    run_step(&tx, config, cancel, 0, || {
+        if let Some(home) = &config.existing_home {
+            if existing_home_disk(&home.device)? == config.disk.name {
+                anyhow::bail!("Existing /home {} is on the install disk", home.device);
+            }
+        }
+        // Downloads happen first so a network failure leaves the disk untouched
+        if let Some(cache_dir) = &config.package_cache {
+            if config.offline_only || offline_repo_available {
+                send_event(
+                    &tx,
+                    InstallerEvent::Log(
+                        "Offline repo in use; skipping package prefetch.".to_string(),
+                    ),
+                );
+            } else {
+                live_pacman_ranked = Some(prepare_live_pacman(&tx, config, false)?);
+                sync_prefetch_databases(&tx)?;
+                send_event(
+                    &tx,
+                    InstallerEvent::Log(format!("Prefetching packages into {}...", cache_dir)),
+                );
+                let base: Vec<String> = base_system_packages(&tx, config, false)?
+                    .into_iter()
+                    .map(str::to_string)
+                    .collect();
+                download_packages(&tx, cache_dir, &base)?;
+                let mut extra = config.base_packages.clone();
+                extra.extend(config.extra_pacman_packages.iter().cloned());
+                let missed = download_packages_best_effort(&tx, cache_dir, &dedup_packages(extra));
+                if !missed.is_empty() {
+                    send_event(
+                        &tx,
+                        InstallerEvent::Log(format!(
+                            "Not prefetched, will download during install: {}",
+                            missed.join(", ")
+                        )),
+                    );
+                }
+            }
+        }
+        if config.scan_bad_blocks {
+            scan_bad_blocks(&tx, &config.disk)?;
+        }
+        if config.secure_wipe {
+            send_event(
+                &tx,
+                InstallerEvent::Log(format!(
+                    "Securely wiping {}; this can take hours on large disks...",
+                    disk_path
+                )),
+            );
+            secure_wipe_disk(&tx, &config.disk, cancel)?;
+        }
         send_event(&tx, InstallerEvent::Log(format!("Wiping {}...", disk_path)));
         run_command(&tx, "wipefs", &["-af", &disk_path], None)?;
         run_command(&tx, "parted", &["-s", &disk_path, "mklabel", "gpt"], None)?;
@@ -124,27 +617,62 @@ pub fn run_installer(
             &["-s", &disk_path, "mkpart", root_label, "513MiB", "100%"],
             None,
         )?;
+        verify_partition(&tx, &config.disk, &efi_part)?;
+        verify_partition(&tx, &config.disk, &root_part)?;
         Ok(())
     })?;
 
     // Step 1: Encrypt the disk
     if config.encrypt_disk {
-        run_step(&tx, 1, || {
+        run_step(&tx, config, cancel, 1, || {
             send_event(&tx, InstallerEvent::Log("Setting up LUKS...".to_string()));
-            let luks_input = format!("{}\n{}\n", config.luks_password, config.luks_password);
-            run_command(
+            // GRUB can only unlock LUKS1, which the encrypted /boot needs
+            if config.full_disk_encryption && config.luks_version != LuksVersion::Luks1 {
+                anyhow::bail!("Full disk encryption needs LUKS1 so GRUB can unlock /boot");
+            }
+            validate_luks_pbkdf(config.luks_version, &config.luks_pbkdf)
+                .map_err(|err| anyhow::anyhow!("Invalid LUKS key derivation: {}", err))?;
+            let memory_arg = config.luks_pbkdf.memory_kib.map(|kib| kib.to_string());
+            let mut format_args = vec![
+                "luksFormat",
+                "--type",
+                config.luks_version.format_type(),
+                "--batch-mode",
+            ];
+            if let Some(algorithm) = &config.luks_pbkdf.algorithm {
+                format_args.extend(["--pbkdf", algorithm.as_str()]);
+            }
+            if let Some(memory) = &memory_arg {
+                format_args.extend(["--pbkdf-memory", memory.as_str()]);
+            }
+            send_event(
                 &tx,
-                "cryptsetup",
-                &["luksFormat", "--type", "luks2", "--batch-mode", &root_part],
-                Some(&luks_input),
-            )?;
+                InstallerEvent::Log(format!(
+                    "Formatting {} as {}",
+                    root_part,
+                    config.luks_version.label()
+                )),
+            );
+            let mut open_args = vec!["open", root_part.as_str(), "cryptroot"];
+            if let Some(header) = &config.luks_detached_header {
+                if config.full_disk_encryption {
+                    anyhow::bail!("Full disk encryption cannot use a detached LUKS header");
+                }
+                if luks_header_disk(header)? == config.disk.name {
+                    anyhow::bail!("Detached LUKS header {} is on the install disk", header);
+                }
+                send_event(
+                    &tx,
+                    InstallerEvent::Log(format!("Writing the LUKS header to {}...", header)),
+                );
+                format_args.extend(["--header", header.as_str()]);
+                open_args.extend(["--header", header.as_str()]);
+            }
+            format_args.push(&root_part);
+            let luks_input = format!("{}\n{}\n", config.luks_password, config.luks_password);
+            run_command(&tx, "cryptsetup", &format_args, Some(&luks_input))?;
             let open_input = format!("{}\n", config.luks_password);
-            run_command(
-                &tx,
-                "cryptsetup",
-                &["open", &root_part, "cryptroot"],
-                Some(&open_input),
-            )?;
+            run_command(&tx, "cryptsetup", &open_args, Some(&open_input))?;
             Ok(())
         })?;
     } else {
@@ -152,7 +680,7 @@ pub fn run_installer(
     }
 
     // Step 2: Create filesystems
-    run_step(&tx, 2, || {
+    run_step(&tx, config, cancel, 2, || {
         send_event(
             &tx,
             InstallerEvent::Log("Formatting filesystems...".to_string()),
@@ -163,113 +691,69 @@ pub fn run_installer(
     })?;
 
     // Step 3: Mount filesystems and create Btrfs subvolumes
-    run_step(&tx, 3, || {
+    run_step(&tx, config, cancel, 3, || {
         run_command(&tx, "mount", &[&root_device, "/mnt"], None)?;
-        run_command(&tx, "btrfs", &["subvolume", "create", "/mnt/@"], None)?;
-        run_command(&tx, "btrfs", &["subvolume", "create", "/mnt/@home"], None)?;
+        for subvol in &config.subvolumes {
+            let path = format!("/mnt/{}", subvol.name);
+            run_command(&tx, "btrfs", &["subvolume", "create", &path], None)?;
+        }
         run_command(&tx, "umount", &["/mnt"], None)?;
-        run_command(
-            &tx,
-            "mount",
-            &["-o", "subvol=@,compress=zstd", &root_device, "/mnt"],
-            None,
-        )?;
-        run_command(&tx, "mkdir", &["-p", "/mnt/home"], None)?;
-        run_command(
+        mount_subvolumes(
             &tx,
-            "mount",
-            &[
-                "-o",
-                "subvol=@home,compress=zstd",
-                &root_device,
-                "/mnt/home",
-            ],
-            None,
+            &config.subvolumes,
+            &root_device,
+            &efi_part,
+            config.esp_mountpoint(),
         )?;
-        run_command(&tx, "mkdir", &["-p", "/mnt/boot"], None)?;
-        run_command(&tx, "mount", &[&efi_part, "/mnt/boot"], None)?;
+        if let Some(home) = &config.existing_home {
+            mount_existing_home(&tx, home)?;
+        }
         Ok(())
     })?;
 
-    // Step 4: Configure zram swap
-    run_step(&tx, 4, || {
-        if config.swap_enabled {
-            send_event(
-                &tx,
-                InstallerEvent::Log("Configuring zram swap...".to_string()),
-            );
-            configure_zram()?;
-        } else {
+    // Step 4: Configure zram swap or the swapfile
+    run_step(&tx, config, cancel, 4, || {
+        if !config.swap_enabled {
             send_event(&tx, InstallerEvent::Log("Swap disabled.".to_string()));
+            return Ok(());
+        }
+        match config.swap_mode {
+            SwapMode::Zram => {
+                send_event(
+                    &tx,
+                    InstallerEvent::Log("Configuring zram swap...".to_string()),
+                );
+                configure_zram(&tx, &config.zram_size)?;
+            }
+            SwapMode::File { size_mib } => {
+                send_event(
+                    &tx,
+                    InstallerEvent::Log(format!("Creating a {} MiB swapfile...", size_mib)),
+                );
+                create_swapfile(&tx, size_mib)?;
+            }
         }
         Ok(())
     })?;
 
     // Step 5: Install the base system using pacstrap
-    run_step(&tx, 5, || {
+    run_step(&tx, config, cancel, 5, || {
         if config.offline_only && !offline_repo_available {
             anyhow::bail!("Offline repo not found at /opt/nebula-repo");
         }
         let use_offline_base = offline_repo_available || config.offline_only;
-        send_event(
-            &tx,
-            InstallerEvent::Log("Initializing pacman keyring...".to_string()),
-        );
-        run_command(&tx, "pacman-key", &["--init"], None)?;
-        run_command(&tx, "pacman-key", &["--populate", "archlinux"], None)?;
-        if use_offline_base {
-            send_event(
-                &tx,
-                InstallerEvent::Log(
-                    "Offline repo detected; using it for base system install.".to_string(),
-                ),
-            );
-        } else {
-            send_event(
-                &tx,
-                InstallerEvent::Log(
-                    "Setting pacman mirror to geo.mirror.pkgbuild.com...".to_string(),
-                ),
-            );
-            configure_mirrorlist("/etc/pacman.d/mirrorlist")?;
-        }
-
-        let mut packages = vec![
-            "base",
-            "linux-firmware",
-            "btrfs-progs",
-            "grub",
-            "efibootmgr",
-            "networkmanager",
-            "plymouth",
-            "sudo",
-            "vim",
-            "zram-generator",
-        ];
-        packages.push(config.kernel_package.as_str());
-        for pkg in &config.driver_packages {
-            if !packages.iter().any(|existing| existing == pkg) {
-                packages.push(pkg.as_str());
-            }
-        }
-        if config
-            .driver_packages
-            .iter()
-            .any(|pkg| pkg == "nvidia-dkms" || pkg == "nvidia-open-dkms")
-        {
-            packages.push(config.kernel_headers.as_str());
-        }
-        if let Some(ucode) = detect_microcode_package()? {
-            send_event(
-                &tx,
-                InstallerEvent::Log(format!("Detected CPU microcode: {}", ucode)),
-            );
-            packages.push(ucode);
-        }
+        let mirrors_ranked = match live_pacman_ranked {
+            Some(ranked) => ranked,
+            None => prepare_live_pacman(&tx, config, use_offline_base)?,
+        };
+
+        let packages = base_system_packages(&tx, config, use_offline_base)?;
         if use_offline_base {
-            write_offline_pacman_conf(OFFLINE_PACMAN_CONF_PATH)?;
+            write_offline_pacman_conf(OFFLINE_PACMAN_CONF_PATH, config.parallel_downloads)?;
+            check_offline_repo_freshness(&tx, &packages)?;
             validate_offline_base_package()?;
             validate_offline_packages(&packages)?;
+            verify_offline_packages(&tx, &packages)?;
         }
 
         let mut args = Vec::new();
@@ -282,18 +766,38 @@ pub fn run_installer(
             args.push(pkg.to_string());
         }
         let args_ref: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+        if let Some(cache_dir) = &config.package_cache {
+            seed_target_package_cache(&tx, cache_dir)?;
+        }
         send_event(
             &tx,
             InstallerEvent::Log("Downloading and installing packages...".to_string()),
         );
         run_pacstrap(&tx, &args_ref)?;
-        configure_mirrorlist("/mnt/etc/pacman.d/mirrorlist")?;
+        verify_pacstrap(&config.kernel_package)?;
+        configure_target_mirrorlist("/mnt/etc/pacman.d/mirrorlist", mirrors_ranked)?;
+        set_parallel_downloads("/mnt/etc/pacman.conf", config.parallel_downloads)?;
+        set_multilib("/mnt/etc/pacman.conf", config.enable_multilib)?;
         Ok(())
     })?;
 
     // Step 6: Generate fstab
-    run_step(&tx, 6, || {
-        let output = run_command_capture(&tx, "genfstab", &["-U", "/mnt"])?;
+    run_step(&tx, config, cancel, 6, || {
+        let mut output = run_command_capture(&tx, "genfstab", &["-U", "/mnt"])?;
+        if config.ssd_tuning {
+            let root_uuid = get_uuid(&tx, &root_device)?;
+            output = tune_fstab_for_ssd(&output, &root_uuid);
+            send_event(
+                &tx,
+                InstallerEvent::Log(
+                    "Added noatime and discard=async to the root mounts.".to_string(),
+                ),
+            );
+        }
+        // genfstab only lists active swap, and the swapfile is never turned on in the live system
+        if config.swapfile_enabled() {
+            output.push_str(&format!("{} none swap defaults 0 0\n", SWAPFILE_PATH));
+        }
         let mut file = OpenOptions::new()
             .create(true)
             .append(true)
@@ -304,7 +808,7 @@ pub fn run_installer(
     })?;
 
     // Step 7: Configure the installed system
-    run_step(&tx, 7, || {
+    run_step(&tx, config, cancel, 7, || {
         write_file("/mnt/etc/hostname", &format!("{}\n", config.hostname))?;
         write_file(
             "/mnt/etc/hosts",
@@ -313,10 +817,11 @@ pub fn run_installer(
                 config.hostname
             ),
         )?;
-        write_file(
-            "/mnt/etc/vconsole.conf",
-            &format!("KEYMAP={}\n", config.keymap),
-        )?;
+        let mut vconsole = format!("KEYMAP={}\n", config.keymap);
+        if let Some(font) = &config.console_font {
+            vconsole.push_str(&format!("FONT={}\n", font));
+        }
+        write_file("/mnt/etc/vconsole.conf", &vconsole)?;
 
         let tz_path = format!("/mnt/usr/share/zoneinfo/{}", config.timezone);
         if !std::path::Path::new(&tz_path).exists() {
@@ -333,41 +838,83 @@ pub fn run_installer(
             None,
         )?;
         run_chroot(&tx, &["hwclock", "--systohc"], None)?;
+        if let Some(server) = &config.ntp_server {
+            write_file(
+                "/mnt/etc/systemd/timesyncd.conf",
+                &format!("[Time]\nNTP={}\n", server),
+            )?;
+            send_event(
+                &tx,
+                InstallerEvent::Log(format!("Configured NTP server {}", server)),
+            );
+        }
         run_chroot(&tx, &["timedatectl", "set-ntp", "true"], None)?;
-        run_chroot(
-            &tx,
-            &[
-                "sed",
-                "-i",
-                "s/^#en_US.UTF-8 UTF-8/en_US.UTF-8 UTF-8/",
-                "/etc/locale.gen",
-            ],
-            None,
-        )?;
-        run_chroot(&tx, &["locale-gen"], None)?;
-        run_chroot(
-            &tx,
-            &["bash", "-c", "echo LANG=en_US.UTF-8 > /etc/locale.conf"],
-            None,
-        )?;
+        if let Some(country) = &config.wifi_country {
+            configure_wireless_regdom(&tx, country)?;
+        }
+        configure_locales(&tx, &config.locale)?;
 
         write_os_release()?;
         set_grub_distributor()?;
-        set_grub_gfx(&tx)?;
+        set_grub_gfx(&tx, config.display_resolution)?;
 
-        run_chroot(
-            &tx,
-            &[
-                "useradd",
-                "-m",
-                "-G",
-                "wheel",
-                "-s",
-                "/bin/zsh",
-                &config.username,
-            ],
-            None,
-        )?;
+        // A resumed install may have created the user before it was interrupted
+        if target_user_exists(&config.username) {
+            send_event(
+                &tx,
+                InstallerEvent::Log(format!(
+                    "User {} already exists; updating it.",
+                    config.username
+                )),
+            );
+            let mut usermod_args = vec!["usermod", "-a", "-G", "wheel", "-s", config.shell.path()];
+            if let Some(full_name) = &config.full_name {
+                usermod_args.extend(["-c", full_name.as_str()]);
+            }
+            usermod_args.push(&config.username);
+            run_chroot(&tx, &usermod_args, None)?;
+        } else {
+            let mut useradd_args = vec!["useradd", "-m", "-G", "wheel", "-s", config.shell.path()];
+            // Keep the UID that owns the preserved home so its files stay accessible
+            let existing_uid = config
+                .existing_home
+                .as_ref()
+                .and_then(|_| existing_home_uid(&config.username));
+            let uid_arg;
+            match existing_uid {
+                Some(uid) if uid >= 1000 && !passwd_has_uid(uid) => {
+                    send_event(
+                        &tx,
+                        InstallerEvent::Log(format!(
+                            "Reusing UID {} from the existing /home/{}",
+                            uid, config.username
+                        )),
+                    );
+                    uid_arg = uid.to_string();
+                    useradd_args.extend(["-u", uid_arg.as_str()]);
+                }
+                Some(uid) => send_event(
+                    &tx,
+                    InstallerEvent::Log(format!(
+                        "Warning: /home/{} is owned by UID {}, which cannot be reused; fix its ownership with chown -R after first boot",
+                        config.username, uid
+                    )),
+                ),
+                None if config.existing_home.is_some() => send_event(
+                    &tx,
+                    InstallerEvent::Log(format!(
+                        "Warning: the existing /home has no {} directory; creating a new one",
+                        config.username
+                    )),
+                ),
+                None => {}
+            }
+            if let Some(full_name) = &config.full_name {
+                useradd_args.extend(["-c", full_name.as_str()]);
+            }
+            useradd_args.push(&config.username);
+            run_chroot(&tx, &useradd_args, None)?;
+        }
         let pass_input = format!(
             "{}:{}
 ",
@@ -375,16 +922,7 @@ pub fn run_installer(
         );
         run_chroot(&tx, &["chpasswd"], Some(&pass_input))?;
         run_chroot(&tx, &["passwd", "-l", "root"], None)?;
-        run_chroot(
-            &tx,
-            &[
-                "sed",
-                "-i",
-                "s/^# %wheel ALL=(ALL:ALL) ALL/%wheel ALL=(ALL:ALL) ALL/",
-                "/etc/sudoers",
-            ],
-            None,
-        )?;
+        write_sudoers_dropin(&tx, &config.sudo_policy)?;
 
         let splash_theme_src = "/usr/share/plymouth/themes/nebula-splash";
         let luks_theme_src = "/usr/share/plymouth/themes/nebula-luks";
@@ -428,7 +966,9 @@ pub fn run_installer(
                     &["-a", luks_theme_src, "/mnt/usr/share/plymouth/themes/"],
                     None,
                 )?;
-                run_chroot(&tx, &["plymouth-set-default-theme", "nebula-luks"], None)?;
+                if !config.verbose_boot && config.plymouth_theme.is_none() {
+                    run_chroot(&tx, &["plymouth-set-default-theme", "nebula-luks"], None)?;
+                }
                 luks_installed = true;
             } else {
                 send_event(
@@ -439,25 +979,93 @@ pub fn run_installer(
                     )),
                 );
             }
-        } else if splash_installed {
+        } else if splash_installed && !config.verbose_boot && config.plymouth_theme.is_none() {
             run_chroot(&tx, &["plymouth-set-default-theme", "nebula-splash"], None)?;
         }
+        // A chosen theme goes in before mkinitcpio so the initramfs carries it
+        if let Some(theme) = config
+            .plymouth_theme
+            .as_deref()
+            .filter(|_| !config.verbose_boot && !config.plymouth_disabled())
+        {
+            set_plymouth_theme(&tx, theme)?;
+        }
+
+        if config.profile == InstallProfile::Minimal {
+            send_event(
+                &tx,
+                InstallerEvent::Log("Minimal profile; skipping GRUB and SDDM themes.".to_string()),
+            );
+        } else {
+            install_themes(&tx, config.display_resolution, config.display_scale)?;
+        }
 
-        install_grub_theme(&tx)?;
-        install_sddm_theme(&tx)?;
-
-        let hooks_line = if config.encrypt_disk {
-            "s/^HOOKS=.*/HOOKS=(base udev autodetect modconf block keyboard keymap plymouth encrypt filesystems)/"
+        let plymouth_hook = if config.plymouth_disabled() {
+            ""
+        } else {
+            " plymouth"
+        };
+        let encrypt_hook = if config.encrypt_disk { " encrypt" } else { "" };
+        // consolefont loads FONT= early enough for the LUKS prompt
+        let font_hook = if config.console_font.is_some() {
+            " consolefont"
+        } else {
+            ""
+        };
+        // resume runs once encrypt has opened the root that holds the swapfile
+        let resume_hook = if config.swapfile_enabled() {
+            " resume"
         } else {
-            "s/^HOOKS=.*/HOOKS=(base udev autodetect modconf block keyboard keymap plymouth filesystems)/"
+            ""
         };
+        let hooks_line = format!(
+            "s/^HOOKS=.*/HOOKS=(base udev autodetect modconf block keyboard keymap{}{}{}{} filesystems)/",
+            font_hook, plymouth_hook, encrypt_hook, resume_hook
+        );
         run_chroot(
             &tx,
-            &["sed", "-i", hooks_line, "/etc/mkinitcpio.conf"],
+            &["sed", "-i", &hooks_line, "/etc/mkinitcpio.conf"],
             None,
         )?;
+        // The encrypt hook mounts a detached header's filesystem, so the initramfs needs its driver
+        let header_location = match &config.luks_detached_header {
+            Some(header) if config.encrypt_disk => Some(luks_header_location(&tx, header)?),
+            _ => None,
+        };
+        if let Some((_, header_fstype, _)) = &header_location {
+            let mkinitcpio_conf =
+                fs::read_to_string("/mnt/etc/mkinitcpio.conf").context("read mkinitcpio.conf")?;
+            if !mkinitcpio_array_contains(&mkinitcpio_conf, "MODULES", header_fstype) {
+                let modules_line = format!("s/^MODULES=(/MODULES=({} /", header_fstype);
+                run_chroot(
+                    &tx,
+                    &["sed", "-i", &modules_line, "/etc/mkinitcpio.conf"],
+                    None,
+                )?;
+            }
+        }
+        if config.encrypt_disk && config.full_disk_encryption {
+            add_luks_keyfile(&tx, &root_part, &config.luks_password)?;
+        }
         run_chroot(&tx, &["mkinitcpio", "-P"], None)?;
-        if config.encrypt_disk {
+        if config.verbose_boot {
+            send_event(
+                &tx,
+                InstallerEvent::Log(
+                    "Verbose boot selected; leaving the Plymouth theme unset.".to_string(),
+                ),
+            );
+        } else if config.plymouth_disabled() {
+            send_event(
+                &tx,
+                InstallerEvent::Log("Plymouth disabled; no boot splash theme set.".to_string()),
+            );
+        } else if let Some(theme) = &config.plymouth_theme {
+            send_event(
+                &tx,
+                InstallerEvent::Log(format!("Using the {} Plymouth theme.", theme)),
+            );
+        } else if config.encrypt_disk {
             if luks_installed {
                 run_chroot(&tx, &["plymouth-set-default-theme", "nebula-luks"], None)?;
             }
@@ -466,14 +1074,61 @@ pub fn run_installer(
         }
 
         if config.encrypt_disk {
-            let root_uuid = get_uuid(&tx, &root_part)?;
-            write_file(
-                "/mnt/etc/crypttab",
-                &format!("cryptroot UUID={} none luks\n", root_uuid),
-            )?;
-            update_grub_cmdline(&root_uuid)?;
+            if let Some((header_uuid, header_fstype, header_path)) = &header_location {
+                // Without a header on disk the partition has no LUKS UUID, so use the PARTUUID
+                let part_uuid = run_command_capture(
+                    &tx,
+                    "blkid",
+                    &["-s", "PARTUUID", "-o", "value", &root_part],
+                )?;
+                let crypt_device = format!("PARTUUID={}", part_uuid.trim());
+                write_file(
+                    "/mnt/etc/crypttab",
+                    &format!(
+                        "cryptroot {} none luks,header={}:UUID={}\n",
+                        crypt_device, header_path, header_uuid
+                    ),
+                )?;
+                let crypt_header =
+                    format!("UUID={}:{}:{}", header_uuid, header_fstype, header_path);
+                update_grub_cmdline(&crypt_device, Some(&crypt_header))?;
+            } else {
+                let root_uuid = get_uuid(&tx, &root_part)?;
+                write_file(
+                    "/mnt/etc/crypttab",
+                    &format!("cryptroot UUID={} none luks\n", root_uuid),
+                )?;
+                update_grub_cmdline(&format!("UUID={}", root_uuid), None)?;
+            }
+            if config.full_disk_encryption {
+                ensure_grub_cmdline_params(&[&format!("cryptkey=rootfs:{}", LUKS_KEYFILE)])?;
+                enable_grub_cryptodisk()?;
+            }
+        }
+        if let Some(param) = config.mitigations.kernel_param() {
+            ensure_grub_cmdline_params(&[param])?;
         }
-        if config.encrypt_disk && !luks_installed {
+        if config.swapfile_enabled() {
+            let params = swapfile_resume_params(&tx, &root_device)?;
+            let params: Vec<&str> = params.iter().map(String::as_str).collect();
+            ensure_grub_cmdline_params(&params)?;
+        }
+        if config.verbose_boot {
+            remove_grub_cmdline_params(&["quiet", "splash"])?;
+            // Arch also ships `quiet` in GRUB_CMDLINE_LINUX_DEFAULT
+            run_command(
+                &tx,
+                "sed",
+                &[
+                    "-i",
+                    "/^GRUB_CMDLINE_LINUX_DEFAULT=/s/\\bquiet\\b//",
+                    "/mnt/etc/default/grub",
+                ],
+                None,
+            )?;
+        } else if config.plymouth_disabled() {
+            remove_grub_cmdline_params(&["splash"])?;
+        } else if config.encrypt_disk && !luks_installed && config.plymouth_theme.is_none() {
             send_event(
                 &tx,
                 InstallerEvent::Log(
@@ -489,7 +1144,7 @@ pub fn run_installer(
     })?;
 
     // Step 8: Install additional packages
-    run_step(&tx, 8, || {
+    run_step(&tx, config, cancel, 8, || {
         send_event(
             &tx,
             InstallerEvent::Log("Installing selected apps and packages...".to_string()),
@@ -507,16 +1162,59 @@ pub fn run_installer(
             &["-rf", "/mnt/usr/share/plymouth/themes/nebula-luks"],
             None,
         )?;
-        let required_pacman_packages = dedup_packages(config.base_packages.clone());
+        let mut required_pacman_packages = config.base_packages.clone();
+        if let Some(helper) = config.aur_helper.package() {
+            required_pacman_packages.push(helper.to_string());
+            if config.makepkg.ccache {
+                required_pacman_packages.push("ccache".to_string());
+            }
+            write_makepkg_dropin(&tx, &config.makepkg)?;
+        }
+        let required_pacman_packages = dedup_packages(required_pacman_packages);
         let mut optional_packages = Vec::new();
         optional_packages.extend(config.extra_pacman_packages.iter().cloned());
-        optional_packages.extend(config.extra_aur_packages.iter().cloned());
+        if config.aur_helper == AurHelper::None {
+            if !config.extra_aur_packages.is_empty() {
+                send_event(
+                    &tx,
+                    InstallerEvent::Log(format!(
+                        "No AUR helper selected; skipping AUR packages: {}",
+                        config.extra_aur_packages.join(", ")
+                    )),
+                );
+            }
+        } else {
+            optional_packages.extend(config.extra_aur_packages.iter().cloned());
+        }
+        // Keep the helper in the required set only so it is installed exactly once
+        optional_packages.retain(|pkg| !required_pacman_packages.contains(pkg));
         let optional_packages = dedup_packages(optional_packages);
         let optional_needs_nebula_repo = optional_packages
             .iter()
-            .any(|pkg| pkg == "yay" || pkg == "yay-bin")
-            || !config.extra_aur_packages.is_empty();
+            .any(|pkg| config.aur_helper.matches_package(pkg))
+            || (config.aur_helper != AurHelper::None && !config.extra_aur_packages.is_empty());
 
+        // Nebula's own packages, the AUR helper and AUR picks only exist in the nebula repo
+        let needs_nebula_repo: Vec<&str> = required_pacman_packages
+            .iter()
+            .chain(optional_packages.iter())
+            .filter(|pkg| {
+                pkg.starts_with("nebula-")
+                    || config.aur_helper.matches_package(pkg)
+                    || config.extra_aur_packages.contains(pkg)
+            })
+            .map(String::as_str)
+            .collect();
+        let configure_nebula_repo = config.configure_nebula_repo || !needs_nebula_repo.is_empty();
+        if !config.configure_nebula_repo && configure_nebula_repo {
+            send_event(
+                &tx,
+                InstallerEvent::Log(format!(
+                    "The nebula repo was turned off, but {} come from it; configuring it anyway.",
+                    needs_nebula_repo.join(", ")
+                )),
+            );
+        }
         if config.offline_only && optional_needs_nebula_repo {
             send_event(
                 &tx,
@@ -533,20 +1231,23 @@ pub fn run_installer(
                 &["--bind", "/opt/nebula-repo", "/mnt/opt/nebula-repo"],
                 None,
             )?;
-            offline_repo_mounted = true;
-            write_offline_pacman_conf(TARGET_OFFLINE_PACMAN_CONF_PATH)?;
+            write_offline_pacman_conf(TARGET_OFFLINE_PACMAN_CONF_PATH, config.parallel_downloads)?;
             if !config.offline_only {
                 write_hybrid_pacman_conf(
                     TARGET_HYBRID_PACMAN_CONF_PATH,
-                    true,
+                    configure_nebula_repo,
+                    config.enable_multilib,
+                    config.parallel_downloads,
                 )?;
             }
         }
-        if offline_repo_available && Path::new(NEBULA_REPO_KEY_PATH).exists() {
-            import_nebula_repo_key(&tx)?;
-        }
-        if !config.offline_only || Path::new("/mnt/usr/share/nebula/nebula-repo.gpg").exists() {
-            ensure_nebula_repo_configured(&tx)?;
+        if configure_nebula_repo {
+            if offline_repo_available && Path::new(NEBULA_REPO_KEY_PATH).exists() {
+                import_nebula_repo_key(&tx)?;
+            }
+            if !config.offline_only || Path::new("/mnt/usr/share/nebula/nebula-repo.gpg").exists() {
+                ensure_nebula_repo_configured(&tx)?;
+            }
         }
         let mut system_db_synced = false;
         if !required_pacman_packages.is_empty() {
@@ -559,7 +1260,7 @@ pub fn run_installer(
             if required_conf.is_none() {
                 system_db_synced = true;
             }
-            install_pacman_packages(&tx, &required_pacman_packages, required_conf)?;
+            install_pacman_packages_with_retry(&tx, &required_pacman_packages, required_conf)?;
         }
         if !optional_packages.is_empty() {
             let optional_conf = if config.offline_only {
@@ -588,6 +1289,9 @@ pub fn run_installer(
                 append_temp_installer_log(
                     "Optional packages failed. See /var/log/nebula-failed-packages.txt",
                 );
+                if config.optional_failure_policy == FailurePolicy::Abort {
+                    anyhow::bail!("Optional packages failed to install: {}", failed.join(" "));
+                }
             }
         }
         if !config.offline_only && !system_db_synced {
@@ -607,17 +1311,24 @@ pub fn run_installer(
         }
 
         // Ensure the primary user gets the default .zshrc if it didn't exist at user creation time.
-        let zsh_setup_cmd = format!(
-            "if [ -f /etc/skel/.zshrc ] && [ ! -f /home/{0}/.zshrc ]; then \
-             cp /etc/skel/.zshrc /home/{0}/.zshrc; \
-             chown {0}:{0} /home/{0}/.zshrc; \
-             fi; \
-             if [ -d /etc/skel/.config/oh-my-zsh/custom/plugins ]; then \
-             mkdir -p /home/{0}/.config/oh-my-zsh/custom; \
-             cp -a -n /etc/skel/.config/oh-my-zsh/custom/plugins /home/{0}/.config/oh-my-zsh/custom/; \
-             chown -R {0}:{0} /home/{0}/.config/oh-my-zsh/custom; \
-             fi; \
-             if [ -d /etc/skel/.config/nvim ]; then \
+        if config.shell == Shell::Zsh {
+            let zsh_setup_cmd = format!(
+                "if [ -f /etc/skel/.zshrc ] && [ ! -f /home/{0}/.zshrc ]; then \
+                 cp /etc/skel/.zshrc /home/{0}/.zshrc; \
+                 chown {0}:{0} /home/{0}/.zshrc; \
+                 fi; \
+                 if [ -d /etc/skel/.config/oh-my-zsh/custom/plugins ]; then \
+                 mkdir -p /home/{0}/.config/oh-my-zsh/custom; \
+                 cp -a -n /etc/skel/.config/oh-my-zsh/custom/plugins /home/{0}/.config/oh-my-zsh/custom/; \
+                 chown -R {0}:{0} /home/{0}/.config/oh-my-zsh/custom; \
+                 fi",
+                config.username
+            );
+            run_chroot(&tx, &["bash", "-c", &zsh_setup_cmd], None)?;
+        }
+        // LazyVim files from /etc/skel, whatever the shell
+        let nvim_setup_cmd = format!(
+            "if [ -d /etc/skel/.config/nvim ]; then \
              mkdir -p /home/{0}/.config; \
              cp -a -n /etc/skel/.config/nvim /home/{0}/.config/; \
              chown -R {0}:{0} /home/{0}/.config/nvim; \
@@ -634,51 +1345,122 @@ pub fn run_installer(
              fi",
             config.username
         );
-        run_chroot(&tx, &["bash", "-c", &zsh_setup_cmd], None)?;
+        run_chroot(&tx, &["bash", "-c", &nvim_setup_cmd], None)?;
 
         Ok(())
     })?;
 
     // Step 9: Install the GRUB bootloader
-    run_step(&tx, 9, || {
-        run_chroot(
-            &tx,
-            &[
-                "grub-install",
-                "--target=x86_64-efi",
-                "--efi-directory=/boot",
-                "--bootloader-id=GRUB",
-            ],
-            None,
-        )?;
+    run_step(&tx, config, cancel, 9, || {
+        let efi_directory = format!("--efi-directory={}", config.esp_mountpoint());
+        let mut grub_args = vec![
+            "grub-install",
+            "--target=x86_64-efi",
+            efi_directory.as_str(),
+            "--bootloader-id=GRUB",
+        ];
+        // An image must boot in a fresh VM and must not touch the build host's NVRAM
+        if config.disk.is_image() {
+            grub_args.extend(["--removable", "--no-nvram"]);
+        }
+        run_chroot(&tx, &grub_args, None)?;
+        if let Some(password) = &config.grub_password {
+            send_event(
+                &tx,
+                InstallerEvent::Log("Setting GRUB menu password...".to_string()),
+            );
+            set_grub_password(&tx, password)?;
+        }
         run_chroot(&tx, &["grub-mkconfig", "-o", "/boot/grub/grub.cfg"], None)?;
         Ok(())
     })?;
 
     // Step 10: Finalize the installation
-    run_step(&tx, 10, || {
+    run_step(&tx, config, cancel, 10, || {
+        run_post_install_commands(&tx, config)?;
         run_chroot(&tx, &["systemctl", "enable", "NetworkManager"], None)?;
-        // Enable Bluetooth only when hardware is present
-        if run_chroot(
-            &tx,
-            &[
-                "bash",
-                "-c",
-                "if ls /sys/class/bluetooth/hci* >/dev/null 2>&1; then systemctl enable bluetooth; fi",
-            ],
-            None,
-        )
-        .is_err()
-        {
+        if config.ssd_tuning {
+            run_chroot(&tx, &["systemctl", "enable", "fstrim.timer"], None)?;
+        }
+        if config.enable_bluetooth {
+            run_chroot(&tx, &["systemctl", "enable", "bluetooth"], None)?;
+        } else {
             send_event(
                 &tx,
                 InstallerEvent::Log(
-                    "Failed to detect Bluetooth hardware; skipping bluetooth.service enable."
-                        .to_string(),
+                    "Bluetooth disabled; skipping bluetooth.service enable.".to_string(),
                 ),
             );
         }
-        if config.base_packages.iter().any(|pkg| pkg == "sddm") {
+        if config.enable_printing {
+            send_event(
+                &tx,
+                InstallerEvent::Log("Enabling printing services...".to_string()),
+            );
+            run_chroot(&tx, &["systemctl", "enable", "cups.socket"], None)?;
+            run_chroot(&tx, &["systemctl", "enable", "avahi-daemon"], None)?;
+        }
+        if config.enable_tlp {
+            run_chroot(&tx, &["systemctl", "enable", "tlp.service"], None)?;
+            // TLP manages radios itself and conflicts with systemd-rfkill
+            run_chroot(
+                &tx,
+                &[
+                    "systemctl",
+                    "mask",
+                    "systemd-rfkill.service",
+                    "systemd-rfkill.socket",
+                ],
+                None,
+            )?;
+        } else if config.enable_power_profiles {
+            run_chroot(
+                &tx,
+                &["systemctl", "enable", "power-profiles-daemon.service"],
+                None,
+            )?;
+        }
+        if config.enable_firewall != FirewallChoice::None {
+            configure_firewall(&tx, config.enable_firewall)?;
+        }
+        if config.enable_mdns {
+            if !config.enable_printing {
+                run_chroot(&tx, &["systemctl", "enable", "avahi-daemon"], None)?;
+            }
+            configure_mdns_nsswitch(&tx)?;
+        }
+        if config.enable_flatpak {
+            if config.offline_only || !is_network_ready().unwrap_or(false) {
+                send_event(
+                    &tx,
+                    InstallerEvent::Log(
+                        "No network available; skipping Flathub remote setup.".to_string(),
+                    ),
+                );
+            } else if let Err(err) = run_chroot(
+                &tx,
+                &[
+                    "flatpak",
+                    "remote-add",
+                    "--if-not-exists",
+                    "flathub",
+                    "https://dl.flathub.org/repo/flathub.flatpakrepo",
+                ],
+                None,
+            ) {
+                send_event(
+                    &tx,
+                    InstallerEvent::Log(format!("Failed to add Flathub remote: {}", err)),
+                );
+            }
+            schedule_flatpak_apps(&tx, &config.flatpak_apps)?;
+        }
+        if config.profile == InstallProfile::Minimal {
+            send_event(
+                &tx,
+                InstallerEvent::Log("Minimal profile; skipping SDDM enable.".to_string()),
+            );
+        } else if config.base_packages.iter().any(|pkg| pkg == "sddm") {
             run_chroot(&tx, &["systemctl", "enable", "sddm"], None)?;
         } else {
             send_event(
@@ -693,6 +1475,7 @@ pub fn run_installer(
                 "Hyprland (Nebula)" => {
                     install_nebula_hypr(&tx, &config.username)?;
                     configure_hypr_monitors(&tx, &config.username)?;
+                    configure_hypr_input(&tx, &config.username, &config.hypr_input)?;
                     schedule_nebula_theme(&tx, &config.username)?;
                     schedule_nebula_init(&tx, &config.username)?;
                 }
@@ -704,10 +1487,23 @@ pub fn run_installer(
                         &config.selected_editors,
                     )?;
                     configure_hypr_monitors(&tx, &config.username)?;
+                    configure_hypr_input(&tx, &config.username, &config.hypr_input)?;
                     schedule_caelestia_init(&tx, &config.username)?;
                 }
             }
         }
+        if config.enable_fingerprint {
+            configure_fingerprint_pam(&tx)?;
+            schedule_fingerprint_reminder(&tx, &config.username)?;
+        }
+        if config.copy_installer {
+            if let Err(err) = copy_installer_to_target(&tx) {
+                send_event(
+                    &tx,
+                    InstallerEvent::Log(format!("Failed to copy the installer: {}", err)),
+                );
+            }
+        }
         let home_config = format!("/home/{}/.config", config.username);
         let home_local = format!("/home/{}/.local", config.username);
         let home_owner = format!("{}:{}", config.username, config.username);
@@ -731,30 +1527,294 @@ pub fn run_installer(
                 InstallerEvent::Log(format!("xdg-user-dirs-update failed: {}", err)),
             );
         }
-        copy_installer_log(&tx);
-        run_command(&tx, "sync", &[], None)?;
-        if offline_repo_mounted {
-            run_command(&tx, "umount", &["/mnt/opt/nebula-repo"], None)?;
+        if let Err(err) = write_install_report(config) {
+            send_event(
+                &tx,
+                InstallerEvent::Log(format!("Failed to write install report: {}", err)),
+            );
         }
-        run_command(&tx, "umount", &["-R", "/mnt"], None)?;
-        if config.encrypt_disk {
-            close_cryptroot_with_retries(&tx);
+        clear_install_state();
+        copy_installer_log(&tx, config.compress_logs);
+        run_command(&tx, "sync", &[], None)?;
+        if config.keep_target_mounted {
+            send_event(
+                &tx,
+                InstallerEvent::Log("Leaving /mnt mounted until the installer exits.".to_string()),
+            );
+            return Ok(());
         }
-        Ok(())
+        release_target(&tx, config.encrypt_disk)
     })?;
 
     send_event(&tx, InstallerEvent::Done(None));
     Ok(())
 }
 
+// Whether the installed system is still mounted on /mnt
+pub fn target_mounted() -> bool {
+    is_mountpoint("/mnt")
+}
+
+// Unmounts the installed system and closes its LUKS mapping
+pub fn release_target(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    encrypt_disk: bool,
+) -> Result<()> {
+    if is_mountpoint("/mnt/opt/nebula-repo") {
+        run_command(tx, "umount", &["/mnt/opt/nebula-repo"], None)?;
+    }
+    run_command(tx, "umount", &["-R", "/mnt"], None)?;
+    if encrypt_disk {
+        close_cryptroot_with_retries(tx);
+    }
+    Ok(())
+}
+
+// Initializes the live keyring and mirrorlist; returns whether reflector ranked the mirrors
+fn prepare_live_pacman(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    config: &InstallConfig,
+    use_offline_base: bool,
+) -> Result<bool> {
+    send_event(
+        tx,
+        InstallerEvent::Log("Initializing pacman keyring...".to_string()),
+    );
+    run_command(tx, "pacman-key", &["--init"], None)?;
+    run_command(tx, "pacman-key", &["--populate", "archlinux"], None)?;
+    // Ranked even for offline installs so first-boot updates on the target are fast
+    let mut mirrors_ranked = false;
+    if config.rank_mirrors && !config.offline_only && is_network_ready().unwrap_or(false) {
+        send_event(
+            tx,
+            InstallerEvent::Log("Ranking pacman mirrors with reflector...".to_string()),
+        );
+        mirrors_ranked = rank_mirrorlist(tx);
+    }
+    if use_offline_base {
+        send_event(
+            tx,
+            InstallerEvent::Log(
+                "Offline repo detected; using it for base system install.".to_string(),
+            ),
+        );
+    } else {
+        if !mirrors_ranked {
+            send_event(
+                tx,
+                InstallerEvent::Log(
+                    "Setting pacman mirror to geo.mirror.pkgbuild.com...".to_string(),
+                ),
+            );
+        }
+        configure_mirrorlist("/etc/pacman.d/mirrorlist", mirrors_ranked)?;
+        set_parallel_downloads("/etc/pacman.conf", config.parallel_downloads)?;
+    }
+    Ok(mirrors_ranked)
+}
+
+// Resolves the firmware choice, falling back to the full linux-firmware when a split is missing
+fn firmware_for_install(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    choice: FirmwareChoice,
+    use_offline_base: bool,
+) -> Vec<&'static str> {
+    let gpu_vendors = detect_gpu_vendors().unwrap_or_default();
+    let packages = firmware_packages(choice, &gpu_vendors);
+    match choice {
+        FirmwareChoice::Full => return packages,
+        FirmwareChoice::Minimal => {
+            send_event(
+                tx,
+                InstallerEvent::Log("Minimal firmware; skipping linux-firmware.".to_string()),
+            );
+            return packages;
+        }
+        FirmwareChoice::VendorOnly => {}
+    }
+    let fallback = if packages.is_empty() {
+        Some("No firmware vendor recognized".to_string())
+    } else {
+        packages
+            .iter()
+            .find(|pkg| !package_installable(tx, pkg, use_offline_base))
+            .map(|pkg| format!("{} is not available", pkg))
+    };
+    match fallback {
+        Some(reason) => {
+            send_event(
+                tx,
+                InstallerEvent::Log(format!(
+                    "Warning: {}; installing the full linux-firmware instead.",
+                    reason
+                )),
+            );
+            firmware_packages(FirmwareChoice::Full, &gpu_vendors)
+        }
+        None => {
+            send_event(
+                tx,
+                InstallerEvent::Log(format!("Installing firmware: {}", packages.join(", "))),
+            );
+            packages
+        }
+    }
+}
+
+// Packages pacstrap installs into the new root
+fn base_system_packages<'a>(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    config: &'a InstallConfig,
+    use_offline_base: bool,
+) -> Result<Vec<&'a str>> {
+    let mut packages = vec![
+        "base",
+        "btrfs-progs",
+        "grub",
+        "efibootmgr",
+        "networkmanager",
+        "plymouth",
+        "sudo",
+        "vim",
+        "zram-generator",
+    ];
+    packages.push(config.kernel_package.as_str());
+    packages.extend(config.shell.package());
+    packages.extend(config.console_font_package());
+    packages.extend(config.enable_firewall.package());
+    if config.wifi_country.is_some() {
+        packages.push("wireless-regdb");
+    }
+    packages.extend(firmware_for_install(tx, config.firmware, use_offline_base));
+    for pkg in &config.driver_packages {
+        if !packages.iter().any(|existing| existing == pkg) {
+            packages.push(pkg.as_str());
+        }
+    }
+    if config
+        .driver_packages
+        .iter()
+        .any(|pkg| pkg == "nvidia-dkms" || pkg == "nvidia-open-dkms")
+    {
+        packages.push(config.kernel_headers.as_str());
+    }
+    // Microcode is nice to have; a repo without it should not fail pacstrap
+    if let Some(ucode) = detect_microcode_package()? {
+        send_event(
+            tx,
+            InstallerEvent::Log(format!("Detected CPU microcode: {}", ucode)),
+        );
+        if package_installable(tx, ucode, use_offline_base) {
+            packages.push(ucode);
+        } else {
+            send_event(
+                tx,
+                InstallerEvent::Log(format!(
+                    "Warning: {} is not available; continuing without CPU microcode.",
+                    ucode
+                )),
+            );
+        }
+    }
+    Ok(packages)
+}
+
+// Runs the user's post-install hooks inside the target system
+fn run_post_install_commands(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    config: &InstallConfig,
+) -> Result<()> {
+    for command in &config.post_install_commands {
+        send_event(
+            tx,
+            InstallerEvent::Log(format!("Running post-install hook: {}", command)),
+        );
+        if let Err(err) = run_chroot(tx, &["bash", "-c", command], None) {
+            if config.abort_on_hook_failure {
+                return Err(err.context(format!("post-install hook failed: {}", command)));
+            }
+            send_event(
+                tx,
+                InstallerEvent::Log(format!("Post-install hook failed: {}", err)),
+            );
+        }
+    }
+    Ok(())
+}
+
+// Live-system tools the steps shell out to; grub-install runs inside the target instead
+const REQUIRED_TOOLS: &[&str] = &[
+    "arch-chroot",
+    "pacstrap",
+    "genfstab",
+    "parted",
+    "wipefs",
+    "mkfs.fat",
+    "mkfs.btrfs",
+    "btrfs",
+    "blkid",
+];
+
+// Names every missing tool up front instead of failing with a spawn error mid-install
+fn preflight(tx: &crossbeam_channel::Sender<InstallerEvent>, config: &InstallConfig) -> Result<()> {
+    send_event(
+        tx,
+        InstallerEvent::Log("Checking for required tools...".to_string()),
+    );
+    let mut tools = REQUIRED_TOOLS.to_vec();
+    if config.encrypt_disk || config.secure_wipe {
+        tools.push("cryptsetup");
+    }
+    if config.scan_bad_blocks {
+        tools.push("badblocks");
+    }
+    let missing: Vec<&str> = tools
+        .into_iter()
+        .filter(|tool| !command_exists(tool))
+        .collect();
+    if !missing.is_empty() {
+        anyhow::bail!(
+            "Missing required tools: {}. arch-chroot, pacstrap and genfstab come from arch-install-scripts",
+            missing.join(", ")
+        );
+    }
+    Ok(())
+}
+
 fn run_step<F>(
     tx: &crossbeam_channel::Sender<InstallerEvent>,
+    config: &InstallConfig,
+    cancel: &AtomicBool,
     index: usize,
     action: F,
 ) -> Result<()>
 where
     F: FnOnce() -> Result<()>,
 {
+    // Once the bootloader step has begun the install always runs to the end
+    if index <= LAST_CANCELLABLE_STEP && cancel.load(Ordering::Relaxed) {
+        anyhow::bail!("Installation cancelled");
+    }
+
+    // Steps finished before an interruption are not run again
+    if index < config.resume_from {
+        send_event(
+            tx,
+            InstallerEvent::Log(format!("{} already completed.", STEP_NAMES[index])),
+        );
+        send_event(
+            tx,
+            InstallerEvent::Step {
+                index,
+                status: StepStatus::Done,
+                err: None,
+            },
+        );
+        let progress = (index as f64 + 1.0) / STEP_COUNT;
+        send_event(tx, InstallerEvent::Progress(progress));
+        return Ok(());
+    }
+
     send_event(
         tx,
         InstallerEvent::Step {
@@ -764,7 +1824,13 @@ where
         },
     );
 
-    if let Err(err) = action() {
+    start_step_log(index);
+    let result = action();
+    if let Err(err) = &result {
+        append_step_log(&format!("Error: {:#}", err));
+    }
+    finish_step_log();
+    if let Err(err) = result {
         send_event(
             tx,
             InstallerEvent::Step {
@@ -786,6 +1852,16 @@ where
     );
     let progress = (index as f64 + 1.0) / STEP_COUNT;
     send_event(tx, InstallerEvent::Progress(progress));
+
+    // The target is mounted from step 3 until the final step unmounts it
+    if (3..STEP_NAMES.len() - 1).contains(&index) {
+        if let Err(err) = save_install_state(config, index) {
+            send_event(
+                tx,
+                InstallerEvent::Log(format!("Failed to save install state: {}", err)),
+            );
+        }
+    }
     Ok(())
 }
 
@@ -804,5 +1880,51 @@ fn skip_step(tx: &crossbeam_channel::Sender<InstallerEvent>, index: usize) {
 }
 
 fn send_event(tx: &crossbeam_channel::Sender<InstallerEvent>, evt: InstallerEvent) {
+    if let InstallerEvent::Log(line) = &evt {
+        append_step_log(line);
+    }
     let _ = tx.try_send(evt);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resume_skips_completed_steps_and_reruns_step_seven() {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let config = InstallConfig {
+            resume_from: 7,
+            ..InstallConfig::default()
+        };
+        let cancel = AtomicBool::new(false);
+        let mut ran = Vec::new();
+        for index in 0..7 {
+            run_step(&tx, &config, &cancel, index, || {
+                ran.push(index);
+                Ok(())
+            })
+            .unwrap();
+        }
+        assert!(ran.is_empty());
+        // Failing keeps the test from saving install state under /mnt
+        let result = run_step(&tx, &config, &cancel, 7, || {
+            ran.push(7);
+            anyhow::bail!("stop")
+        });
+        assert!(result.is_err());
+        assert_eq!(ran, vec![7]);
+        let done: Vec<usize> = rx
+            .try_iter()
+            .filter_map(|event| match event {
+                InstallerEvent::Step {
+                    index,
+                    status: StepStatus::Done,
+                    ..
+                } => Some(index),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(done, (0..7).collect::<Vec<_>>());
+    }
+}
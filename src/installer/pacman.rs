@@ -2,66 +2,189 @@ use std::env;
 use std::fs;
 use std::path::Path;
 use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
 
 use anyhow::{Context, Result};
 
+use crate::mirrors::{rank_by_latency, render_mirrorlist};
 use crate::model::InstallerEvent;
 
-use super::commands::{run_chroot, run_chroot_stream, run_command, run_command_stream};
-use super::system::write_file;
+use super::commands::{
+    run_chroot, run_chroot_stream, run_command, run_command_capture, run_command_stream,
+};
+use super::system::{free_space_mib, write_file};
 use super::{send_event, NEBULA_REPO_KEY_PATH, OFFLINE_PACMAN_CONF_PATH};
 
-// Configures the pacman mirrorlist
-pub(crate) fn configure_mirrorlist(path: &str) -> Result<()> {
-    let contents = if let Ok(mirrorlist) = env::var("NEBULA_PACMAN_MIRRORLIST") {
-        let trimmed = mirrorlist.trim();
-        if trimmed.is_empty() {
-            String::new()
-        } else if trimmed.ends_with('\n') {
-            trimmed.to_string()
-        } else {
-            format!("{trimmed}\n")
-        }
-    } else if let Ok(mirror) = env::var("NEBULA_PACMAN_MIRROR") {
-        let trimmed = mirror.trim();
-        if trimmed.is_empty() {
-            String::new()
-        } else {
-            let base = trimmed.trim_end_matches('/');
+const DEFAULT_MIRRORLIST: &str = "Server = https://mirror.nebulalinux.com/stable/$repo/os/$arch\n";
+
+// The one [nebula] stanza written to every pacman.conf that uses the online repo
+const NEBULA_REPO_SECTION: &str = "[nebula]\nSigLevel = Required DatabaseOptional\nServer = https://pkgs.nebulalinux.com/stable/$arch\n";
+
+const OFFLINE_REPO_DIR: &str = "/opt/nebula-repo";
+const OFFLINE_REPO_DB_PATH: &str = "/opt/nebula-repo/nebula-offline.db";
+
+// Where reflector writes its ranked list so the live and target mirrorlists can both use it
+const RANKED_MIRRORLIST_PATH: &str = "/tmp/nebula-ranked-mirrorlist";
+
+// Custom mirrors from the environment; NEBULA_PACMAN_MIRRORLIST wins over NEBULA_PACMAN_MIRROR
+fn custom_mirrorlist() -> Option<String> {
+    if let Some(mirrorlist) = env::var("NEBULA_PACMAN_MIRRORLIST")
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+    {
+        return Some(format!("{}\n", mirrorlist.trim()));
+    }
+    env::var("NEBULA_PACMAN_MIRROR")
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+        .map(|mirror| {
+            let base = mirror.trim().trim_end_matches('/');
             format!("Server = {base}/$repo/os/$arch\n")
+        })
+}
+
+// Configures the live pacman mirrorlist: custom mirrors, else the ranked list, else the default
+pub(crate) fn configure_mirrorlist(path: &str, ranked: bool) -> Result<()> {
+    let contents = match custom_mirrorlist() {
+        Some(custom) => custom,
+        None if ranked => {
+            fs::read_to_string(RANKED_MIRRORLIST_PATH).context("read ranked mirrorlist")?
         }
-    } else {
-        concat!("Server = https://mirror.nebulalinux.com/stable/$repo/os/$arch\n",).to_string()
+        None => DEFAULT_MIRRORLIST.to_string(),
     };
     fs::write(path, contents).context("write mirrorlist")?;
     Ok(())
 }
 
+// Configures the installed system's mirrorlist: custom mirrors first, followed by the ranked list
+pub(crate) fn configure_target_mirrorlist(path: &str, ranked: bool) -> Result<()> {
+    let mut contents = custom_mirrorlist().unwrap_or_default();
+    if ranked {
+        if !contents.is_empty() {
+            contents.push('\n');
+        }
+        contents.push_str(
+            &fs::read_to_string(RANKED_MIRRORLIST_PATH).context("read ranked mirrorlist")?,
+        );
+    }
+    if contents.is_empty() {
+        contents = DEFAULT_MIRRORLIST.to_string();
+    }
+    fs::write(path, contents).context("write mirrorlist")?;
+    Ok(())
+}
+
+// Ranks mirrors with reflector, falling back to a latency probe; false means the static mirror
+pub(crate) fn rank_mirrorlist(tx: &crossbeam_channel::Sender<InstallerEvent>) -> bool {
+    let reflector_available = Command::new("reflector")
+        .arg("--version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false);
+    if !reflector_available {
+        send_event(
+            tx,
+            InstallerEvent::Log("reflector not installed; probing mirror latency.".to_string()),
+        );
+        return rank_mirrorlist_by_latency(tx);
+    }
+    match run_command(
+        tx,
+        "reflector",
+        &[
+            "--latest",
+            "20",
+            "--sort",
+            "rate",
+            "--protocol",
+            "https",
+            "--save",
+            RANKED_MIRRORLIST_PATH,
+        ],
+        None,
+    ) {
+        Ok(()) => true,
+        Err(err) => {
+            send_event(
+                tx,
+                InstallerEvent::Log(format!(
+                    "Mirror ranking failed ({}); probing mirror latency.",
+                    err
+                )),
+            );
+            rank_mirrorlist_by_latency(tx)
+        }
+    }
+}
+
+// Writes the ranked list from TCP connect times, returning false when no mirror answered
+fn rank_mirrorlist_by_latency(tx: &crossbeam_channel::Sender<InstallerEvent>) -> bool {
+    let ranked = rank_by_latency();
+    let Some((fastest, latency)) = ranked.first() else {
+        send_event(
+            tx,
+            InstallerEvent::Log("No mirror answered; using the static mirror.".to_string()),
+        );
+        return false;
+    };
+    if let Err(err) = fs::write(RANKED_MIRRORLIST_PATH, render_mirrorlist(&ranked)) {
+        send_event(
+            tx,
+            InstallerEvent::Log(format!(
+                "Failed to write ranked mirrorlist ({}); using the static mirror.",
+                err
+            )),
+        );
+        return false;
+    }
+    send_event(
+        tx,
+        InstallerEvent::Log(format!(
+            "Ranked {} mirrors by latency; fastest is {} ({} ms).",
+            ranked.len(),
+            fastest,
+            latency.as_millis()
+        )),
+    );
+    true
+}
+
 // Writes a pacman.conf file for offline installations
-pub(crate) fn write_offline_pacman_conf(path: &str) -> Result<()> {
-    let contents = concat!(
-        "[options]\n",
-        "HoldPkg     = pacman glibc\n",
-        "Architecture = auto\n",
-        "ParallelDownloads = 5\n",
-        "SigLevel = Required DatabaseOptional\n",
-        "LocalFileSigLevel = Optional\n",
-        "\n",
-        "[nebula-offline]\n",
-        "SigLevel = Optional TrustAll\n",
-        "Server = file:///opt/nebula-repo\n",
+pub(crate) fn write_offline_pacman_conf(path: &str, parallel_downloads: u8) -> Result<()> {
+    let contents = format!(
+        concat!(
+            "[options]\n",
+            "HoldPkg     = pacman glibc\n",
+            "Architecture = auto\n",
+            "ParallelDownloads = {}\n",
+            "SigLevel = Required DatabaseOptional\n",
+            "LocalFileSigLevel = Optional\n",
+            "\n",
+            "[nebula-offline]\n",
+            "SigLevel = Optional TrustAll\n",
+            "Server = file:///opt/nebula-repo\n",
+        ),
+        parallel_downloads
     );
     fs::write(path, contents).context("write offline pacman.conf")?;
     Ok(())
 }
 
 // Writes a pacman.conf file for offline-first installs (offline repo + online fallback)
-pub(crate) fn write_hybrid_pacman_conf(path: &str, include_nebula_repo: bool) -> Result<()> {
-    let mut contents = String::from(
+pub(crate) fn write_hybrid_pacman_conf(
+    path: &str,
+    include_nebula_repo: bool,
+    include_multilib: bool,
+    parallel_downloads: u8,
+) -> Result<()> {
+    let mut contents = format!(
         "[options]\n\
 HoldPkg     = pacman glibc\n\
 Architecture = auto\n\
-ParallelDownloads = 5\n\
+ParallelDownloads = {}\n\
 SigLevel = Required DatabaseOptional\n\
 LocalFileSigLevel = Optional\n\
 \n\
@@ -69,26 +192,89 @@ LocalFileSigLevel = Optional\n\
 SigLevel = Optional TrustAll\n\
 Server = file:///opt/nebula-repo\n\
 \n",
+        parallel_downloads
     );
     if include_nebula_repo {
-        contents.push_str(
-            "[nebula]\nSigLevel = Required DatabaseOptional\nServer = https://pkgs.nebulalinux.com/stable/$arch\n\n",
-        );
+        contents.push_str(NEBULA_REPO_SECTION);
+        contents.push('\n');
     }
     contents.push_str(
         "[core]\n\
 Include = /etc/pacman.d/mirrorlist\n\
 \n\
 [extra]\n\
-Include = /etc/pacman.d/mirrorlist\n\
-\n\
-[multilib]\n\
 Include = /etc/pacman.d/mirrorlist\n",
     );
+    if include_multilib {
+        contents.push_str("\n[multilib]\nInclude = /etc/pacman.d/mirrorlist\n");
+    }
     fs::write(path, contents).context("write hybrid pacman.conf")?;
     Ok(())
 }
 
+// Sets ParallelDownloads in an existing pacman.conf, adding it under [options] if missing
+pub(crate) fn set_parallel_downloads(path: &str, parallel_downloads: u8) -> Result<()> {
+    let existing = fs::read_to_string(path).with_context(|| format!("read {}", path))?;
+    let setting = format!("ParallelDownloads = {}", parallel_downloads);
+    let mut replaced = false;
+    let mut lines: Vec<String> = existing
+        .lines()
+        .map(|line| {
+            let trimmed = line.trim_start_matches('#').trim();
+            if !replaced && trimmed.starts_with("ParallelDownloads") {
+                replaced = true;
+                setting.clone()
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+    if !replaced {
+        if let Some(idx) = lines.iter().position(|line| line.trim() == "[options]") {
+            lines.insert(idx + 1, setting);
+        }
+    }
+    let mut contents = lines.join("\n");
+    contents.push('\n');
+    fs::write(path, contents).with_context(|| format!("write {}", path))?;
+    Ok(())
+}
+
+// Uncomments or comments out the [multilib] section of an existing pacman.conf
+pub(crate) fn set_multilib(path: &str, enabled: bool) -> Result<()> {
+    let existing = fs::read_to_string(path).with_context(|| format!("read {}", path))?;
+    let mut in_multilib = false;
+    let mut found = false;
+    let mut lines: Vec<String> = existing
+        .lines()
+        .map(|line| {
+            let bare = line.trim_start_matches('#').trim();
+            if bare.starts_with('[') {
+                in_multilib = bare == "[multilib]";
+                found |= in_multilib;
+            } else if !bare.starts_with("Include") {
+                return line.to_string();
+            }
+            if !in_multilib {
+                line.to_string()
+            } else if enabled {
+                bare.to_string()
+            } else {
+                format!("#{}", bare)
+            }
+        })
+        .collect();
+    if enabled && !found {
+        lines.push(String::new());
+        lines.push("[multilib]".to_string());
+        lines.push("Include = /etc/pacman.d/mirrorlist".to_string());
+    }
+    let mut contents = lines.join("\n");
+    contents.push('\n');
+    fs::write(path, contents).with_context(|| format!("write {}", path))?;
+    Ok(())
+}
+
 // Validates that the required packages
 pub(crate) fn validate_offline_packages(packages: &[&str]) -> Result<()> {
     let repo_path = Path::new("/opt/nebula-repo");
@@ -129,6 +315,187 @@ pub(crate) fn validate_offline_packages(packages: &[&str]) -> Result<()> {
     );
 }
 
+// Whether pacstrap can get `pkg`: from the offline repo, or from the live sync databases
+pub(crate) fn package_installable(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    pkg: &str,
+    offline: bool,
+) -> bool {
+    if offline {
+        return validate_offline_packages(&[pkg]).is_ok();
+    }
+    run_command(tx, "pacman", &["-Sy", "--noconfirm"], None).is_ok()
+        && run_command(tx, "pacman", &["-Si", pkg], None).is_ok()
+}
+
+// One package from the offline repo database
+#[derive(Default)]
+struct RepoDbEntry {
+    name: String,
+    filename: String,
+    sha256: String,
+}
+
+// Splits the concatenated `desc` files of a repo database into packages
+fn parse_repo_db(contents: &str) -> Vec<RepoDbEntry> {
+    let mut entries = Vec::new();
+    let mut current: Option<RepoDbEntry> = None;
+    let mut lines = contents.lines();
+    while let Some(line) = lines.next() {
+        let field = match line.trim() {
+            "%FILENAME%" | "%NAME%" | "%SHA256SUM%" => line.trim(),
+            _ => continue,
+        };
+        let value = lines.next().unwrap_or_default().trim().to_string();
+        // `desc` files start with %FILENAME%
+        if field == "%FILENAME%" {
+            entries.extend(current.take());
+            current = Some(RepoDbEntry::default());
+        }
+        let Some(entry) = current.as_mut() else {
+            continue;
+        };
+        match field {
+            "%FILENAME%" => entry.filename = value,
+            "%NAME%" => entry.name = value,
+            _ => entry.sha256 = value,
+        }
+    }
+    entries.extend(current);
+    entries
+}
+
+// Every package entry in the offline repo database
+fn read_offline_repo_db() -> Result<Vec<RepoDbEntry>> {
+    let output = Command::new("tar")
+        .args(["-xOf", OFFLINE_REPO_DB_PATH])
+        .output()
+        .context("read offline repo database")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!(
+            "Offline repo database {} is unreadable: {}",
+            OFFLINE_REPO_DB_PATH,
+            stderr.trim()
+        );
+    }
+    Ok(parse_repo_db(&String::from_utf8_lossy(&output.stdout)))
+}
+
+// Package name from a file name like `linux-6.9.1.arch1-1-x86_64.pkg.tar.zst`
+fn package_name_from_filename(filename: &str) -> Option<&str> {
+    let mut parts = filename.rsplitn(4, '-');
+    parts.nth(3)
+}
+
+// A package file the repo database does not list makes pacstrap report "target not found"
+// even though the file is there, so stale databases are reported before pacstrap runs
+pub(crate) fn check_offline_repo_freshness(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    packages: &[&str],
+) -> Result<()> {
+    let entries = read_offline_repo_db()?;
+    let db_modified = fs::metadata(OFFLINE_REPO_DB_PATH)
+        .and_then(|meta| meta.modified())
+        .context("read offline repo database mtime")?;
+    let mut newest_package = None;
+    let mut unlisted = Vec::new();
+    for entry in fs::read_dir(OFFLINE_REPO_DIR)
+        .context("read offline repo")?
+        .flatten()
+    {
+        let filename = entry.file_name().to_string_lossy().to_string();
+        if !filename.ends_with(".pkg.tar.zst") {
+            continue;
+        }
+        if let Ok(modified) = entry.metadata().and_then(|meta| meta.modified()) {
+            newest_package = newest_package.max(Some(modified));
+        }
+        if !entries.iter().any(|listed| listed.filename == filename) {
+            unlisted.push(filename);
+        }
+    }
+    if newest_package.is_some_and(|newest| newest > db_modified) {
+        send_event(
+            tx,
+            InstallerEvent::Log(format!(
+                "Warning: {} is older than the newest package file in {}",
+                OFFLINE_REPO_DB_PATH, OFFLINE_REPO_DIR
+            )),
+        );
+    }
+    if unlisted.is_empty() {
+        return Ok(());
+    }
+    unlisted.sort();
+    send_event(
+        tx,
+        InstallerEvent::Log(format!(
+            "Warning: offline repo database looks stale; {} package file(s) are not listed in it: {}",
+            unlisted.len(),
+            unlisted.join(", ")
+        )),
+    );
+    let needed: Vec<&str> = unlisted
+        .iter()
+        .filter_map(|filename| package_name_from_filename(filename))
+        .filter(|name| packages.contains(name))
+        .collect();
+    if !needed.is_empty() {
+        anyhow::bail!(
+            "Offline repo database is stale: {} exist in {} but are missing from {}; rebuild it with repo-add",
+            needed.join(", "),
+            OFFLINE_REPO_DIR,
+            OFFLINE_REPO_DB_PATH
+        );
+    }
+    Ok(())
+}
+
+// Checks each required offline package against the sha256 recorded in the repo database
+pub(crate) fn verify_offline_packages(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    packages: &[&str],
+) -> Result<()> {
+    send_event(
+        tx,
+        InstallerEvent::Log("Verifying offline package checksums...".to_string()),
+    );
+    let entries = read_offline_repo_db()?;
+    let mut corrupt = Vec::new();
+    for pkg in packages {
+        // Groups such as `base` have no entry of their own
+        let Some(entry) = entries
+            .iter()
+            .find(|entry| entry.name == *pkg && !entry.sha256.is_empty())
+        else {
+            continue;
+        };
+        let path = format!("/opt/nebula-repo/{}", entry.filename);
+        let actual = Command::new("sha256sum")
+            .arg(&path)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| {
+                String::from_utf8_lossy(&output.stdout)
+                    .split_whitespace()
+                    .next()
+                    .map(str::to_string)
+            });
+        if actual.as_deref() != Some(entry.sha256.as_str()) {
+            corrupt.push(entry.filename.clone());
+        }
+    }
+    if !corrupt.is_empty() {
+        anyhow::bail!(
+            "Offline repo has corrupt packages (checksum mismatch): {}",
+            corrupt.join(", ")
+        );
+    }
+    Ok(())
+}
+
 // Validates that the base package group
 pub(crate) fn validate_offline_base_package() -> Result<()> {
     let sync_status = Command::new("pacman")
@@ -186,21 +553,38 @@ pub(crate) fn install_optional_packages_best_effort(
     Ok(failed)
 }
 
+const FAILED_PACKAGES_LOG_PATH: &str = "/mnt/var/log/nebula-failed-packages.txt";
+const FAILED_PACKAGES_HEADER: &str = "Failed optional packages:";
+
 // Writes a log of failed optional packages to the installed system
 pub(crate) fn write_failed_packages_log(packages: &[String]) -> Result<()> {
     if packages.is_empty() {
         return Ok(());
     }
     fs::create_dir_all("/mnt/var/log").context("create log dir")?;
-    let mut contents = String::from("Failed optional packages:\n");
+    let mut contents = format!("{}\n", FAILED_PACKAGES_HEADER);
     for pkg in packages {
         contents.push_str(pkg);
         contents.push('\n');
     }
-    write_file("/mnt/var/log/nebula-failed-packages.txt", &contents)?;
+    write_file(FAILED_PACKAGES_LOG_PATH, &contents)?;
     Ok(())
 }
 
+// Reads back the failed optional packages written during package installation
+pub(crate) fn read_failed_packages_log() -> Vec<String> {
+    fs::read_to_string(FAILED_PACKAGES_LOG_PATH)
+        .map(|contents| {
+            contents
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && *line != FAILED_PACKAGES_HEADER)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 // Removes duplicate packages from a list
 pub(crate) fn dedup_packages(mut packages: Vec<String>) -> Vec<String> {
     let mut seen = Vec::new();
@@ -242,18 +626,48 @@ pub(crate) fn ensure_nebula_repo_configured(
         ],
         None,
     )?;
-    run_chroot(
-        tx,
-        &[
-            "bash",
-            "-c",
-            r"if ! grep -q '^\[nebula\]' /etc/pacman.conf; then sed -i '/^\[core\]/i [nebula]\nSigLevel = Required DatabaseOptional\nServer = https://pkgs.nebulalinux.com/stable/\$arch\n' /etc/pacman.conf; fi",
-        ],
-        None,
-    )?;
+    let path = "/mnt/etc/pacman.conf";
+    let existing = fs::read_to_string(path).with_context(|| format!("read {}", path))?;
+    if let Some(updated) = with_single_nebula_repo(&existing) {
+        write_file(path, &updated)?;
+        send_event(
+            tx,
+            InstallerEvent::Log("Configured the nebula repo in pacman.conf".to_string()),
+        );
+    }
     Ok(())
 }
 
+// pacman.conf with exactly one [nebula] section right before [core]; None if it already is
+fn with_single_nebula_repo(contents: &str) -> Option<String> {
+    let mut lines: Vec<&str> = Vec::new();
+    let mut in_nebula = false;
+    for line in contents.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            in_nebula = trimmed == "[nebula]";
+        }
+        if !in_nebula {
+            lines.push(line);
+        }
+    }
+    match lines.iter().position(|line| line.trim() == "[core]") {
+        Some(idx) => {
+            let section = NEBULA_REPO_SECTION.lines().chain([""]);
+            lines.splice(idx..idx, section);
+        }
+        None => {
+            if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+                lines.push("");
+            }
+            lines.extend(NEBULA_REPO_SECTION.lines());
+        }
+    }
+    let mut updated = lines.join("\n");
+    updated.push('\n');
+    (updated != contents).then_some(updated)
+}
+
 pub(crate) fn import_nebula_repo_key(tx: &crossbeam_channel::Sender<InstallerEvent>) -> Result<()> {
     fs::create_dir_all("/mnt/usr/share/nebula").context("create nebula key dir")?;
     run_command(
@@ -312,9 +726,87 @@ pub(crate) fn install_pacman_packages(
     )
 }
 
+// Same as `install_pacman_packages`, but retried when a mirror or the network flakes out
+pub(crate) fn install_pacman_packages_with_retry(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    packages: &[String],
+    pacman_conf: Option<&str>,
+) -> Result<()> {
+    with_network_retry(tx, "Package install", || {
+        install_pacman_packages(tx, packages, pacman_conf)
+    })
+}
+
+// Waits between attempts of a pacman transaction that failed on the network
+const NETWORK_RETRY_DELAYS_SECS: [u64; 3] = [2, 4, 8];
+
+// Substrings of pacman/curl errors caused by the network or a mirror
+const NETWORK_ERROR_PATTERNS: &[&str] = &[
+    "failed retrieving file",
+    "failed to synchronize",
+    "download library error",
+    "could not resolve",
+    "resolving timed out",
+    "connection timed out",
+    "connection refused",
+    "operation too slow",
+    "operation timed out",
+    "the requested url returned error",
+    "ssl",
+];
+
+// Runs a pacman transaction again after a short backoff while it fails on the network
+fn with_network_retry<F>(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    what: &str,
+    mut action: F,
+) -> Result<()>
+where
+    F: FnMut() -> Result<()>,
+{
+    let mut delays = NETWORK_RETRY_DELAYS_SECS.iter();
+    loop {
+        let err = match action() {
+            Ok(()) => return Ok(()),
+            Err(err) => err,
+        };
+        let Some(delay) = delays.next().filter(|_| is_network_error(&err)) else {
+            return Err(err);
+        };
+        send_event(
+            tx,
+            InstallerEvent::Log(format!(
+                "{} failed on a network error, retrying in {}s...",
+                what, delay
+            )),
+        );
+        thread::sleep(Duration::from_secs(*delay));
+    }
+}
+
+// Missing packages and a held lock will not fix themselves, so those are never retried
+fn is_network_error(err: &anyhow::Error) -> bool {
+    let details = format!("{:#}", err).to_lowercase();
+    if details.contains("target not found") || details.contains("unable to lock database") {
+        return false;
+    }
+    NETWORK_ERROR_PATTERNS
+        .iter()
+        .any(|pattern| details.contains(pattern))
+}
+
 pub(crate) fn sync_pacman_databases(
     tx: &crossbeam_channel::Sender<InstallerEvent>,
     pacman_conf: Option<&str>,
+) -> Result<()> {
+    with_network_retry(tx, "Database sync", || {
+        sync_pacman_databases_once(tx, pacman_conf)
+    })
+}
+
+fn sync_pacman_databases_once(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    pacman_conf: Option<&str>,
 ) -> Result<()> {
     let mut args = vec![
         "pacman".to_string(),
@@ -335,6 +827,147 @@ pub(crate) fn sync_pacman_databases(
     )
 }
 
+// Target cache that pacstrap and the chroot pacman both read before downloading
+const TARGET_PACKAGE_CACHE_DIR: &str = "/mnt/var/cache/pacman/pkg";
+
+// Empty package database for prefetching; the live system's own database would make pacman
+// skip every dependency the ISO already has installed, which the target still needs
+const PREFETCH_DB_PATH: &str = "/tmp/nebula-prefetch-db";
+
+// Syncs the prefetch database from scratch; must run before `download_packages`
+pub(crate) fn sync_prefetch_databases(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+) -> Result<()> {
+    if Path::new(PREFETCH_DB_PATH).exists() {
+        fs::remove_dir_all(PREFETCH_DB_PATH)
+            .with_context(|| format!("remove {}", PREFETCH_DB_PATH))?;
+    }
+    fs::create_dir_all(PREFETCH_DB_PATH).with_context(|| format!("create {}", PREFETCH_DB_PATH))?;
+    with_network_retry(tx, "Prefetch database sync", || {
+        run_command_stream(
+            tx,
+            "pacman",
+            &["-Sy", "--noconfirm", "--dbpath", PREFETCH_DB_PATH],
+            None,
+            Some("Syncing package databases..."),
+            Some(&[("PACMAN_COLOR", "never")]),
+        )
+    })
+}
+
+// Fails early when the full download would not fit in `cache_dir`, which is RAM by default
+fn check_prefetch_space(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    cache_dir: &str,
+    packages: &[String],
+) -> Result<()> {
+    let mut args = vec!["-Sp", "--dbpath", PREFETCH_DB_PATH, "--print-format", "%s"];
+    args.extend(packages.iter().map(|pkg| pkg.as_str()));
+    let sizes = run_command_capture(tx, "pacman", &args)?;
+    let needed_mib = sizes
+        .lines()
+        .filter_map(|line| line.trim().parse::<u64>().ok())
+        .sum::<u64>()
+        .div_ceil(1024 * 1024);
+    let avail_mib = free_space_mib(tx, cache_dir)?;
+    if needed_mib > avail_mib {
+        anyhow::bail!(
+            "Prefetching needs {} MiB but {} has {} MiB free; point NEBULA_PREFETCH_PACKAGES at a larger disk",
+            needed_mib,
+            cache_dir,
+            avail_mib
+        );
+    }
+    Ok(())
+}
+
+// Downloads packages and all their dependencies into `cache_dir` without installing them,
+// before the disk is touched
+pub(crate) fn download_packages(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    cache_dir: &str,
+    packages: &[String],
+) -> Result<()> {
+    if packages.is_empty() {
+        return Ok(());
+    }
+    fs::create_dir_all(cache_dir).with_context(|| format!("create {}", cache_dir))?;
+    check_prefetch_space(tx, cache_dir, packages)?;
+    with_network_retry(tx, "Package download", || {
+        let mut args = vec![
+            "-Sw",
+            "--noconfirm",
+            "--dbpath",
+            PREFETCH_DB_PATH,
+            "--cachedir",
+            cache_dir,
+        ];
+        args.extend(packages.iter().map(|pkg| pkg.as_str()));
+        run_command_stream(
+            tx,
+            "pacman",
+            &args,
+            None,
+            Some("Downloading packages..."),
+            Some(&[("PACMAN_COLOR", "never")]),
+        )
+    })
+}
+
+// Tries to download packages individually if the batch download fails; returns the misses
+pub(crate) fn download_packages_best_effort(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    cache_dir: &str,
+    packages: &[String],
+) -> Vec<String> {
+    if packages.is_empty() || download_packages(tx, cache_dir, packages).is_ok() {
+        return Vec::new();
+    }
+    let mut failed = Vec::new();
+    for pkg in packages {
+        if download_packages(tx, cache_dir, std::slice::from_ref(pkg)).is_err() {
+            failed.push(pkg.clone());
+        }
+    }
+    failed
+}
+
+// Moves prefetched packages into the target cache so pacstrap and pacman skip downloading them
+pub(crate) fn seed_target_package_cache(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    cache_dir: &str,
+) -> Result<()> {
+    let Ok(entries) = fs::read_dir(cache_dir) else {
+        return Ok(());
+    };
+    fs::create_dir_all(TARGET_PACKAGE_CACHE_DIR).context("create target package cache")?;
+    let mut copied = 0;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+        let target = Path::new(TARGET_PACKAGE_CACHE_DIR).join(entry.file_name());
+        fs::copy(&path, &target).with_context(|| format!("copy {}", path.display()))?;
+        copied += 1;
+    }
+    send_event(
+        tx,
+        InstallerEvent::Log(format!(
+            "Copied {} prefetched files to the target package cache.",
+            copied
+        )),
+    );
+    // The live cache usually sits in RAM, so free it once the target has a copy
+    if let Err(err) = fs::remove_dir_all(cache_dir) {
+        send_event(
+            tx,
+            InstallerEvent::Log(format!("Warning: failed to remove {}: {}", cache_dir, err)),
+        );
+    }
+    Ok(())
+}
+
 // Special handler for pacstrap, which can have weird output buffering
 pub(crate) fn run_pacstrap(
     tx: &crossbeam_channel::Sender<InstallerEvent>,
@@ -378,3 +1011,80 @@ pub(crate) fn run_pacstrap(
         Some(&[("SYSTEMD_OFFLINE", "1"), ("PACMAN_COLOR", "never")]),
     )
 }
+
+// Checks that pacstrap left a usable system behind before later steps depend on it
+pub(crate) fn verify_pacstrap(kernel_package: &str) -> Result<()> {
+    let kernel_image = format!("/mnt/boot/vmlinuz-{}", kernel_package);
+    let missing: Vec<&str> = ["/mnt/usr/bin/pacman", "/mnt/usr/bin/bash", &kernel_image]
+        .into_iter()
+        .filter(|path| !Path::new(path).exists())
+        .collect();
+    if !missing.is_empty() {
+        anyhow::bail!(
+            "pacstrap did not install a complete base system (missing {}). \
+A package download was probably interrupted; check the network and retry the install.",
+            missing.join(", ")
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPTIONS: &str = "[options]\nParallelDownloads = 5\n\n";
+    const CORE: &str = "[core]\nInclude = /etc/pacman.d/mirrorlist\n\n";
+    const EXTRA: &str = "[extra]\nInclude = /etc/pacman.d/mirrorlist\n";
+
+    fn expected() -> String {
+        format!("{}{}\n{}{}", OPTIONS, NEBULA_REPO_SECTION, CORE, EXTRA)
+    }
+
+    fn nebula_count(contents: &str) -> usize {
+        contents
+            .lines()
+            .filter(|line| line.trim() == "[nebula]")
+            .count()
+    }
+
+    #[test]
+    fn adds_missing_nebula_before_core() {
+        let contents = format!("{}{}{}", OPTIONS, CORE, EXTRA);
+        let updated = with_single_nebula_repo(&contents).unwrap();
+        assert_eq!(updated, expected());
+    }
+
+    #[test]
+    fn leaves_single_nebula_section_alone() {
+        assert_eq!(with_single_nebula_repo(&expected()), None);
+    }
+
+    #[test]
+    fn collapses_duplicate_nebula_sections() {
+        let stale = "[nebula]\nServer = https://old.example.com/$arch\n\n";
+        let contents = format!("{}{}{}{}{}", OPTIONS, stale, CORE, stale, EXTRA);
+        let updated = with_single_nebula_repo(&contents).unwrap();
+        assert_eq!(nebula_count(&updated), 1);
+        assert!(!updated.contains("old.example.com"));
+        assert!(updated.contains(NEBULA_REPO_SECTION));
+    }
+
+    #[test]
+    fn keeps_other_sections() {
+        let custom = "[custom]\nSigLevel = Optional\nServer = file:///srv/repo\n";
+        let contents = format!("{}{}{}{}", OPTIONS, CORE, EXTRA, custom);
+        let updated = with_single_nebula_repo(&contents).unwrap();
+        for section in [OPTIONS, CORE, EXTRA, custom] {
+            assert!(updated.contains(section.trim_end()), "{}", section);
+        }
+        assert_eq!(nebula_count(&updated), 1);
+    }
+
+    #[test]
+    fn appends_nebula_without_core() {
+        let updated = with_single_nebula_repo(OPTIONS.trim_end()).unwrap();
+        assert!(updated.ends_with(NEBULA_REPO_SECTION));
+        assert!(updated.starts_with("[options]\nParallelDownloads = 5\n\n[nebula]"));
+    }
+}
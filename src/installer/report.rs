@@ -0,0 +1,75 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use super::pacman::read_failed_packages_log;
+use super::system::write_file;
+use super::{InstallConfig, SwapMode};
+
+const INSTALL_REPORT_PATH: &str = "/mnt/var/log/nebula-install-report.json";
+
+// Structured summary of a finished install, written for auditing
+#[derive(Serialize)]
+pub struct InstallReport {
+    pub installer_version: String,
+    pub disk: String,
+    pub disk_model: String,
+    pub disk_size: String,
+    pub filesystem: String,
+    pub encrypted: bool,
+    pub zram_swap: bool,
+    pub swapfile_mib: Option<u64>,
+    pub kernels: Vec<String>,
+    pub driver_packages: Vec<String>,
+    pub package_counts: PackageCounts,
+    pub aur_helper: String,
+    pub offline_only: bool,
+    pub failed_optional_packages: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct PackageCounts {
+    pub base: usize,
+    pub drivers: usize,
+    pub extra_pacman: usize,
+    pub extra_aur: usize,
+    pub flatpak: usize,
+}
+
+impl InstallReport {
+    // Builds the report from the install configuration and the packages that failed to install
+    pub fn new(config: &InstallConfig, failed_optional_packages: Vec<String>) -> Self {
+        Self {
+            installer_version: env!("CARGO_PKG_VERSION").to_string(),
+            disk: config.disk.device_path(),
+            disk_model: config.disk.model.clone(),
+            disk_size: config.disk.size.clone(),
+            filesystem: "btrfs".to_string(),
+            encrypted: config.encrypt_disk,
+            zram_swap: config.swap_enabled && config.swap_mode == SwapMode::Zram,
+            swapfile_mib: match config.swap_mode {
+                SwapMode::File { size_mib } if config.swap_enabled => Some(size_mib),
+                _ => None,
+            },
+            kernels: vec![config.kernel_package.clone()],
+            driver_packages: config.driver_packages.clone(),
+            package_counts: PackageCounts {
+                base: config.base_packages.len(),
+                drivers: config.driver_packages.len(),
+                extra_pacman: config.extra_pacman_packages.len(),
+                extra_aur: config.extra_aur_packages.len(),
+                flatpak: config.flatpak_apps.len(),
+            },
+            aur_helper: config.aur_helper.label().to_string(),
+            offline_only: config.offline_only,
+            failed_optional_packages,
+        }
+    }
+}
+
+// Writes the install report to the target system's log directory
+pub(crate) fn write_install_report(config: &InstallConfig) -> Result<()> {
+    let report = InstallReport::new(config, read_failed_packages_log());
+    let mut contents = serde_json::to_string_pretty(&report).context("serialize install report")?;
+    contents.push('\n');
+    write_file(INSTALL_REPORT_PATH, &contents)
+}
@@ -0,0 +1,177 @@
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::disks::DiskInfo;
+use crate::model::InstallerEvent;
+
+use super::commands::run_command;
+use super::system::{
+    close_cryptroot_with_retries, is_mountpoint, mount_existing_home, mount_subvolumes,
+};
+use super::{send_event, InstallConfig};
+
+const INSTALL_STATE_DIR: &str = "/mnt/var/lib/nebula";
+const INSTALL_STATE_PATH: &str = "/mnt/var/lib/nebula/install-state.json";
+// Unencrypted copy on the ESP so an interrupted encrypted install can still be found
+const ESP_STATE_FILE: &str = "nebula-install-state.json";
+// The ESP is mounted on /efi instead of /boot with full disk encryption
+const ESP_MOUNTPOINTS: [&str; 2] = ["/mnt/boot", "/mnt/efi"];
+const RESUME_MOUNT_DIR: &str = "/tmp/nebula-resume-esp";
+
+// Progress of an interrupted install (secrets are never written to disk)
+#[derive(Serialize, Deserialize)]
+pub struct InstallState {
+    pub completed_step: usize,
+    pub grub_password_set: bool,
+    pub config: InstallConfig,
+}
+
+// Records the last completed installation step on the target system
+pub(crate) fn save_install_state(config: &InstallConfig, completed_step: usize) -> Result<()> {
+    let state = InstallState {
+        completed_step,
+        grub_password_set: config.grub_password.is_some(),
+        config: config.clone(),
+    };
+    let contents = serde_json::to_string_pretty(&state).context("serialize install state")?;
+    fs::create_dir_all(INSTALL_STATE_DIR).context("create install state dir")?;
+    fs::write(INSTALL_STATE_PATH, &contents).context("write install state")?;
+    let esp_dir = format!("/mnt{}", config.esp_mountpoint());
+    if Path::new(&esp_dir).exists() {
+        let esp_state_path = format!("{}/{}", esp_dir, ESP_STATE_FILE);
+        fs::write(esp_state_path, &contents).context("write ESP install state")?;
+    }
+    Ok(())
+}
+
+// Removes the install state once the installation has finished
+pub(crate) fn clear_install_state() {
+    let _ = fs::remove_file(INSTALL_STATE_PATH);
+    for esp_dir in ESP_MOUNTPOINTS {
+        let _ = fs::remove_file(format!("{}/{}", esp_dir, ESP_STATE_FILE));
+    }
+}
+
+// Looks for an interrupted install on the EFI partition of each disk
+pub fn find_resumable_install(disks: &[DiskInfo]) -> Option<InstallState> {
+    for disk in disks {
+        let esp = disk.partition_path(1);
+        if !Path::new(&esp).exists() {
+            continue;
+        }
+        let fstype = Command::new("blkid")
+            .args(["-s", "TYPE", "-o", "value", &esp])
+            .output()
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+            .unwrap_or_default();
+        if fstype != "vfat" {
+            continue;
+        }
+        if fs::create_dir_all(RESUME_MOUNT_DIR).is_err() {
+            return None;
+        }
+        let mounted = Command::new("mount")
+            .args(["-o", "ro", &esp, RESUME_MOUNT_DIR])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false);
+        if !mounted {
+            continue;
+        }
+        let contents = fs::read_to_string(Path::new(RESUME_MOUNT_DIR).join(ESP_STATE_FILE));
+        let _ = Command::new("umount").arg(RESUME_MOUNT_DIR).status();
+        let Ok(contents) = contents else {
+            continue;
+        };
+        if let Ok(state) = serde_json::from_str::<InstallState>(&contents) {
+            if state.config.disk.name == disk.name {
+                return Some(state);
+            }
+        }
+    }
+    None
+}
+
+// Re-opens and re-mounts the target filesystems before resuming an install
+pub(crate) fn remount_for_resume(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    config: &InstallConfig,
+    root_part: &str,
+    root_device: &str,
+    efi_part: &str,
+) -> Result<()> {
+    send_event(
+        tx,
+        InstallerEvent::Log("Re-mounting filesystems to resume the install...".to_string()),
+    );
+    if config.encrypt_disk && !Path::new(root_device).exists() {
+        let open_input = format!("{}\n", config.luks_password);
+        let mut open_args = vec!["open", root_part, "cryptroot"];
+        if let Some(header) = &config.luks_detached_header {
+            open_args.extend(["--header", header.as_str()]);
+        }
+        run_command(tx, "cryptsetup", &open_args, Some(&open_input))?;
+    }
+    mount_subvolumes(
+        tx,
+        &config.subvolumes,
+        root_device,
+        efi_part,
+        config.esp_mountpoint(),
+    )?;
+    if let Some(home) = &config.existing_home {
+        mount_existing_home(tx, home)?;
+    }
+
+    // Verify the mounts before touching the target system
+    let targets = config.subvolumes.iter().map(|subvol| subvol.target());
+    let esp_target = format!("/mnt{}", config.esp_mountpoint());
+    let home_target = config
+        .existing_home
+        .as_ref()
+        .map(|_| "/mnt/home".to_string());
+    for mountpoint in targets.chain([esp_target]).chain(home_target) {
+        if !is_mountpoint(&mountpoint) {
+            anyhow::bail!("{} is not mounted; cannot resume", mountpoint);
+        }
+    }
+    if !Path::new(INSTALL_STATE_PATH).exists() {
+        anyhow::bail!("Install state not found on the target; cannot resume");
+    }
+    Ok(())
+}
+
+// Unmounts the target and closes the crypt mapping after a cancelled install
+pub(crate) fn teardown_after_cancel(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    encrypt_disk: bool,
+) {
+    // A cancelled install should not be offered for resuming
+    clear_install_state();
+    if is_mountpoint("/mnt/opt/nebula-repo") {
+        if let Err(err) = run_command(tx, "umount", &["/mnt/opt/nebula-repo"], None) {
+            send_event(
+                tx,
+                InstallerEvent::Log(format!("Failed to unmount offline repo: {}", err)),
+            );
+        }
+    }
+    if is_mountpoint("/mnt") {
+        if let Err(err) = run_command(tx, "umount", &["-R", "/mnt"], None) {
+            send_event(
+                tx,
+                InstallerEvent::Log(format!("Failed to unmount /mnt: {}", err)),
+            );
+        }
+    }
+    if encrypt_disk && Path::new("/dev/mapper/cryptroot").exists() {
+        close_cryptroot_with_retries(tx);
+    }
+    send_event(tx, InstallerEvent::Log("Cleanup finished.".to_string()));
+}
@@ -1,18 +1,38 @@
 use std::fs;
-use std::path::Path;
-use std::process::Command;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result};
 
+use crate::config::config;
+use crate::disks::DiskInfo;
+use crate::locales::DEFAULT_LOCALE;
 use crate::model::InstallerEvent;
 use crate::monitors::render_hypr_monitors_conf;
 
-use super::commands::{run_chroot, run_command, run_command_capture};
-use super::send_event;
+use super::commands::{
+    run_chroot, run_command, run_command_capture, run_command_stream, step_log_path,
+};
+use super::{
+    send_event, ExistingHome, FirewallChoice, HyprInput, LuksPbkdf, LuksVersion, MakepkgTuning,
+    SubvolSpec, SudoPolicy, STEP_NAMES,
+};
 
 const WLR_RANDR_CACHE_PATH: &str = "/tmp/nebula-wlr-randr.txt";
+// cryptsetup's bounds for --pbkdf-memory
+const LUKS_PBKDF_MIN_MEMORY_KIB: u32 = 32;
+const LUKS_PBKDF_MAX_MEMORY_KIB: u32 = 4 * 1024 * 1024;
+// wlr-randr output loaded once per run; every later resolution lookup reuses it
+static WLR_RANDR_OUTPUT: OnceLock<Option<String>> = OnceLock::new();
+const WIPE_MAPPER_NAME: &str = "nebula-wipe";
+const BADBLOCKS_LIST_PATH: &str = "/tmp/nebula-badblocks.txt";
+// Keyfile in the initramfs so an encrypted /boot only asks for the passphrase once, in GRUB
+pub(crate) const LUKS_KEYFILE: &str = "/crypto_keyfile.bin";
 
 // Detects the CPU
 pub(crate) fn detect_microcode_package() -> Result<Option<&'static str>> {
@@ -30,14 +50,400 @@ pub(crate) fn detect_microcode_package() -> Result<Option<&'static str>> {
     Ok(None)
 }
 
+// Denies incoming and allows outgoing traffic, then enables the firewall service for the next
+// boot. Nothing is loaded into the running kernel, which belongs to the live system.
+pub(crate) fn configure_firewall(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    firewall: FirewallChoice,
+) -> Result<()> {
+    send_event(
+        tx,
+        InstallerEvent::Log(format!("Configuring the {} firewall...", firewall.label())),
+    );
+    match firewall {
+        FirewallChoice::None => return Ok(()),
+        FirewallChoice::Ufw => {
+            run_chroot(tx, &["ufw", "default", "deny", "incoming"], None)?;
+            run_chroot(tx, &["ufw", "default", "allow", "outgoing"], None)?;
+            // What `ufw enable` records, without touching the live system's netfilter rules
+            run_chroot(
+                tx,
+                &[
+                    "sed",
+                    "-i",
+                    "s/^ENABLED=.*/ENABLED=yes/",
+                    "/etc/ufw/ufw.conf",
+                ],
+                None,
+            )?;
+            run_chroot(tx, &["systemctl", "enable", "ufw.service"], None)?;
+        }
+        FirewallChoice::Firewalld => {
+            // The public zone rejects unsolicited incoming traffic and allows all outgoing
+            run_chroot(
+                tx,
+                &["firewall-offline-cmd", "--set-default-zone=public"],
+                None,
+            )?;
+            run_chroot(tx, &["systemctl", "enable", "firewalld.service"], None)?;
+        }
+    }
+    Ok(())
+}
+
+// Adds mdns_minimal to the hosts line of nsswitch.conf so <hostname>.local resolves
+pub(crate) fn configure_mdns_nsswitch(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+) -> Result<()> {
+    let path = "/mnt/etc/nsswitch.conf";
+    let contents = fs::read_to_string(path).context("read nsswitch.conf")?;
+    let mut changed = false;
+    let lines: Vec<String> = contents
+        .lines()
+        .map(|line| {
+            let Some(sources) = line.strip_prefix("hosts:") else {
+                return line.to_string();
+            };
+            // Already configured, e.g. when the step is re-run after a resume
+            if sources
+                .split_whitespace()
+                .any(|source| source.starts_with("mdns"))
+            {
+                return line.to_string();
+            }
+            let mut tokens: Vec<&str> = sources.split_whitespace().collect();
+            let index = tokens
+                .iter()
+                .position(|source| *source == "resolve" || *source == "dns")
+                .unwrap_or(tokens.len());
+            tokens.insert(index, "mdns_minimal [NOTFOUND=return]");
+            changed = true;
+            format!("hosts: {}", tokens.join(" "))
+        })
+        .collect();
+    if changed {
+        let mut updated = lines.join("\n");
+        updated.push('\n');
+        fs::write(path, updated).context("write nsswitch.conf")?;
+        send_event(
+            tx,
+            InstallerEvent::Log("Enabled mDNS host resolution in nsswitch.conf".to_string()),
+        );
+    }
+    Ok(())
+}
+
+// Uncomments the chosen locale (plus en_US.UTF-8 as a fallback) and makes it the system default
+pub(crate) fn configure_locales(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    locale: &str,
+) -> Result<()> {
+    let locale = if locale.trim().is_empty() {
+        DEFAULT_LOCALE
+    } else {
+        locale.trim()
+    };
+    let path = "/mnt/etc/locale.gen";
+    let contents = fs::read_to_string(path).context("read locale.gen")?;
+    let mut updated: String = contents
+        .lines()
+        .map(|line| {
+            let Some(entry) = line.strip_prefix('#') else {
+                return line.to_string();
+            };
+            match entry.split_whitespace().next() {
+                Some(name)
+                    if !entry.starts_with(char::is_whitespace)
+                        && (name == locale || name == DEFAULT_LOCALE) =>
+                {
+                    entry.to_string()
+                }
+                _ => line.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    updated.push('\n');
+    fs::write(path, updated).context("write locale.gen")?;
+    run_chroot(tx, &["locale-gen"], None)?;
+    write_file("/mnt/etc/locale.conf", &format!("LANG={}\n", locale))?;
+    send_event(
+        tx,
+        InstallerEvent::Log(format!("Set system locale to {}", locale)),
+    );
+    Ok(())
+}
+
+// Subvolume and path of the btrfs swapfile; kept out of @ so snapshots never include it
+pub const SWAP_SUBVOLUME: &str = "@swap";
+pub const SWAP_MOUNTPOINT: &str = "/swap";
+pub(crate) const SWAPFILE_PATH: &str = "/swap/swapfile";
+
+// Space a swapfile must leave free on the root filesystem for the system and packages
+const SWAPFILE_MIN_FREE_MIB: u64 = 20 * 1024;
+
+// Default swapfile size: the machine's RAM, so hibernation has room for a full image
+pub fn default_swapfile_size_mib() -> u64 {
+    total_memory_mib().unwrap_or(4096)
+}
+
+// Parses a swapfile size in MiB
+pub fn parse_swapfile_size(value: &str) -> Result<u64, &'static str> {
+    match value.trim().parse::<u64>() {
+        Ok(0) => Err("size must be greater than zero"),
+        Ok(size) => Ok(size),
+        Err(_) => Err("size must be a whole number of MiB"),
+    }
+}
+
+// Free space in MiB on the filesystem holding `path`
+pub(crate) fn free_space_mib(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    path: &str,
+) -> Result<u64> {
+    let avail = run_command_capture(tx, "df", &["--output=avail", "-BM", path])?;
+    avail
+        .lines()
+        .nth(1)
+        .and_then(|line| line.trim().trim_end_matches('M').parse().ok())
+        .context("parse df output")
+}
+
+// Creates a no-CoW swapfile in the @swap subvolume after checking it fits
+pub(crate) fn create_swapfile(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    size_mib: u64,
+) -> Result<()> {
+    let swap_dir = format!("/mnt{}", SWAP_MOUNTPOINT);
+    let avail_mib = free_space_mib(tx, &swap_dir)?;
+    if size_mib + SWAPFILE_MIN_FREE_MIB > avail_mib {
+        anyhow::bail!(
+            "A {} MiB swapfile does not fit: {} MiB free, {} MiB must stay free for the system",
+            size_mib,
+            avail_mib,
+            SWAPFILE_MIN_FREE_MIB
+        );
+    }
+    run_command(tx, "chattr", &["+C", &swap_dir], None)?;
+    let swapfile = format!("/mnt{}", SWAPFILE_PATH);
+    let size = format!("{}m", size_mib);
+    run_command(
+        tx,
+        "btrfs",
+        &["filesystem", "mkswapfile", "--size", &size, &swapfile],
+        None,
+    )?;
+    Ok(())
+}
+
+// Kernel parameters that let the initramfs resume from the swapfile
+pub(crate) fn swapfile_resume_params(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    root_device: &str,
+) -> Result<Vec<String>> {
+    let root_uuid = get_uuid(tx, root_device)?;
+    let swapfile = format!("/mnt{}", SWAPFILE_PATH);
+    let offset = run_command_capture(
+        tx,
+        "btrfs",
+        &["inspect-internal", "map-swapfile", "-r", &swapfile],
+    )?;
+    Ok(vec![
+        format!("resume=UUID={}", root_uuid),
+        format!("resume_offset={}", offset.trim()),
+    ])
+}
+
 // Writes the zram configuration file
-pub(crate) fn configure_zram() -> Result<()> {
-    let contents = "[zram0]\nzram-size = ram\n";
+pub(crate) fn configure_zram(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    zram_size: &str,
+) -> Result<()> {
+    let ram_mib = total_memory_mib().unwrap_or(0);
+    let effective = evaluate_zram_size(zram_size, ram_mib as f64)
+        .map_err(|err| anyhow::anyhow!("Invalid zram size '{}': {}", zram_size, err))?;
+    let contents = format!("[zram0]\nzram-size = {}\n", zram_size);
     fs::create_dir_all("/mnt/etc/systemd").context("create systemd dir")?;
     fs::write("/mnt/etc/systemd/zram-generator.conf", contents).context("write zram config")?;
+    send_event(
+        tx,
+        InstallerEvent::Log(format!(
+            "zram-size = {} (about {} MiB with {} MiB of RAM)",
+            zram_size, effective as u64, ram_mib
+        )),
+    );
+    Ok(())
+}
+
+// Total memory of the running system in MiB
+fn total_memory_mib() -> Option<u64> {
+    let meminfo = fs::read_to_string("/proc/meminfo").ok()?;
+    let line = meminfo.lines().find(|line| line.starts_with("MemTotal:"))?;
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib / 1024)
+}
+
+// LUKS1 only knows PBKDF2; the Argon2 memory cost must fit in half this machine's RAM,
+// or the initramfs cannot unlock the root at boot
+pub fn validate_luks_pbkdf(version: LuksVersion, pbkdf: &LuksPbkdf) -> Result<(), String> {
+    let algorithm = pbkdf.algorithm.as_deref();
+    if !matches!(algorithm, None | Some("pbkdf2" | "argon2i" | "argon2id")) {
+        return Err("use pbkdf2, argon2i or argon2id".to_string());
+    }
+    if version == LuksVersion::Luks1 && algorithm.is_some_and(|name| name != "pbkdf2") {
+        return Err("LUKS1 only supports pbkdf2".to_string());
+    }
+    let Some(memory_kib) = pbkdf.memory_kib else {
+        return Ok(());
+    };
+    if version == LuksVersion::Luks1 || algorithm == Some("pbkdf2") {
+        return Err("a memory cost needs argon2i or argon2id on LUKS2".to_string());
+    }
+    if memory_kib < LUKS_PBKDF_MIN_MEMORY_KIB {
+        return Err(format!(
+            "memory cost must be at least {} KiB",
+            LUKS_PBKDF_MIN_MEMORY_KIB
+        ));
+    }
+    let half_ram_kib = total_memory_mib().unwrap_or(1024) * 1024 / 2;
+    let limit_kib = half_ram_kib.min(u64::from(LUKS_PBKDF_MAX_MEMORY_KIB));
+    if u64::from(memory_kib) > limit_kib {
+        return Err(format!(
+            "memory cost of {} KiB is more than the {} KiB this machine can spare when unlocking",
+            memory_kib, limit_kib
+        ));
+    }
     Ok(())
 }
 
+// Checks a zram-size expression against the zram-generator syntax and this machine's RAM
+pub fn validate_zram_size(expr: &str) -> Result<(), &'static str> {
+    let ram_mib = total_memory_mib().unwrap_or(1024);
+    evaluate_zram_size(expr, ram_mib as f64).map(|_| ())
+}
+
+// Evaluates a zram-size expression: numbers in MiB, `ram`, + - * /, parentheses, min() and max()
+fn evaluate_zram_size(expr: &str, ram_mib: f64) -> Result<f64, &'static str> {
+    let mut parser = ZramSizeParser {
+        chars: expr.chars().filter(|ch| !ch.is_whitespace()).collect(),
+        pos: 0,
+        ram_mib,
+    };
+    if parser.chars.is_empty() {
+        return Err("size cannot be empty");
+    }
+    let value = parser.expr()?;
+    if parser.pos != parser.chars.len() {
+        return Err("unexpected character");
+    }
+    if value <= 0.0 || !value.is_finite() {
+        return Err("size must be greater than zero");
+    }
+    Ok(value)
+}
+
+struct ZramSizeParser {
+    chars: Vec<char>,
+    pos: usize,
+    ram_mib: f64,
+}
+
+impl ZramSizeParser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expr(&mut self) -> Result<f64, &'static str> {
+        let mut value = self.term()?;
+        loop {
+            if self.eat('+') {
+                value += self.term()?;
+            } else if self.eat('-') {
+                value -= self.term()?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn term(&mut self) -> Result<f64, &'static str> {
+        let mut value = self.factor()?;
+        loop {
+            if self.eat('*') {
+                value *= self.factor()?;
+            } else if self.eat('/') {
+                let divisor = self.factor()?;
+                if divisor == 0.0 {
+                    return Err("division by zero");
+                }
+                value /= divisor;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn factor(&mut self) -> Result<f64, &'static str> {
+        if self.eat('(') {
+            let value = self.expr()?;
+            return if self.eat(')') {
+                Ok(value)
+            } else {
+                Err("missing closing parenthesis")
+            };
+        }
+        match self.peek() {
+            Some(ch) if ch.is_ascii_digit() || ch == '.' => {
+                let start = self.pos;
+                while matches!(self.peek(), Some(ch) if ch.is_ascii_digit() || ch == '.') {
+                    self.pos += 1;
+                }
+                let number: String = self.chars[start..self.pos].iter().collect();
+                number.parse().map_err(|_| "invalid number")
+            }
+            Some(ch) if ch.is_ascii_alphabetic() => {
+                let start = self.pos;
+                while matches!(self.peek(), Some(ch) if ch.is_ascii_alphabetic()) {
+                    self.pos += 1;
+                }
+                let name: String = self.chars[start..self.pos].iter().collect();
+                match name.as_str() {
+                    "ram" => Ok(self.ram_mib),
+                    "min" | "max" => {
+                        if !self.eat('(') {
+                            return Err("expected ( after min/max");
+                        }
+                        let first = self.expr()?;
+                        if !self.eat(',') {
+                            return Err("min/max take two arguments");
+                        }
+                        let second = self.expr()?;
+                        if !self.eat(')') {
+                            return Err("missing closing parenthesis");
+                        }
+                        Ok(if name == "min" {
+                            first.min(second)
+                        } else {
+                            first.max(second)
+                        })
+                    }
+                    _ => Err("only ram, min() and max() are allowed"),
+                }
+            }
+            _ => Err("expected a number, ram, min() or max()"),
+        }
+    }
+}
+
 // Gets the UUID of a block device
 pub(crate) fn get_uuid(
     tx: &crossbeam_channel::Sender<InstallerEvent>,
@@ -47,6 +453,358 @@ pub(crate) fn get_uuid(
     Ok(output.trim().to_string())
 }
 
+// Mounts the subvolumes parents-first below /mnt, then the ESP on `esp_mountpoint` last
+pub(crate) fn mount_subvolumes(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    subvolumes: &[SubvolSpec],
+    root_device: &str,
+    efi_part: &str,
+    esp_mountpoint: &str,
+) -> Result<()> {
+    if !subvolumes.iter().any(|subvol| subvol.mountpoint == "/") {
+        anyhow::bail!("No btrfs subvolume is mounted at /");
+    }
+    let mut ordered: Vec<&SubvolSpec> = subvolumes.iter().collect();
+    ordered.sort_by_key(|subvol| subvol.target().matches('/').count());
+    for subvol in ordered {
+        let target = subvol.target();
+        if is_mountpoint(&target) {
+            continue;
+        }
+        let mut options = format!("subvol={}", subvol.name);
+        if !subvol.options.is_empty() {
+            options.push(',');
+            options.push_str(&subvol.options);
+        }
+        run_command(tx, "mkdir", &["-p", &target], None)?;
+        run_command(tx, "mount", &["-o", &options, root_device, &target], None)?;
+    }
+    let esp_target = format!("/mnt{}", esp_mountpoint);
+    if !is_mountpoint(&esp_target) {
+        run_command(tx, "mkdir", &["-p", &esp_target], None)?;
+        run_command(tx, "mount", &[efi_part, &esp_target], None)?;
+    }
+    Ok(())
+}
+
+// Parses NEBULA_EXISTING_HOME: a partition, optionally followed by `:subvolume`
+pub fn parse_existing_home(value: &str) -> Result<ExistingHome> {
+    let (device, subvolume) = match value.split_once(':') {
+        Some((device, subvolume)) => (device.trim(), Some(subvolume.trim())),
+        None => (value.trim(), None),
+    };
+    if !device.starts_with("/dev/") {
+        anyhow::bail!("{} is not a /dev path", device);
+    }
+    if !Path::new(device).exists() {
+        anyhow::bail!("{} does not exist", device);
+    }
+    Ok(ExistingHome {
+        device: device.to_string(),
+        subvolume: subvolume
+            .filter(|subvolume| !subvolume.is_empty())
+            .map(str::to_string),
+    })
+}
+
+// Disk holding the preserved /home, walking up through any mapper or LVM layers
+pub fn existing_home_disk(device: &str) -> Result<String> {
+    let output = Command::new("lsblk")
+        .args(["-nrs", "-o", "NAME,TYPE", device])
+        .output()
+        .context("run lsblk")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("lsblk {} failed: {}", device, stderr.trim());
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let disk = stdout.lines().find_map(|line| match line.split_once(' ') {
+        Some((name, "disk")) => Some(name.to_string()),
+        _ => None,
+    });
+    match disk {
+        Some(disk) if format!("/dev/{}", disk) != device => Ok(disk),
+        _ => anyhow::bail!("{} is not a partition", device),
+    }
+}
+
+// Mounts the preserved /home as-is; nothing on it is ever formatted
+pub(crate) fn mount_existing_home(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    home: &ExistingHome,
+) -> Result<()> {
+    if is_mountpoint("/mnt/home") {
+        return Ok(());
+    }
+    send_event(
+        tx,
+        InstallerEvent::Log(format!(
+            "Mounting the existing /home from {}...",
+            home.device
+        )),
+    );
+    run_command(tx, "mkdir", &["-p", "/mnt/home"], None)?;
+    match &home.subvolume {
+        Some(subvolume) => {
+            let options = format!("subvol={}", subvolume);
+            run_command(
+                tx,
+                "mount",
+                &["-o", &options, &home.device, "/mnt/home"],
+                None,
+            )
+        }
+        None => run_command(tx, "mount", &[&home.device, "/mnt/home"], None),
+    }
+}
+
+// Owner of the user's directory on the preserved /home, if there is one
+pub(crate) fn existing_home_uid(username: &str) -> Option<u32> {
+    use std::os::unix::fs::MetadataExt;
+
+    fs::metadata(format!("/mnt/home/{}", username))
+        .ok()
+        .filter(|meta| meta.is_dir())
+        .map(|meta| meta.uid())
+}
+
+// Whether an account in the target already uses the UID
+pub(crate) fn passwd_has_uid(uid: u32) -> bool {
+    let passwd = fs::read_to_string("/mnt/etc/passwd").unwrap_or_default();
+    passwd
+        .lines()
+        .any(|line| line.split(':').nth(2) == Some(uid.to_string().as_str()))
+}
+
+// Swaps relatime for noatime and adds discard=async on the root device's btrfs mounts
+pub(crate) fn tune_fstab_for_ssd(fstab: &str, root_uuid: &str) -> String {
+    let root_source = format!("UUID={}", root_uuid);
+    let mut tuned = String::new();
+    for line in fstab.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 4 || fields[0] != root_source || fields[2] != "btrfs" {
+            tuned.push_str(line);
+            tuned.push('\n');
+            continue;
+        }
+        let mut options: Vec<&str> = fields[3]
+            .split(',')
+            .filter(|option| !matches!(*option, "relatime" | "strictatime" | "discard"))
+            .collect();
+        for extra in ["noatime", "discard=async"] {
+            if !options.contains(&extra) {
+                options.push(extra);
+            }
+        }
+        let options = options.join(",");
+        let mut updated = fields.clone();
+        updated[3] = &options;
+        tuned.push_str(&updated.join("\t"));
+        tuned.push('\n');
+    }
+    tuned
+}
+
+pub(crate) fn is_mountpoint(path: &str) -> bool {
+    Command::new("mountpoint")
+        .args(["-q", path])
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+// Adds a random keyfile to the LUKS root and bundles it into the initramfs
+pub(crate) fn add_luks_keyfile(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    root_part: &str,
+    luks_password: &str,
+) -> Result<()> {
+    send_event(
+        tx,
+        InstallerEvent::Log("Adding a LUKS keyfile to the initramfs...".to_string()),
+    );
+    let keyfile = format!("/mnt{}", LUKS_KEYFILE);
+    // A resumed install may already have enrolled the keyfile in a keyslot
+    let enrolled = Path::new(&keyfile).exists()
+        && Command::new("cryptsetup")
+            .args([
+                "open",
+                "--test-passphrase",
+                "--key-file",
+                &keyfile,
+                root_part,
+            ])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false);
+    if enrolled {
+        send_event(
+            tx,
+            InstallerEvent::Log("LUKS keyfile already enrolled; reusing it.".to_string()),
+        );
+    } else {
+        run_command(
+            tx,
+            "dd",
+            &[
+                "bs=512",
+                "count=4",
+                "if=/dev/random",
+                &format!("of={}", keyfile),
+                "iflag=fullblock",
+            ],
+            None,
+        )?;
+        run_command(tx, "chmod", &["000", &keyfile], None)?;
+        let password_input = format!("{}\n", luks_password);
+        run_command(
+            tx,
+            "cryptsetup",
+            &["luksAddKey", "--batch-mode", root_part, &keyfile],
+            Some(&password_input),
+        )?;
+    }
+    let mkinitcpio_conf =
+        fs::read_to_string("/mnt/etc/mkinitcpio.conf").context("read mkinitcpio.conf")?;
+    if !mkinitcpio_array_contains(&mkinitcpio_conf, "FILES", LUKS_KEYFILE) {
+        let files_line = format!("s|^FILES=(|FILES=({} |", LUKS_KEYFILE);
+        run_chroot(
+            tx,
+            &["sed", "-i", &files_line, "/etc/mkinitcpio.conf"],
+            None,
+        )?;
+    }
+    Ok(())
+}
+
+// Whether a mkinitcpio.conf array such as FILES=(...) already lists `value`
+pub(crate) fn mkinitcpio_array_contains(conf: &str, key: &str, value: &str) -> bool {
+    let prefix = format!("{}=(", key);
+    conf.lines()
+        .filter_map(|line| line.trim().strip_prefix(prefix.as_str()))
+        .any(|items| {
+            items
+                .trim_end_matches(')')
+                .split_whitespace()
+                .any(|item| item.trim_matches('"') == value)
+        })
+}
+
+// Whether the account already exists in the target, e.g. from an interrupted run
+pub(crate) fn target_user_exists(username: &str) -> bool {
+    Command::new("arch-chroot")
+        .args(["/mnt", "id", "-u", username])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+// Half-second checks for a new partition's device node to appear
+const PARTITION_WAIT_ATTEMPTS: usize = 10;
+
+// Confirms a partition path belongs to the install disk before anything formats it,
+// waiting briefly for udev to create the device node
+pub(crate) fn verify_partition(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    disk: &DiskInfo,
+    partition: &str,
+) -> Result<()> {
+    let _ = run_command(tx, "udevadm", &["settle"], None);
+    for _ in 0..PARTITION_WAIT_ATTEMPTS {
+        if Path::new(partition).exists() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(500));
+    }
+    let output = Command::new("lsblk")
+        .args(["-no", "PKNAME", partition])
+        .output()
+        .context("run lsblk")?;
+    let parent = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if !output.status.success() || parent.is_empty() {
+        anyhow::bail!("Partition {} was not found after partitioning", partition);
+    }
+    if parent != disk.name {
+        anyhow::bail!(
+            "Partition {} belongs to {}, not the install disk {}",
+            partition,
+            parent,
+            disk.name
+        );
+    }
+    Ok(())
+}
+
+// Finds the disk holding a detached LUKS header path; it must live on a real block device
+pub fn luks_header_disk(path: &str) -> Result<String> {
+    let header = Path::new(path);
+    if !header.is_absolute() {
+        anyhow::bail!("{} is not an absolute path", path);
+    }
+    if header.is_dir() {
+        anyhow::bail!("{} is a directory", path);
+    }
+    let (source, _, _) = luks_header_mount(header)?;
+    let output = Command::new("lsblk")
+        .args(["-no", "PKNAME", &source])
+        .output()
+        .context("run lsblk")?;
+    let disk = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if disk.is_empty() {
+        Ok(source.trim_start_matches("/dev/").to_string())
+    } else {
+        Ok(disk)
+    }
+}
+
+// Returns the filesystem UUID, type and in-filesystem path of a detached LUKS header
+pub(crate) fn luks_header_location(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    path: &str,
+) -> Result<(String, String, String)> {
+    let header = Path::new(path);
+    let (source, target, fstype) = luks_header_mount(header)?;
+    let relative = header
+        .strip_prefix(&target)
+        .with_context(|| format!("{} is not under {}", path, target))?;
+    let uuid = get_uuid(tx, &source)?;
+    if uuid.is_empty() {
+        anyhow::bail!("{} has no filesystem UUID", source);
+    }
+    Ok((uuid, fstype, format!("/{}", relative.display())))
+}
+
+// Source device, mountpoint and filesystem type backing the header's directory
+fn luks_header_mount(header: &Path) -> Result<(String, String, String)> {
+    let parent = header
+        .parent()
+        .filter(|dir| dir.is_dir())
+        .with_context(|| format!("{} has no existing parent directory", header.display()))?;
+    let output = Command::new("findmnt")
+        .args(["-n", "-o", "SOURCE,TARGET,FSTYPE", "--target"])
+        .arg(parent)
+        .output()
+        .context("run findmnt")?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut fields = stdout.split_whitespace();
+    let (Some(source), Some(target), Some(fstype)) = (fields.next(), fields.next(), fields.next())
+    else {
+        anyhow::bail!("{} is not on a mounted filesystem", header.display());
+    };
+    if !source.starts_with("/dev/") {
+        anyhow::bail!(
+            "{} is on {}, not on a removable block device",
+            header.display(),
+            source
+        );
+    }
+    Ok((source.to_string(), target.to_string(), fstype.to_string()))
+}
+
 // Installs Hyprland user config from nebula-hypr
 pub(crate) fn install_nebula_hypr(
     tx: &crossbeam_channel::Sender<InstallerEvent>,
@@ -72,11 +830,11 @@ pub(crate) fn install_nebula_hypr(
         send_event(
             tx,
             InstallerEvent::Log(
-                "nebula-hypr installer script not found; skipping Hyprland config install."
+                "nebula-hypr installer script not found; using the default Hyprland config."
                     .to_string(),
             ),
         );
-        return Ok(());
+        return copy_default_hypr_config(tx, username);
     };
 
     send_event(
@@ -87,6 +845,56 @@ pub(crate) fn install_nebula_hypr(
     Ok(())
 }
 
+// Copies a default Hyprland config into the user's home when nebula-hypr is unavailable
+pub(crate) fn copy_default_hypr_config(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    username: &str,
+) -> Result<()> {
+    let dest = format!("/mnt/home/{}/.config/hypr", username);
+    if Path::new(&dest).join("hyprland.conf").exists() {
+        send_event(
+            tx,
+            InstallerEvent::Log(format!("Hyprland config already present in {}.", dest)),
+        );
+        return Ok(());
+    }
+
+    // Config trees first, then the example config shipped with the hyprland package
+    let dir_sources = ["/etc/skel/.config/hypr", "/mnt/etc/skel/.config/hypr"];
+    let file_sources = [
+        "/mnt/usr/share/hypr/hyprland.conf",
+        "/usr/share/hypr/hyprland.conf",
+    ];
+    fs::create_dir_all(&dest).context("create hypr config dir")?;
+    if let Some(source) = dir_sources.iter().find(|path| Path::new(path).is_dir()) {
+        run_command(tx, "cp", &["-a", &format!("{}/.", source), &dest], None)?;
+        send_event(
+            tx,
+            InstallerEvent::Log(format!("Copied default Hyprland config from {}", source)),
+        );
+    } else if let Some(source) = file_sources.iter().find(|path| Path::new(path).is_file()) {
+        fs::copy(source, Path::new(&dest).join("hyprland.conf"))
+            .context("copy default hyprland.conf")?;
+        send_event(
+            tx,
+            InstallerEvent::Log(format!("Copied default Hyprland config from {}", source)),
+        );
+    } else {
+        send_event(
+            tx,
+            InstallerEvent::Log(
+                "No default Hyprland config found; ~/.config/hypr left empty.".to_string(),
+            ),
+        );
+        return Ok(());
+    }
+
+    let owner = format!("{}:{}", username, username);
+    let target = format!("/home/{}/.config/hypr", username);
+    run_chroot(tx, &["chown", "-R", &owner, &target], None)?;
+    Ok(())
+}
+
 // Installs Hyprland user config from caelestia-meta
 pub(crate) fn install_caelestia(
     tx: &crossbeam_channel::Sender<InstallerEvent>,
@@ -218,69 +1026,96 @@ fn install_caelestia_optional_configs(
     Ok(())
 }
 
-// Schedules a GNOME dark theme application on first login via autostart and Hyprland exec-once
-pub(crate) fn schedule_nebula_theme(
+// Per-user first-boot checklist; queued task scripts run once, in file name order
+const FIRSTBOOT_TASK_DIR: &str = ".local/share/nebula/firstboot.d";
+const FIRSTBOOT_SCRIPT: &str = ".local/share/nebula/post-install/run-firstboot.sh";
+
+// Runs every pending task, logs to firstboot.log, and notifies when done. A task exiting
+// with 75 waits for a later login; failed ones are retried until all of them pass.
+const FIRSTBOOT_CHECKLIST: &str = concat!(
+    "#!/usr/bin/env bash\n",
+    "set -uo pipefail\n",
+    "state_dir=\"$HOME/.local/share/nebula\"\n",
+    "task_dir=\"$state_dir/firstboot.d\"\n",
+    "log_file=\"$state_dir/firstboot.log\"\n",
+    "marker=\"$state_dir/firstboot.done\"\n",
+    "[[ -f \"$marker\" ]] && exit 0\n",
+    "mkdir -p \"$state_dir\"\n",
+    "# Autostart and Hyprland exec-once can both start it in one session\n",
+    "exec 9>\"$state_dir/firstboot.lock\"\n",
+    "flock -n 9 || exit 0\n",
+    "log() { printf '%s %s\\n' \"$(date '+%F %T')\" \"$*\" >> \"$log_file\"; }\n",
+    "log \"First-boot checklist started (${XDG_CURRENT_DESKTOP:-unknown session})\"\n",
+    "pending=0\n",
+    "failed=0\n",
+    "shopt -s nullglob\n",
+    "for task in \"$task_dir\"/*.sh; do\n",
+    "  name=\"$(basename \"$task\" .sh)\"\n",
+    "  log \"Running $name\"\n",
+    "  bash \"$task\" >> \"$log_file\" 2>&1\n",
+    "  status=$?\n",
+    "  if [[ $status -eq 0 ]]; then\n",
+    "    log \"Finished $name\"\n",
+    "    rm -f \"$task\"\n",
+    "  elif [[ $status -eq 75 ]]; then\n",
+    "    log \"Deferred $name to a later login\"\n",
+    "    pending=$((pending + 1))\n",
+    "  else\n",
+    "    log \"Failed $name (exit $status)\"\n",
+    "    failed=$((failed + 1))\n",
+    "  fi\n",
+    "done\n",
+    "if [[ $failed -eq 0 && $pending -eq 0 ]]; then\n",
+    "  touch \"$marker\"\n",
+    "  rm -f \"$HOME/.config/autostart/nebula-firstboot.desktop\"\n",
+    "  summary=\"First-boot setup finished.\"\n",
+    "elif [[ $failed -eq 0 ]]; then\n",
+    "  summary=\"First-boot setup will continue on a later login.\"\n",
+    "else\n",
+    "  summary=\"$failed first-boot task(s) failed and will be retried. See $log_file\"\n",
+    "fi\n",
+    "log \"$summary\"\n",
+    "if [[ -n \"${WAYLAND_DISPLAY:-}${DISPLAY:-}\" ]] && command -v notify-send >/dev/null 2>&1; then\n",
+    "  notify-send \"Nebula\" \"$summary\" || true\n",
+    "fi\n",
+);
+
+// Installs the checklist runner and starts it from GNOME autostart and Hyprland exec-once
+fn install_firstboot_checklist(
     tx: &crossbeam_channel::Sender<InstallerEvent>,
     username: &str,
 ) -> Result<()> {
     let home_dir = format!("/mnt/home/{}", username);
-    let autostart_dir = format!("{}/.config/autostart", home_dir);
-    let autostart_file = format!("{}/nebula-theme.desktop", autostart_dir);
-    let script_dir = format!("{}/.local/share/nebula/post-install", home_dir);
-    let script_path = format!("{}/run-gnome-theme.sh", script_dir);
+    let script_path = format!("{}/{}", home_dir, FIRSTBOOT_SCRIPT);
+    let autostart_file = format!("{}/.config/autostart/nebula-firstboot.desktop", home_dir);
     let hypr_dir = format!("{}/.local/share/nebula/hypr", home_dir);
-    let hypr_include = format!("{}/nebula-theme.conf", hypr_dir);
-    let hypr_include_home = "~/.local/share/nebula/hypr/nebula-theme.conf";
+    let hypr_include = format!("{}/nebula-firstboot.conf", hypr_dir);
+    let hypr_include_home = "~/.local/share/nebula/hypr/nebula-firstboot.conf";
     let hypr_main = format!("{}/.config/hypr/hyprland.conf", home_dir);
     let hypr_source_line = format!("source = {}", hypr_include_home);
     let hypr_exec_line =
-        "exec-once = /bin/bash -lc \"$HOME/.local/share/nebula/post-install/run-gnome-theme.sh\"";
+        "exec-once = /bin/bash -lc \"$HOME/.local/share/nebula/post-install/run-firstboot.sh\"";
 
-    fs::create_dir_all(&autostart_dir).context("create autostart dir")?;
-    fs::create_dir_all(&script_dir).context("create theme script dir")?;
-    fs::create_dir_all(&hypr_dir).context("create hypr config dir")?;
+    write_file(&script_path, FIRSTBOOT_CHECKLIST)?;
+    run_command(tx, "chmod", &["+x", &script_path], None)?;
 
     let autostart_contents = concat!(
         "[Desktop Entry]\n",
         "Type=Application\n",
-        "Name=Nebula Theme Setup\n",
-        "Comment=Apply GNOME dark theme on first login\n",
-        "Exec=/bin/bash -lc \"$HOME/.local/share/nebula/post-install/run-gnome-theme.sh\"\n",
+        "Name=Nebula First Boot\n",
+        "Comment=Run the remaining Nebula setup tasks on first login\n",
+        "Exec=/bin/bash -lc \"$HOME/.local/share/nebula/post-install/run-firstboot.sh\"\n",
         "Terminal=false\n",
         "OnlyShowIn=GNOME;\n",
         "X-GNOME-Autostart-enabled=true\n",
     );
-    fs::write(&autostart_file, autostart_contents).context("write theme autostart")?;
-
-    let script_contents = concat!(
-        "#!/usr/bin/env bash\n",
-        "set -euo pipefail\n",
-        "theme_marker=\"$HOME/.cache/nebula-theme-applied\"\n",
-        "if [[ -f \"$theme_marker\" ]]; then\n",
-        "  exit 0\n",
-        "fi\n",
-        "mkdir -p \"$HOME/.config/dconf\"\n",
-        "if command -v gsettings >/dev/null 2>&1; then\n",
-        "  gsettings set org.gnome.desktop.interface color-scheme 'prefer-dark' || true\n",
-        "  gsettings set org.gnome.desktop.interface gtk-theme 'Adwaita-dark' || true\n",
-        "fi\n",
-        "mkdir -p \"$(dirname \"$theme_marker\")\"\n",
-        "touch \"$theme_marker\"\n",
-        "autostart_file=\"$HOME/.config/autostart/nebula-theme.desktop\"\n",
-        "if [[ -f \"$autostart_file\" ]]; then\n",
-        "  rm -f \"$autostart_file\"\n",
-        "fi\n",
-    );
-    fs::write(&script_path, script_contents).context("write theme script")?;
-    run_command(tx, "chmod", &["+x", &script_path], None)?;
+    write_file(&autostart_file, autostart_contents)?;
 
-    let hypr_include_contents = format!("# Nebula post-install hooks\n{}\n", hypr_exec_line);
-    fs::write(&hypr_include, hypr_include_contents).context("write hypr theme include")?;
+    let hypr_include_contents = format!("# Nebula first-boot checklist\n{}\n", hypr_exec_line);
+    write_file(&hypr_include, &hypr_include_contents)?;
     if Path::new(&hypr_main).exists() {
         let existing = fs::read_to_string(&hypr_main).unwrap_or_default();
-        let mut updated =
-            existing.replace(&format!("source = {}", hypr_include), hypr_include_home);
-        updated = updated
+        let mut updated = existing
             .lines()
             .filter(|line| !line.trim_start().starts_with("source = /mnt/home/"))
             .collect::<Vec<_>>()
@@ -289,27 +1124,26 @@ pub(crate) fn schedule_nebula_theme(
             if !updated.ends_with('\n') {
                 updated.push('\n');
             }
-            updated.push_str("# Nebula post-install hooks\n");
+            updated.push_str("# Nebula first-boot checklist\n");
             updated.push_str(&hypr_source_line);
             updated.push('\n');
         }
         if updated != existing {
-            fs::write(&hypr_main, updated).context("append hypr theme include")?;
+            fs::write(&hypr_main, updated).context("append hypr first-boot include")?;
         }
     } else {
         send_event(
             tx,
-            InstallerEvent::Log("Hyprland defaults not found; skipping theme hook.".to_string()),
+            InstallerEvent::Log(
+                "Hyprland defaults not found; the first-boot checklist runs from autostart only."
+                    .to_string(),
+            ),
         );
     }
 
     let chown_user = format!("{}:{}", username, username);
     let chown_autostart = format!("/home/{}/.config/autostart", username);
-    let chown_script_dir = format!("/home/{}/.local/share/nebula/post-install", username);
-    let chown_hypr_include = format!(
-        "/home/{}/.local/share/nebula/hypr/nebula-theme.conf",
-        username
-    );
+    let chown_state_dir = format!("/home/{}/.local/share/nebula", username);
     run_chroot(
         tx,
         &[
@@ -317,201 +1151,412 @@ pub(crate) fn schedule_nebula_theme(
             "-R",
             &chown_user,
             &chown_autostart,
-            &chown_script_dir,
-            &chown_hypr_include,
+            &chown_state_dir,
         ],
         None,
     )?;
     Ok(())
 }
 
-// Schedules a one-time Nebula init on first Hyprland login
-pub(crate) fn schedule_nebula_init(
+// Queues a task for the first-boot checklist; the name prefix sets the run order
+fn add_firstboot_task(
     tx: &crossbeam_channel::Sender<InstallerEvent>,
     username: &str,
+    name: &str,
+    contents: &str,
 ) -> Result<()> {
-    let home_dir = format!("/mnt/home/{}", username);
-    let autostart_dir = format!("{}/.config/autostart", home_dir);
-    let autostart_file = format!("{}/nebula-init.desktop", autostart_dir);
-    let script_dir = format!("{}/.local/share/nebula/post-install", home_dir);
-    let script_path = format!("{}/run-nebula-init.sh", script_dir);
-    let hypr_dir = format!("{}/.local/share/nebula/hypr", home_dir);
-    let hypr_include = format!("{}/nebula-init.conf", hypr_dir);
-    let hypr_include_home = "~/.local/share/nebula/hypr/nebula-init.conf";
-    let hypr_main = format!("{}/.config/hypr/hyprland.conf", home_dir);
-    let hypr_source_line = format!("source = {}", hypr_include_home);
-    let hypr_exec_line = "exec-once = /bin/bash -lc \"$HOME/.local/share/nebula/post-install/run-nebula-init.sh\"";
+    let task_path = format!("/mnt/home/{}/{}/{}.sh", username, FIRSTBOOT_TASK_DIR, name);
+    write_file(&task_path, contents)?;
+    run_command(tx, "chmod", &["+x", &task_path], None)?;
+    install_firstboot_checklist(tx, username)?;
+    send_event(
+        tx,
+        InstallerEvent::Log(format!("Queued first-boot task {}.", name)),
+    );
+    Ok(())
+}
 
-    fs::create_dir_all(&autostart_dir).context("create autostart dir")?;
-    fs::create_dir_all(&script_dir).context("create nebula init script dir")?;
-    fs::create_dir_all(&hypr_dir).context("create hypr init dir")?;
+// First-boot task running a post-install script that needs a Hyprland session
+fn hyprland_firstboot_task(script_name: &str) -> String {
+    format!(
+        concat!(
+            "#!/usr/bin/env bash\n",
+            "[[ -n \"${{HYPRLAND_INSTANCE_SIGNATURE:-}}\" ]] || exit 75\n",
+            "exec /bin/bash -l \"$HOME/.local/share/nebula/post-install/{}\"\n",
+        ),
+        script_name
+    )
+}
+
+// Queues the bundled GNOME defaults (dark theme and friends) for the first-boot checklist
+pub(crate) fn schedule_nebula_theme(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    username: &str,
+) -> Result<()> {
+    let settings_path = format!(
+        "/mnt/home/{}/.local/share/nebula/post-install/gnome-settings.txt",
+        username
+    );
+    // One "schema key value" per line; the value keeps its GVariant quoting
+    let settings_contents: String = config()
+        .gnome
+        .settings
+        .iter()
+        .map(|setting| format!("{} {} {}\n", setting.schema, setting.key, setting.value))
+        .collect();
+    write_file(&settings_path, &settings_contents)?;
+
+    let task_contents = concat!(
+        "#!/usr/bin/env bash\n",
+        "set -euo pipefail\n",
+        "mkdir -p \"$HOME/.config/dconf\"\n",
+        "if command -v gsettings >/dev/null 2>&1; then\n",
+        "  settings_file=\"$HOME/.local/share/nebula/post-install/gnome-settings.txt\"\n",
+        "  if [[ -f \"$settings_file\" ]]; then\n",
+        "    while read -r schema key value; do\n",
+        "      [[ -z \"$schema\" || \"$schema\" == \\#* ]] && continue\n",
+        "      gsettings set \"$schema\" \"$key\" \"$value\" || true\n",
+        "    done < \"$settings_file\"\n",
+        "  fi\n",
+        "fi\n",
+    );
+    add_firstboot_task(tx, username, "10-gnome-theme", task_contents)
+}
+
+// Sets the Wi-Fi regulatory domain for wireless-regdb and as a cfg80211 module option
+pub(crate) fn configure_wireless_regdom(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    country: &str,
+) -> Result<()> {
+    let regdom_path = "/mnt/etc/conf.d/wireless-regdom";
+    let setting = format!("WIRELESS_REGDOM=\"{}\"", country);
+    let existing = fs::read_to_string(regdom_path).unwrap_or_default();
+    // The packaged file lists every country commented out; enable only the chosen one
+    let mut lines: Vec<String> = existing
+        .lines()
+        .map(|line| {
+            let uncommented = line.trim_start_matches('#').trim();
+            if uncommented == setting {
+                setting.clone()
+            } else if uncommented.starts_with("WIRELESS_REGDOM=") && !line.starts_with('#') {
+                format!("#{}", line)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+    if !lines.contains(&setting) {
+        lines.push(setting);
+    }
+    write_file(regdom_path, &format!("{}\n", lines.join("\n")))?;
+    write_file(
+        "/mnt/etc/modprobe.d/nebula-regdom.conf",
+        &format!("options cfg80211 ieee80211_regdom={}\n", country),
+    )?;
+    send_event(
+        tx,
+        InstallerEvent::Log(format!("Set the Wi-Fi regulatory domain to {}", country)),
+    );
+    Ok(())
+}
+
+// Allows fingerprint auth for console login and sudo, falling back to the password
+pub(crate) fn configure_fingerprint_pam(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+) -> Result<()> {
+    let pam_line = "auth      sufficient pam_fprintd.so";
+    for pam_file in ["/mnt/etc/pam.d/sudo", "/mnt/etc/pam.d/system-local-login"] {
+        let existing =
+            fs::read_to_string(pam_file).with_context(|| format!("read {}", pam_file))?;
+        if existing.contains("pam_fprintd.so") {
+            continue;
+        }
+        // The first matching auth line wins, so it has to sit above the password modules
+        let mut lines: Vec<&str> = existing.lines().collect();
+        let insert_at = lines
+            .iter()
+            .position(|line| line.starts_with("#%PAM"))
+            .map_or(0, |idx| idx + 1);
+        lines.insert(insert_at, pam_line);
+        let mut updated = lines.join("\n");
+        updated.push('\n');
+        fs::write(pam_file, updated).with_context(|| format!("write {}", pam_file))?;
+    }
+    send_event(
+        tx,
+        InstallerEvent::Log("Enabled fingerprint auth for login and sudo.".to_string()),
+    );
+    Ok(())
+}
+
+// Reminds the user to enroll a fingerprint until one is enrolled
+pub(crate) fn schedule_fingerprint_reminder(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    username: &str,
+) -> Result<()> {
+    let script_path = "/mnt/usr/local/lib/nebula/fingerprint-reminder.sh";
+    let autostart_path = "/mnt/etc/xdg/autostart/nebula-fingerprint.desktop";
+    let profile_path = "/mnt/etc/profile.d/nebula-fingerprint.sh";
+    let hypr_main = format!("/mnt/home/{}/.config/hypr/hyprland.conf", username);
+    let hypr_exec_line = "exec-once = /usr/local/lib/nebula/fingerprint-reminder.sh";
+
+    let script_contents = concat!(
+        "#!/usr/bin/env bash\n",
+        "command -v fprintd-list >/dev/null 2>&1 || exit 0\n",
+        "if ! fprintd-list \"$USER\" 2>/dev/null | grep -q \"no fingers enrolled\"; then\n",
+        "  exit 0\n",
+        "fi\n",
+        "message=\"No fingerprint enrolled yet. Run 'fprintd-enroll' to use the fingerprint reader.\"\n",
+        "if [[ -n \"${WAYLAND_DISPLAY:-}\" ]] && command -v notify-send >/dev/null 2>&1; then\n",
+        "  notify-send \"Fingerprint reader\" \"$message\"\n",
+        "else\n",
+        "  echo \"$message\"\n",
+        "fi\n",
+    );
+    write_file(script_path, script_contents)?;
+    run_command(tx, "chmod", &["+x", script_path], None)?;
 
     let autostart_contents = concat!(
         "[Desktop Entry]\n",
         "Type=Application\n",
-        "Name=Nebula Init\n",
-        "Comment=Apply Nebula post-install tasks on first Hyprland login\n",
-        "Exec=/bin/bash -lc \"$HOME/.local/share/nebula/post-install/run-nebula-init.sh\"\n",
+        "Name=Nebula Fingerprint Reminder\n",
+        "Comment=Remind to enroll a fingerprint\n",
+        "Exec=/usr/local/lib/nebula/fingerprint-reminder.sh\n",
         "Terminal=false\n",
-        "OnlyShowIn=Hyprland;\n",
-        "X-GNOME-Autostart-enabled=true\n",
     );
-    fs::write(&autostart_file, autostart_contents).context("write nebula init autostart")?;
+    write_file(autostart_path, autostart_contents)?;
 
-    let sources = [
-        "/mnt/usr/share/nebula-hypr/nebula-init.sh",
-        "/usr/share/nebula-hypr/nebula-init.sh",
-        "/run/archiso/bootmnt/airootfs/usr/share/nebula-hypr/nebula-init.sh",
-        "/run/archiso/bootmnt/usr/share/nebula-hypr/nebula-init.sh",
-    ];
-    let mut found = None;
-    for source in &sources {
-        if Path::new(source).exists() {
-            found = Some(*source);
-            break;
-        }
-    }
-    let script_source = if let Some(source) = found {
-        source
-    } else {
-        send_event(
-            tx,
-            InstallerEvent::Log("Nebula init script not found; skipping init setup.".to_string()),
-        );
-        return Ok(());
-    };
-    fs::copy(script_source, &script_path).context("copy nebula init script")?;
-    run_command(tx, "chmod", &["+x", &script_path], None)?;
+    // Console logins get the reminder from the login shell
+    let profile_contents = concat!(
+        "if [[ $- == *i* && -z \"${WAYLAND_DISPLAY:-}\" ]]; then\n",
+        "  /usr/local/lib/nebula/fingerprint-reminder.sh\n",
+        "fi\n",
+    );
+    write_file(profile_path, profile_contents)?;
 
-    let hypr_include_contents = format!("# Nebula init\n{}\n", hypr_exec_line);
-    fs::write(&hypr_include, hypr_include_contents).context("write hypr init include")?;
-    if Path::new(&hypr_main).exists() {
-        let existing = fs::read_to_string(&hypr_main).unwrap_or_default();
-        if !existing.lines().any(|line| line.trim() == hypr_source_line) {
-            let mut updated = existing;
-            if !updated.ends_with('\n') {
-                updated.push('\n');
+    if Path::new(&hypr_main).exists() {
+        let mut existing = fs::read_to_string(&hypr_main).unwrap_or_default();
+        if !existing.lines().any(|line| line.trim() == hypr_exec_line) {
+            if !existing.ends_with('\n') {
+                existing.push('\n');
             }
-            updated.push_str("# Nebula init\n");
-            updated.push_str(&hypr_source_line);
-            updated.push('\n');
-            fs::write(&hypr_main, updated).context("append hypr init include")?;
+            existing.push_str(hypr_exec_line);
+            existing.push('\n');
+            fs::write(&hypr_main, existing).context("append fingerprint reminder")?;
         }
     }
+    send_event(
+        tx,
+        InstallerEvent::Log("Scheduled a fingerprint enrollment reminder.".to_string()),
+    );
+    Ok(())
+}
 
-    let chown_user = format!("{}:{}", username, username);
-    let chown_autostart = format!("/home/{}/.config/autostart", username);
-    let chown_script_dir = format!("/home/{}/.local/share/nebula/post-install", username);
-    let chown_hypr_include = format!("/home/{}/.local/share/nebula/hypr", username);
-    run_chroot(
+// Adds --unrestricted to the 10_linux entry class unless an earlier run already did
+const GRUB_UNRESTRICTED_SED: &str =
+    "/--unrestricted/!s/^CLASS=\"\\(.*\\)\"$/CLASS=\"\\1 --unrestricted\"/";
+const GRUB_UNRESTRICTED_HOOK_PATH: &str = "/mnt/etc/pacman.d/hooks/nebula-grub-unrestricted.hook";
+
+// 40_custom with exactly one superuser block, replacing the one from an earlier run
+fn with_grub_superuser(custom: &str, hash: &str) -> String {
+    let mut updated: String = custom
+        .lines()
+        .filter(|line| {
+            !line.starts_with("set superusers=") && !line.starts_with("password_pbkdf2 root ")
+        })
+        .map(|line| format!("{}\n", line))
+        .collect();
+    updated.push_str(&format!(
+        "set superusers=\"root\"\npassword_pbkdf2 root {}\n",
+        hash
+    ));
+    updated
+}
+
+// Protects GRUB menu editing with a PBKDF2 superuser password while keeping entries bootable
+pub(crate) fn set_grub_password(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    password: &str,
+) -> Result<()> {
+    send_event(
         tx,
-        &[
-            "chown",
-            "-R",
-            &chown_user,
-            &chown_autostart,
-            &chown_script_dir,
-            &chown_hypr_include,
-        ],
+        InstallerEvent::Log("$ arch-chroot /mnt grub-mkpasswd-pbkdf2".to_string()),
+    );
+    let mut child = Command::new("arch-chroot")
+        .args(["/mnt", "grub-mkpasswd-pbkdf2"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("spawn grub-mkpasswd-pbkdf2")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(format!("{0}\n{0}\n", password).as_bytes())
+            .context("write grub password")?;
+    }
+    let output = child
+        .wait_with_output()
+        .context("wait grub-mkpasswd-pbkdf2")?;
+    if !output.status.success() {
+        anyhow::bail!("grub-mkpasswd-pbkdf2 failed");
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let hash = stdout
+        .split_whitespace()
+        .find(|token| token.starts_with("grub.pbkdf2."))
+        .context("parse grub password hash")?;
+
+    let custom_path = "/mnt/etc/grub.d/40_custom";
+    let custom = fs::read_to_string(custom_path)
+        .unwrap_or_else(|_| "#!/bin/sh\nexec tail -n +3 $0\n".to_string());
+    write_file(custom_path, &with_grub_superuser(&custom, hash))?;
+    run_command(tx, "chmod", &["755", custom_path], None)?;
+
+    // Menu entries boot without a password, editing them requires one
+    run_command(
+        tx,
+        "sed",
+        &["-i", GRUB_UNRESTRICTED_SED, "/mnt/etc/grub.d/10_linux"],
         None,
     )?;
+    // 10_linux belongs to the grub package, so every upgrade needs the flag again
+    write_file(
+        GRUB_UNRESTRICTED_HOOK_PATH,
+        &format!(
+            "[Trigger]\nOperation = Install\nOperation = Upgrade\nType = Package\nTarget = grub\n\n[Action]\nDescription = Keeping GRUB menu entries bootable without the password...\nWhen = PostTransaction\nExec = /usr/bin/sed -i '{}' /etc/grub.d/10_linux\n",
+            GRUB_UNRESTRICTED_SED
+        ),
+    )?;
     Ok(())
 }
 
-// Schedules a one-time Caelestia init on first Hyprland login
-pub(crate) fn schedule_caelestia_init(
+// Schedules a one-time Flatpak app install on first boot via a systemd unit
+pub(crate) fn schedule_flatpak_apps(
     tx: &crossbeam_channel::Sender<InstallerEvent>,
-    username: &str,
+    apps: &[String],
 ) -> Result<()> {
-    let home_dir = format!("/mnt/home/{}", username);
-    let autostart_dir = format!("{}/.config/autostart", home_dir);
-    let autostart_file = format!("{}/caelestia-init.desktop", autostart_dir);
-    let script_dir = format!("{}/.local/share/nebula/post-install", home_dir);
-    let script_path = format!("{}/run-caelestia-init.sh", script_dir);
-    let hypr_dir = format!("{}/.local/share/nebula/hypr", home_dir);
-    let hypr_include = format!("{}/caelestia-init.conf", hypr_dir);
-    let hypr_include_home = "~/.local/share/nebula/hypr/caelestia-init.conf";
-    let hypr_main = format!("{}/.config/hypr/hyprland.conf", home_dir);
-    let hypr_source_line = format!("source = {}", hypr_include_home);
-    let hypr_exec_line = "exec-once = /bin/bash -lc \"$HOME/.local/share/nebula/post-install/run-caelestia-init.sh\"";
+    if apps.is_empty() {
+        return Ok(());
+    }
+    let script_path = "/mnt/usr/local/lib/nebula/install-flatpak-apps.sh";
+    let unit_path = "/mnt/etc/systemd/system/nebula-flatpak-apps.service";
 
-    fs::create_dir_all(&autostart_dir).context("create autostart dir")?;
-    fs::create_dir_all(&script_dir).context("create caelestia init script dir")?;
-    fs::create_dir_all(&hypr_dir).context("create hypr init dir")?;
+    let mut script_contents = String::from(concat!(
+        "#!/usr/bin/env bash\n",
+        "set -uo pipefail\n",
+        "flatpak remote-add --if-not-exists flathub https://dl.flathub.org/repo/flathub.flatpakrepo || exit 1\n",
+    ));
+    for app in apps {
+        script_contents.push_str(&format!(
+            "flatpak install --system --noninteractive flathub {} || true\n",
+            app
+        ));
+    }
+    script_contents.push_str("systemctl disable nebula-flatpak-apps.service || true\n");
+    write_file(script_path, &script_contents)?;
+    run_command(tx, "chmod", &["+x", script_path], None)?;
 
-    let autostart_contents = concat!(
-        "[Desktop Entry]\n",
-        "Type=Application\n",
-        "Name=Caelestia Init\n",
-        "Comment=Apply Caelestia scheme on first Hyprland login\n",
-        "Exec=/bin/bash -lc \"$HOME/.local/share/nebula/post-install/run-caelestia-init.sh\"\n",
-        "Terminal=false\n",
-        "OnlyShowIn=Hyprland;\n",
-        "X-GNOME-Autostart-enabled=true\n",
+    let unit_contents = concat!(
+        "[Unit]\n",
+        "Description=Nebula first boot Flatpak app install\n",
+        "Wants=network-online.target\n",
+        "After=network-online.target\n",
+        "\n",
+        "[Service]\n",
+        "Type=oneshot\n",
+        "ExecStart=/usr/local/lib/nebula/install-flatpak-apps.sh\n",
+        "\n",
+        "[Install]\n",
+        "WantedBy=multi-user.target\n",
     );
-    fs::write(&autostart_file, autostart_contents).context("write caelestia init autostart")?;
+    write_file(unit_path, unit_contents)?;
+    run_chroot(
+        tx,
+        &["systemctl", "enable", "nebula-flatpak-apps.service"],
+        None,
+    )?;
+    send_event(
+        tx,
+        InstallerEvent::Log(format!(
+            "Scheduled {} Flatpak app(s) for install on first boot.",
+            apps.len()
+        )),
+    );
+    Ok(())
+}
 
-    let sources = [
-        "/mnt/usr/share/caelestia/caelestia-init.sh",
-        "/usr/share/caelestia/caelestia-init.sh",
-        "/run/archiso/bootmnt/airootfs/usr/share/caelestia/caelestia-init.sh",
-        "/run/archiso/bootmnt/usr/share/caelestia/caelestia-init.sh",
-    ];
-    let mut found = None;
-    for source in &sources {
-        if Path::new(source).exists() {
-            found = Some(*source);
-            break;
-        }
-    }
-    let script_source = if let Some(source) = found {
-        source
-    } else {
+// Copies the first bundled init script found and queues it for the first Hyprland login
+fn schedule_hyprland_init(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    username: &str,
+    label: &str,
+    sources: &[&str],
+    script_name: &str,
+    task_name: &str,
+) -> Result<()> {
+    let Some(script_source) = sources.iter().find(|source| Path::new(source).exists()) else {
         send_event(
             tx,
-            InstallerEvent::Log(
-                "Caelestia init script not found; skipping init setup.".to_string(),
-            ),
+            InstallerEvent::Log(format!("{} script not found; skipping init setup.", label)),
         );
         return Ok(());
     };
-    fs::copy(script_source, &script_path).context("copy caelestia init script")?;
-    run_command(tx, "chmod", &["+x", &script_path], None)?;
-
-    let hypr_include_contents = format!("# Nebula Caelestia init\n{}\n", hypr_exec_line);
-    fs::write(&hypr_include, hypr_include_contents).context("write hypr init include")?;
-    if Path::new(&hypr_main).exists() {
-        let existing = fs::read_to_string(&hypr_main).unwrap_or_default();
-        if !existing.lines().any(|line| line.trim() == hypr_source_line) {
-            let mut updated = existing;
-            if !updated.ends_with('\n') {
-                updated.push('\n');
-            }
-            updated.push_str("# Nebula Caelestia init\n");
-            updated.push_str(&hypr_source_line);
-            updated.push('\n');
-            fs::write(&hypr_main, updated).context("append hypr init include")?;
-        }
+    let script_path = format!(
+        "/mnt/home/{}/.local/share/nebula/post-install/{}",
+        username, script_name
+    );
+    if let Some(parent) = Path::new(&script_path).parent() {
+        fs::create_dir_all(parent).context("create post-install script dir")?;
     }
+    fs::copy(script_source, &script_path)
+        .with_context(|| format!("copy {} script", label.to_lowercase()))?;
+    run_command(tx, "chmod", &["+x", &script_path], None)?;
+    add_firstboot_task(
+        tx,
+        username,
+        task_name,
+        &hyprland_firstboot_task(script_name),
+    )
+}
 
-    let chown_user = format!("{}:{}", username, username);
-    let chown_autostart = format!("/home/{}/.config/autostart", username);
-    let chown_script_dir = format!("/home/{}/.local/share/nebula/post-install", username);
-    let chown_hypr_include = format!("/home/{}/.local/share/nebula/hypr", username);
-    run_chroot(
+// Queues the one-time Nebula init for the first Hyprland login
+pub(crate) fn schedule_nebula_init(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    username: &str,
+) -> Result<()> {
+    schedule_hyprland_init(
         tx,
+        username,
+        "Nebula init",
         &[
-            "chown",
-            "-R",
-            &chown_user,
-            &chown_autostart,
-            &chown_script_dir,
-            &chown_hypr_include,
+            "/mnt/usr/share/nebula-hypr/nebula-init.sh",
+            "/usr/share/nebula-hypr/nebula-init.sh",
+            "/run/archiso/bootmnt/airootfs/usr/share/nebula-hypr/nebula-init.sh",
+            "/run/archiso/bootmnt/usr/share/nebula-hypr/nebula-init.sh",
         ],
-        None,
-    )?;
+        "run-nebula-init.sh",
+        "20-nebula-init",
+    )
+}
 
-    Ok(())
+// Queues the one-time Caelestia init for the first Hyprland login
+pub(crate) fn schedule_caelestia_init(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    username: &str,
+) -> Result<()> {
+    schedule_hyprland_init(
+        tx,
+        username,
+        "Caelestia init",
+        &[
+            "/mnt/usr/share/caelestia/caelestia-init.sh",
+            "/usr/share/caelestia/caelestia-init.sh",
+            "/run/archiso/bootmnt/airootfs/usr/share/caelestia/caelestia-init.sh",
+            "/run/archiso/bootmnt/usr/share/caelestia/caelestia-init.sh",
+        ],
+        "run-caelestia-init.sh",
+        "20-caelestia-init",
+    )
 }
 
 pub(crate) fn configure_hypr_monitors(
@@ -561,9 +1606,129 @@ pub(crate) fn configure_hypr_monitors(
     Ok(())
 }
 
+const SUDOERS_DROPIN: &str = "/etc/sudoers.d/10-nebula";
+
+// Grants wheel sudo through a drop-in and checks it with visudo; a rejected file is removed
+// so the main sudoers stays usable
+pub(crate) fn write_sudoers_dropin(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    policy: &SudoPolicy,
+) -> Result<()> {
+    let mut contents = String::from("# Generated by the Nebula installer\n");
+    if policy.nopasswd {
+        contents.push_str("%wheel ALL=(ALL:ALL) NOPASSWD: ALL\n");
+    } else {
+        contents.push_str("%wheel ALL=(ALL:ALL) ALL\n");
+    }
+    if let Some(minutes) = policy.timestamp_timeout {
+        contents.push_str(&format!("Defaults timestamp_timeout={}\n", minutes));
+    }
+    if policy.insults {
+        contents.push_str("Defaults insults\n");
+    }
+    let target_path = format!("/mnt{}", SUDOERS_DROPIN);
+    write_file(&target_path, &contents)?;
+    run_command(tx, "chmod", &["440", &target_path], None)?;
+    if let Err(err) = run_chroot(tx, &["visudo", "-c", "-f", SUDOERS_DROPIN], None) {
+        let _ = fs::remove_file(&target_path);
+        return Err(err.context("sudoers drop-in failed visudo check"));
+    }
+    Ok(())
+}
+
+const MAKEPKG_DROPIN: &str = "/mnt/etc/makepkg.conf.d/nebula.conf";
+
+// makepkg sources its conf.d files after makepkg.conf, so these settings win without editing it
+pub(crate) fn write_makepkg_dropin(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    tuning: &MakepkgTuning,
+) -> Result<()> {
+    if !tuning.parallel && !tuning.ccache {
+        return Ok(());
+    }
+    let mut contents = String::from("# Generated by the Nebula installer\n");
+    if tuning.parallel {
+        let cores = thread::available_parallelism()
+            .map(|count| count.get())
+            .unwrap_or(1);
+        send_event(
+            tx,
+            InstallerEvent::Log(format!(
+                "Configuring makepkg for parallel builds ({} cores detected)",
+                cores
+            )),
+        );
+        // nproc is evaluated per build, so the setting follows later hardware changes
+        contents.push_str("MAKEFLAGS=\"-j$(nproc)\"\n");
+        contents.push_str("COMPRESSZST=(zstd -c -T0 -)\n");
+        contents.push_str("COMPRESSXZ=(xz -c -z -T0 -)\n");
+    }
+    if tuning.ccache {
+        contents.push_str("BUILDENV=(!distcc color ccache check !sign)\n");
+    }
+    fs::create_dir_all("/mnt/etc/makepkg.conf.d").context("create makepkg.conf.d")?;
+    write_file(MAKEPKG_DROPIN, &contents)
+}
+
+// Writes keyboard and touchpad defaults to input.conf and sources it from hyprland.conf
+pub(crate) fn configure_hypr_input(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    username: &str,
+    input: &HyprInput,
+) -> Result<()> {
+    let hypr_dir = format!("/mnt/home/{}/.config/hypr", username);
+    let config_path = format!("{}/input.conf", hypr_dir);
+    send_event(
+        tx,
+        InstallerEvent::Log(format!("Writing Hyprland input config to {}", config_path)),
+    );
+    let contents = format!(
+        "# Generated by the Nebula installer\n\
+input {{\n\
+    kb_layout = {}\n\
+    kb_variant = {}\n\
+    repeat_rate = {}\n\
+    repeat_delay = {}\n\
+    touchpad {{\n\
+        natural_scroll = {}\n\
+    }}\n\
+}}\n",
+        input.kb_layout,
+        input.kb_variant,
+        input.repeat_rate,
+        input.repeat_delay,
+        input.natural_scroll
+    );
+    fs::create_dir_all(&hypr_dir).context("create hypr config dir")?;
+    fs::write(&config_path, contents).context("write hypr input config")?;
+
+    let hypr_main = format!("{}/hyprland.conf", hypr_dir);
+    let input_source = "source = ~/.config/hypr/input.conf";
+    if let Ok(existing) = fs::read_to_string(&hypr_main) {
+        if !existing.lines().any(|line| line.trim() == input_source) {
+            let mut updated = existing;
+            if !updated.ends_with('\n') {
+                updated.push('\n');
+            }
+            updated.push_str("# Nebula input config\n");
+            updated.push_str(input_source);
+            updated.push('\n');
+            fs::write(&hypr_main, updated).context("append Hyprland input include")?;
+        }
+    }
+    Ok(())
+}
+
 pub(crate) fn get_wlr_randr_output(
     tx: &crossbeam_channel::Sender<InstallerEvent>,
 ) -> Option<String> {
+    WLR_RANDR_OUTPUT
+        .get_or_init(|| load_wlr_randr_output(tx))
+        .clone()
+}
+
+// Reads the file cache left by an earlier run, or runs wlr-randr and fills it
+fn load_wlr_randr_output(tx: &crossbeam_channel::Sender<InstallerEvent>) -> Option<String> {
     if let Ok(contents) = fs::read_to_string(WLR_RANDR_CACHE_PATH) {
         if !contents.trim().is_empty() {
             send_event(
@@ -699,6 +1864,146 @@ pub(crate) fn write_os_release() -> Result<()> {
     Ok(())
 }
 
+// Overwrites the whole disk so old data cannot be recovered after partitioning
+pub(crate) fn secure_wipe_disk(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    disk: &DiskInfo,
+    cancel: &AtomicBool,
+) -> Result<()> {
+    let disk_path = disk.device_path();
+    let rotational = fs::read_to_string(format!("/sys/block/{}/queue/rotational", disk.name))
+        .map(|value| value.trim() != "0")
+        .unwrap_or(true);
+    if !rotational {
+        send_event(
+            tx,
+            InstallerEvent::Log("SSD detected; trying secure discard...".to_string()),
+        );
+        if run_command(tx, "blkdiscard", &["-f", "-s", &disk_path], None).is_ok() {
+            return Ok(());
+        }
+        send_event(
+            tx,
+            InstallerEvent::Log(
+                "Secure discard not supported; falling back to a random fill.".to_string(),
+            ),
+        );
+    }
+
+    // Zeros written through a throwaway plain mapping land on disk as random data
+    run_command(
+        tx,
+        "cryptsetup",
+        &[
+            "open",
+            "--type",
+            "plain",
+            "--key-file",
+            "/dev/urandom",
+            "--sector-size",
+            "4096",
+            &disk_path,
+            WIPE_MAPPER_NAME,
+        ],
+        None,
+    )?;
+    let mapper_path = format!("/dev/mapper/{}", WIPE_MAPPER_NAME);
+    let result = fill_with_zeros(tx, &mapper_path, cancel);
+    if let Err(err) = run_command(tx, "cryptsetup", &["close", WIPE_MAPPER_NAME], None) {
+        send_event(
+            tx,
+            InstallerEvent::Log(format!("Failed to close wipe mapping: {}", err)),
+        );
+    }
+    result
+}
+
+// Read-only surface scan; refuses to install onto a disk with bad sectors
+pub(crate) fn scan_bad_blocks(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    disk: &DiskInfo,
+) -> Result<()> {
+    let disk_path = disk.device_path();
+    let _ = fs::remove_file(BADBLOCKS_LIST_PATH);
+    // The heartbeat stands in for `-s`, whose backspace-drawn progress doesn't fit the log
+    run_command_stream(
+        tx,
+        "badblocks",
+        &["-v", "-b", "4096", "-o", BADBLOCKS_LIST_PATH, &disk_path],
+        None,
+        Some("Scanning for bad blocks; this can take hours on large disks..."),
+        None,
+    )?;
+    let bad_blocks = fs::read_to_string(BADBLOCKS_LIST_PATH)
+        .unwrap_or_default()
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .count();
+    if bad_blocks > 0 {
+        anyhow::bail!(
+            "{} bad blocks found on {}; the disk is likely failing (list in {})",
+            bad_blocks,
+            disk_path,
+            BADBLOCKS_LIST_PATH
+        );
+    }
+    send_event(
+        tx,
+        InstallerEvent::Log(format!("No bad blocks found on {}", disk_path)),
+    );
+    Ok(())
+}
+
+// Writes zeros until the device is full, reporting progress and an ETA every few seconds
+fn fill_with_zeros(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    path: &str,
+    cancel: &AtomicBool,
+) -> Result<()> {
+    let total: u64 = run_command_capture(tx, "blockdev", &["--getsize64", path])?
+        .trim()
+        .parse()
+        .context("parse device size")?;
+    let mut device = fs::OpenOptions::new()
+        .write(true)
+        .open(path)
+        .with_context(|| format!("open {}", path))?;
+    let buffer = vec![0u8; 4 * 1024 * 1024];
+    let started = Instant::now();
+    let mut last_report = Instant::now();
+    let mut written: u64 = 0;
+    while written < total {
+        if cancel.load(Ordering::Relaxed) {
+            anyhow::bail!("Installation cancelled");
+        }
+        match device.write(&buffer) {
+            Ok(0) => break,
+            Ok(count) => written += count as u64,
+            Err(err) if err.raw_os_error() == Some(libc::ENOSPC) => break,
+            Err(err) => return Err(err).with_context(|| format!("write {}", path)),
+        }
+        if last_report.elapsed() >= Duration::from_secs(10) {
+            last_report = Instant::now();
+            let fraction = written as f64 / total as f64;
+            let rate = written as f64 / started.elapsed().as_secs_f64().max(1.0);
+            let remaining = ((total - written.min(total)) as f64 / rate.max(1.0)) as u64;
+            send_event(tx, InstallerEvent::StepProgress(fraction));
+            send_event(
+                tx,
+                InstallerEvent::Log(format!(
+                    "Wiping disk: {:.0}% (about {}m {:02}s left)",
+                    fraction * 100.0,
+                    remaining / 60,
+                    remaining % 60
+                )),
+            );
+        }
+    }
+    device.sync_all().context("flush wiped device")?;
+    send_event(tx, InstallerEvent::Log("Disk wipe finished.".to_string()));
+    Ok(())
+}
+
 pub(crate) fn close_cryptroot_with_retries(tx: &crossbeam_channel::Sender<InstallerEvent>) {
     const MAX_TRIES: usize = 5;
     send_event(tx, InstallerEvent::Log("Closing cryptroot...".to_string()));
@@ -736,6 +2041,35 @@ pub(crate) fn close_cryptroot_with_retries(tx: &crossbeam_channel::Sender<Instal
     }
 }
 
+// Copies the running installer and its shared assets into the target for later reruns
+pub(crate) fn copy_installer_to_target(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+) -> Result<()> {
+    // The binary may live on media that is gone by now, e.g. a removed USB stick
+    let exe = std::env::current_exe().context("locate the installer binary")?;
+    if !exe.is_file() {
+        anyhow::bail!("{} is not accessible", exe.display());
+    }
+    let exe_path = exe.to_string_lossy().to_string();
+    let target = "/mnt/usr/local/bin/nebula-installer";
+    run_command(tx, "mkdir", &["-p", "/mnt/usr/local/bin"], None)?;
+    run_command(tx, "install", &["-m", "755", &exe_path, target], None)?;
+    if Path::new("/usr/share/nebula").is_dir() {
+        run_command(tx, "mkdir", &["-p", "/mnt/usr/share/nebula"], None)?;
+        run_command(
+            tx,
+            "cp",
+            &["-a", "-n", "/usr/share/nebula/.", "/mnt/usr/share/nebula/"],
+            None,
+        )?;
+    }
+    send_event(
+        tx,
+        InstallerEvent::Log("Installer copied to /usr/local/bin/nebula-installer".to_string()),
+    );
+    Ok(())
+}
+
 pub(crate) fn write_file(path: &str, contents: &str) -> Result<()> {
     if let Some(parent) = Path::new(path).parent() {
         fs::create_dir_all(parent).context("create parent dirs")?;
@@ -744,8 +2078,48 @@ pub(crate) fn write_file(path: &str, contents: &str) -> Result<()> {
     Ok(())
 }
 
+// Logs from earlier install attempts kept next to the new one as `.1`, `.2`, ...
+const ROTATED_LOG_COUNT: usize = 3;
+
+// Shifts an existing log, plain or gzipped, up one slot so a reinstall keeps it
+fn rotate_log(dest: &Path) {
+    for suffix in ["", ".gz"] {
+        let slot = |index: usize| match index {
+            0 => PathBuf::from(format!("{}{}", dest.display(), suffix)),
+            _ => PathBuf::from(format!("{}.{}{}", dest.display(), index, suffix)),
+        };
+        for index in (0..ROTATED_LOG_COUNT).rev() {
+            if slot(index).exists() {
+                let _ = fs::rename(slot(index), slot(index + 1));
+            }
+        }
+    }
+}
+
+// Copies a log into the target, gzipped to `<dest>.gz` when `compress` is set; returns the written path
+pub(crate) fn copy_and_compress_log(src: &Path, dest: &Path, compress: bool) -> Result<PathBuf> {
+    if !compress {
+        fs::copy(src, dest).with_context(|| format!("copy {}", src.display()))?;
+        return Ok(dest.to_path_buf());
+    }
+    let gz_path = PathBuf::from(format!("{}.gz", dest.display()));
+    let output =
+        fs::File::create(&gz_path).with_context(|| format!("create {}", gz_path.display()))?;
+    let status = Command::new("gzip")
+        .args(["-9", "-c"])
+        .arg(src)
+        .stdout(output)
+        .status()
+        .context("run gzip")?;
+    if !status.success() {
+        let _ = fs::remove_file(&gz_path);
+        anyhow::bail!("gzip failed for {}", src.display());
+    }
+    Ok(gz_path)
+}
+
 // Copies the installer log from /tmp to the installed systems /var/log
-pub(crate) fn copy_installer_log(tx: &crossbeam_channel::Sender<InstallerEvent>) {
+pub(crate) fn copy_installer_log(tx: &crossbeam_channel::Sender<InstallerEvent>, compress: bool) {
     let src = Path::new("/tmp/nebula-installer.log");
     let dest = Path::new("/mnt/var/log/nebula-installer.log");
     if !src.exists() {
@@ -760,14 +2134,78 @@ pub(crate) fn copy_installer_log(tx: &crossbeam_channel::Sender<InstallerEvent>)
             return;
         }
     }
-    match fs::copy(src, dest) {
-        Ok(_) => send_event(
+    rotate_log(dest);
+    let saved = copy_and_compress_log(src, dest, compress).or_else(|err| {
+        if !compress {
+            return Err(err);
+        }
+        send_event(
+            tx,
+            InstallerEvent::Log(format!(
+                "Compressing the installer log failed ({:#}); saving it uncompressed.",
+                err
+            )),
+        );
+        copy_and_compress_log(src, dest, false)
+    });
+    match saved {
+        Ok(path) => send_event(
             tx,
-            InstallerEvent::Log(format!("Saved installer log to {}", dest.display())),
+            InstallerEvent::Log(format!("Saved installer log to {}", path.display())),
         ),
         Err(err) => send_event(
             tx,
-            InstallerEvent::Log(format!("Failed to save installer log: {}", err)),
+            InstallerEvent::Log(format!("Failed to save installer log: {:#}", err)),
         ),
     }
+
+    // Per-step logs; the final step's own log is copied as far as it got
+    let step_logs_dir = Path::new("/mnt/var/log/nebula");
+    if let Err(err) = fs::create_dir_all(step_logs_dir) {
+        send_event(
+            tx,
+            InstallerEvent::Log(format!("Failed to create step log dir: {}", err)),
+        );
+        return;
+    }
+    for index in 0..STEP_NAMES.len() {
+        let step_log = step_log_path(index);
+        let step_log = Path::new(&step_log);
+        if let Some(name) = step_log.file_name().filter(|_| step_log.exists()) {
+            let dest = step_logs_dir.join(name);
+            if copy_and_compress_log(step_log, &dest, compress).is_err() {
+                let _ = fs::copy(step_log, dest);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mkinitcpio_array_lookup() {
+        let conf = "MODULES=(btrfs)\n#FILES=(/old.bin)\nFILES=(/crypto_keyfile.bin \"/etc/x\")\n";
+        assert!(mkinitcpio_array_contains(conf, "FILES", LUKS_KEYFILE));
+        assert!(mkinitcpio_array_contains(conf, "FILES", "/etc/x"));
+        assert!(mkinitcpio_array_contains(conf, "MODULES", "btrfs"));
+        assert!(!mkinitcpio_array_contains(conf, "MODULES", "vfat"));
+        assert!(!mkinitcpio_array_contains(conf, "FILES", "/old.bin"));
+        assert!(!mkinitcpio_array_contains("FILES=()", "FILES", "/x"));
+    }
+
+    #[test]
+    fn grub_superuser_block_is_replaced() {
+        let stock = "#!/bin/sh\nexec tail -n +3 $0\n";
+        let once = with_grub_superuser(stock, "grub.pbkdf2.sha512.10000.AAA");
+        assert_eq!(
+            once,
+            "#!/bin/sh\nexec tail -n +3 $0\nset superusers=\"root\"\npassword_pbkdf2 root grub.pbkdf2.sha512.10000.AAA\n"
+        );
+        let again = with_grub_superuser(&once, "grub.pbkdf2.sha512.10000.BBB");
+        assert_eq!(again.matches("set superusers=").count(), 1);
+        assert_eq!(again.matches("password_pbkdf2 root").count(), 1);
+        assert!(again.ends_with("password_pbkdf2 root grub.pbkdf2.sha512.10000.BBB\n"));
+    }
 }
@@ -1,28 +1,69 @@
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 
 use crate::model::InstallerEvent;
 
-use super::commands::run_command;
+use super::commands::{run_chroot, run_command};
 use super::send_event;
 use super::system::get_wlr_randr_output;
 use super::system::write_file;
 
+const PLYMOUTH_THEMES_DIR: &str = "/usr/share/plymouth/themes";
+
+// Plymouth themes on the live system, e.g. `bgrt`, `spinner`, `nebula-splash`
+pub fn available_plymouth_themes() -> Vec<String> {
+    let mut themes: Vec<String> = fs::read_dir(PLYMOUTH_THEMES_DIR)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().is_dir())
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    themes.sort();
+    themes
+}
+
+// Makes a chosen Plymouth theme the default, copying it from the live system if the target lacks it
+pub(crate) fn set_plymouth_theme(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    theme: &str,
+) -> Result<()> {
+    let target_dir = format!("/mnt{}/{}", PLYMOUTH_THEMES_DIR, theme);
+    if !Path::new(&target_dir).exists() {
+        let source_dir = format!("{}/{}", PLYMOUTH_THEMES_DIR, theme);
+        if !Path::new(&source_dir).exists() {
+            anyhow::bail!("Plymouth theme {} not found", theme);
+        }
+        let target_parent = format!("/mnt{}", PLYMOUTH_THEMES_DIR);
+        run_command(tx, "mkdir", &["-p", &target_parent], None)?;
+        run_command(tx, "cp", &["-a", &source_dir, &target_parent], None)?;
+    }
+    run_chroot(tx, &["plymouth-set-default-theme", theme], None)
+}
+
 // Updates the GRUB command line for an encrypted root filesystem
-pub(crate) fn update_grub_cmdline(root_uuid: &str) -> Result<()> {
+// `crypt_device` is a device spec such as `UUID=...`; `crypt_header` is a detached header spec
+pub(crate) fn update_grub_cmdline(crypt_device: &str, crypt_header: Option<&str>) -> Result<()> {
     let path = "/mnt/etc/default/grub";
     let contents = fs::read_to_string(path).context("read grub config")?;
+    let header_param = crypt_header
+        .map(|header| format!(" cryptheader={}", header))
+        .unwrap_or_default();
+    let cmdline = format!(
+        "GRUB_CMDLINE_LINUX=\"cryptdevice={}:cryptroot{} root=/dev/mapper/cryptroot quiet splash\"",
+        crypt_device, header_param
+    );
     let mut updated = String::new();
     let mut replaced = false;
     for line in contents.lines() {
         if line.starts_with("GRUB_CMDLINE_LINUX=") {
-            let value = format!(
-                "GRUB_CMDLINE_LINUX=\"cryptdevice=UUID={}:cryptroot root=/dev/mapper/cryptroot quiet splash\"",
-                root_uuid
-            );
-            updated.push_str(&value);
+            updated.push_str(&cmdline);
             updated.push('\n');
             replaced = true;
         } else {
@@ -31,10 +72,8 @@ pub(crate) fn update_grub_cmdline(root_uuid: &str) -> Result<()> {
         }
     }
     if !replaced {
-        updated.push_str(&format!(
-            "GRUB_CMDLINE_LINUX=\"cryptdevice=UUID={}:cryptroot root=/dev/mapper/cryptroot quiet splash\"\n",
-            root_uuid
-        ));
+        updated.push_str(&cmdline);
+        updated.push('\n');
     }
     fs::write(path, updated).context("write grub config")?;
     Ok(())
@@ -127,8 +166,48 @@ pub(crate) fn remove_grub_cmdline_params(params: &[&str]) -> Result<()> {
     Ok(())
 }
 
+// Copies the GRUB and SDDM themes side by side; they touch separate files
+pub(crate) fn install_themes(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    resolution_override: Option<(u32, u32)>,
+    scale_override: Option<f32>,
+) -> Result<()> {
+    let started = Instant::now();
+    let (grub, sddm) = thread::scope(|scope| {
+        let grub = scope.spawn(|| timed(|| install_grub_theme(tx, resolution_override)));
+        let sddm =
+            scope.spawn(|| timed(|| install_sddm_theme(tx, resolution_override, scale_override)));
+        (grub.join(), sddm.join())
+    });
+    let grub = grub.map_err(|_| anyhow!("GRUB theme install panicked"))??;
+    let sddm = sddm.map_err(|_| anyhow!("SDDM theme install panicked"))??;
+    let elapsed = started.elapsed();
+    let saved = (grub + sddm).saturating_sub(elapsed);
+    send_event(
+        tx,
+        InstallerEvent::Log(format!(
+            "Themes installed in {:.1}s (GRUB {:.1}s, SDDM {:.1}s); {:.1}s saved by running them together",
+            elapsed.as_secs_f32(),
+            grub.as_secs_f32(),
+            sddm.as_secs_f32(),
+            saved.as_secs_f32()
+        )),
+    );
+    Ok(())
+}
+
+// How long a theme install took, for the time-saved log line
+fn timed(install: impl FnOnce() -> Result<()>) -> Result<Duration> {
+    let started = Instant::now();
+    install()?;
+    Ok(started.elapsed())
+}
+
 // Installs the custom Nebula GRUB theme
-pub(crate) fn install_grub_theme(tx: &crossbeam_channel::Sender<InstallerEvent>) -> Result<()> {
+fn install_grub_theme(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    resolution_override: Option<(u32, u32)>,
+) -> Result<()> {
     let theme_dest = "/mnt/boot/grub/themes/nebula-vimix-grub";
 
     let theme_src = if let Some(source) = find_grub_theme_source(tx) {
@@ -143,7 +222,7 @@ pub(crate) fn install_grub_theme(tx: &crossbeam_channel::Sender<InstallerEvent>)
         return Ok(());
     };
 
-    let (selection, detected) = detect_grub_theme_selection(tx);
+    let (selection, detected) = detect_grub_theme_selection(tx, resolution_override);
     if let Some((width, height)) = detected {
         send_event(
             tx,
@@ -269,7 +348,11 @@ pub(crate) fn find_grub_theme_source(
 }
 
 // Installs and configures the custom Nebula SDDM theme
-pub(crate) fn install_sddm_theme(tx: &crossbeam_channel::Sender<InstallerEvent>) -> Result<()> {
+fn install_sddm_theme(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    resolution_override: Option<(u32, u32)>,
+    scale_override: Option<f32>,
+) -> Result<()> {
     let theme_sources = [
         "/usr/share/sddm/themes/nebula-sddm",
         "/run/archiso/bootmnt/airootfs/usr/share/sddm/themes/nebula-sddm",
@@ -305,11 +388,21 @@ pub(crate) fn install_sddm_theme(tx: &crossbeam_channel::Sender<InstallerEvent>)
         "/mnt/etc/sddm.conf.d/virtualkbd.conf",
         "[General]\nInputMethod=qtvirtualkeyboard\n",
     )?;
-    let wlr_output = get_wlr_randr_output(tx);
-    let scale = wlr_output
-        .as_deref()
-        .and_then(detect_scale_from_wlr_randr)
-        .or_else(detect_display_scale);
+    let scale = if let Some(scale) = scale_override {
+        send_event(
+            tx,
+            InstallerEvent::Log(format!("Display scale override in effect: {:.2}", scale)),
+        );
+        Some(scale)
+    } else if let Some((width, height)) = resolution_override {
+        Some(scale_from_resolution(width, height))
+    } else {
+        let wlr_output = get_wlr_randr_output(tx);
+        wlr_output
+            .as_deref()
+            .and_then(detect_scale_from_wlr_randr)
+            .or_else(detect_display_scale)
+    };
     let scale_value = scale.unwrap_or(1.0);
     if let Some(scale) = scale {
         send_event(
@@ -364,14 +457,45 @@ pub(crate) fn set_grub_distributor() -> Result<()> {
     Ok(())
 }
 
+// Lets GRUB unlock the LUKS1 root that holds /boot (Arch ships the line commented out)
+pub(crate) fn enable_grub_cryptodisk() -> Result<()> {
+    let path = "/mnt/etc/default/grub";
+    let contents = fs::read_to_string(path).context("read grub config")?;
+    let mut updated = String::new();
+    let mut found = false;
+
+    for line in contents.lines() {
+        if line
+            .trim_start_matches('#')
+            .starts_with("GRUB_ENABLE_CRYPTODISK=")
+        {
+            updated.push_str("GRUB_ENABLE_CRYPTODISK=y\n");
+            found = true;
+        } else {
+            updated.push_str(line);
+            updated.push('\n');
+        }
+    }
+
+    if !found {
+        updated.push_str("GRUB_ENABLE_CRYPTODISK=y\n");
+    }
+
+    fs::write(path, updated).context("write grub config")?;
+    Ok(())
+}
+
 // Sets the GRUB menu resolution and keeps it for the kernel payload
-pub(crate) fn set_grub_gfx(tx: &crossbeam_channel::Sender<InstallerEvent>) -> Result<()> {
+pub(crate) fn set_grub_gfx(
+    tx: &crossbeam_channel::Sender<InstallerEvent>,
+    resolution_override: Option<(u32, u32)>,
+) -> Result<()> {
     let path = "/mnt/etc/default/grub";
     let contents = fs::read_to_string(path).context("read grub config")?;
     let mut updated = String::new();
     let mut found_gfx = false;
     let mut found_payload = false;
-    let (selection, detected) = detect_grub_theme_selection(tx);
+    let (selection, detected) = detect_grub_theme_selection(tx, resolution_override);
     if let Some((width, height)) = detected {
         send_event(
             tx,
@@ -442,8 +566,21 @@ struct GrubThemeSelection {
 
 fn detect_grub_theme_selection(
     tx: &crossbeam_channel::Sender<InstallerEvent>,
+    resolution_override: Option<(u32, u32)>,
 ) -> (GrubThemeSelection, Option<(u32, u32)>) {
-    let detected = detect_grub_resolution(tx);
+    let detected = match resolution_override {
+        Some((width, height)) => {
+            send_event(
+                tx,
+                InstallerEvent::Log(format!(
+                    "Display resolution override in effect: {}x{}",
+                    width, height
+                )),
+            );
+            Some((width, height))
+        }
+        None => detect_grub_resolution(tx),
+    };
     let selection = detected
         .map(|(width, height)| select_grub_theme_selection(width, height))
         .unwrap_or_else(default_grub_theme_selection);
@@ -584,6 +721,20 @@ fn parse_wlr_mode(token: &str) -> Option<(u32, u32)> {
     Some((width, height))
 }
 
+// NEBULA_RESOLUTION value such as `2560x1440`
+pub fn parse_display_resolution(value: &str) -> Option<(u32, u32)> {
+    parse_mode(value).filter(|(width, height)| *width > 0 && *height > 0)
+}
+
+// NEBULA_DISPLAY_SCALE value; the SDDM greeter is usable from 0.5 to 4
+pub fn parse_display_scale(value: &str) -> Option<f32> {
+    value
+        .trim()
+        .parse::<f32>()
+        .ok()
+        .filter(|scale| (0.5..=4.0).contains(scale))
+}
+
 fn scale_from_resolution(width: u32, height: u32) -> f32 {
     if width >= 3840 || height >= 2160 {
         2.0
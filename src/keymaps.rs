@@ -24,6 +24,30 @@ pub fn load_keymaps() -> Result<Vec<String>> {
     Ok(vec!["us".to_string()])
 }
 
+// Console keymaps whose XKB layout has a different name
+const XKB_LAYOUT_RENAMES: [(&str, &str); 3] = [("uk", "gb"), ("la", "latam"), ("dvorak", "us")];
+
+// XKB layout matching a console keymap, e.g. `de-latin1` -> `de`, `de_CH-latin1` -> `ch`
+pub fn xkb_layout_for_keymap(keymap: &str) -> String {
+    let name = keymap.trim().to_ascii_lowercase();
+    let name = name.split('-').next().unwrap_or_default();
+    let layout = match name.split_once('_') {
+        Some((_, country)) => country,
+        None => name.trim_end_matches(|c: char| c.is_ascii_digit()),
+    };
+    XKB_LAYOUT_RENAMES
+        .iter()
+        .find(|(console, _)| *console == layout)
+        .map(|(_, xkb)| xkb.to_string())
+        .unwrap_or_else(|| {
+            if layout.is_empty() {
+                "us".to_string()
+            } else {
+                layout.to_string()
+            }
+        })
+}
+
 // Returns `None` if the keymap is not found
 pub fn find_keymap_index(maps: &[String], value: &str) -> Option<usize> {
     maps.iter().position(|map| map == value)
@@ -0,0 +1,41 @@
+//! Nebula Linux installation library
+//!
+//! The TUI binary is one frontend on top of this crate; a GUI can drive the same
+//! install by filling in an [`installer::InstallConfig`] and calling
+//! [`installer::run_installer`] on a background thread:
+//!
+//! ```no_run
+//! use nebula_installer::installer::{run_installer, InstallConfig};
+//! use nebula_installer::model::InstallerEvent;
+//! use std::sync::{atomic::AtomicBool, Arc};
+//!
+//! # fn frontend(config: InstallConfig) {
+//! let (tx, rx) = crossbeam_channel::unbounded();
+//! let cancel = Arc::new(AtomicBool::new(false));
+//! std::thread::spawn(move || run_installer(tx, &config, cancel));
+//! for event in rx {
+//!     if let InstallerEvent::Done(err) = event {
+//!         println!("finished: {:?}", err);
+//!         break;
+//!     }
+//! }
+//! # }
+//! ```
+//!
+//! See [`model::InstallerEvent`] for the events sent over the channel.
+
+pub mod answers;
+pub mod config;
+pub mod disks;
+pub mod drivers;
+pub mod installer;
+pub mod keymaps;
+pub mod locales;
+mod mirrors;
+pub mod model;
+mod monitors;
+pub mod network;
+pub mod packages;
+pub mod power;
+pub mod selection;
+pub mod timezones;
@@ -0,0 +1,41 @@
+use anyhow::Result;
+use std::fs;
+
+// Locale every install generates so fallback text stays readable
+pub const DEFAULT_LOCALE: &str = "en_US.UTF-8";
+
+// Lists the locales offered by the live system's /etc/locale.gen
+pub fn load_locales() -> Result<Vec<String>> {
+    if let Ok(contents) = fs::read_to_string("/etc/locale.gen") {
+        let mut locales: Vec<String> = contents
+            .lines()
+            .filter_map(|line| {
+                // Entries look like `#de_DE.UTF-8 UTF-8`; the header examples are indented
+                let entry = line.strip_prefix('#').unwrap_or(line);
+                if entry.starts_with(char::is_whitespace) {
+                    return None;
+                }
+                let mut fields = entry.split_whitespace();
+                let (Some(name), Some(_charset), None) =
+                    (fields.next(), fields.next(), fields.next())
+                else {
+                    return None;
+                };
+                Some(name.to_string())
+            })
+            .collect();
+        locales.sort(); // Sort the locales alphabetically
+        locales.dedup(); // Remove any duplicate entries
+        if !locales.is_empty() {
+            return Ok(locales);
+        }
+    }
+
+    // Fallback to the default locale if the list is missing or empty
+    Ok(vec![DEFAULT_LOCALE.to_string()])
+}
+
+// Returns `None` if the locale is not found
+pub fn find_locale_index(locales: &[String], value: &str) -> Option<usize> {
+    locales.iter().position(|locale| locale == value)
+}
@@ -1,27 +1,24 @@
-mod disks;
-mod drivers;
-mod installer;
-mod keymaps;
-mod model;
-mod monitors;
-mod network;
-mod packages;
-mod config;
-mod selection;
-mod timezones;
 mod ui;
 
+// The install logic lives in the library so other frontends can reuse it
+use nebula_installer::{
+    answers, config, disks, drivers, installer, keymaps, locales, model, network, packages, power,
+    selection, timezones,
+};
+
 use std::collections::VecDeque;
 use std::fs::OpenOptions;
 use std::io::{self, Write};
 use std::path::Path;
 use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::thread;
 use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result};
-use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
-use crossterm::terminal::{disable_raw_mode, enable_raw_mode, ClearType};
+use crossterm::event::{self, DisableMouseCapture, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode, is_raw_mode_enabled, ClearType};
 use crossterm::{cursor, execute, terminal::Clear};
 use ratatui::backend::CrosstermBackend;
 use ratatui::style::{Color, Modifier, Style};
@@ -29,57 +26,98 @@ use ratatui::text::{Line, Span};
 use ratatui::Terminal;
 
 // Import everything from our modules
-use crate::disks::{list_disks, DiskInfo};
+use crate::answers::{write_answer_file, AnswerFile};
+use crate::config::config;
+use crate::disks::{list_disks, luks_containers, DiskInfo, ImageTarget};
 use crate::drivers::{
-    detect_gpu_vendors, driver_packages, format_gpu_summary, nvidia_variant_label, GpuVendor,
-    NvidiaVariant,
+    detect_gpu_vendors, driver_packages, format_gpu_summary, nvidia_variant_label, FirmwareChoice,
+    GpuVendor, NvidiaVariant,
+};
+use crate::installer::{
+    available_plymouth_themes, default_swapfile_size_mib, existing_home_disk,
+    find_resumable_install, luks_header_disk, parse_console_font, parse_display_resolution,
+    parse_display_scale, parse_existing_home, parse_swapfile_size, release_target, run_installer,
+    target_mounted, validate_luks_pbkdf, validate_zram_size, FailurePolicy, FirewallChoice,
+    HyprInput, InstallConfig, InstallState, LuksPbkdf, LuksVersion, MakepkgTuning, Shell,
+    SubvolSpec, SudoPolicy, SwapMode, DEFAULT_PACKAGE_CACHE_DIR, LAST_CANCELLABLE_STEP,
+    PLYMOUTH_DISABLED, STEP_NAMES, SWAP_MOUNTPOINT, SWAP_SUBVOLUME,
+};
+use crate::keymaps::{find_keymap_index, load_keymaps, xkb_layout_for_keymap};
+use crate::locales::{find_locale_index, load_locales, DEFAULT_LOCALE};
+use crate::model::{
+    App, InstallerEvent, LogLevel, PowerAction, Step, StepStatus, POWER_COUNTDOWN_SECS,
 };
-use crate::installer::{run_installer, InstallConfig, STEP_NAMES};
-use crate::keymaps::{find_keymap_index, load_keymaps};
-use crate::model::{App, InstallerEvent, Step, StepStatus};
 use crate::network::{
-    active_connection_label, connect_wifi_profile, disconnect_wifi_device, forget_wifi_connection,
-    has_wifi_device, is_network_ready, is_wifi_connected, list_wifi_networks, wifi_device_name,
-    wifi_device_state,
+    active_connection_label, connect_enterprise_wifi_profile, connect_wifi_profile,
+    disconnect_wifi_device, forget_session_wifi_connections, forget_wifi_connection,
+    has_wifi_device, is_network_ready, is_wifi_connected, list_wifi_networks, network_check,
+    start_network_check, start_speed_test, take_network_check_result, wifi_device_name,
+    wifi_device_state, NetworkCheck, WifiNetwork, DEFAULT_NETWORK_RETRIES,
 };
-use crate::packages::required_packages;
+use crate::packages::{load_package_list, required_packages, AurHelper};
+use crate::power::battery_status;
 use crate::selection::{
-    browser_choices, compositor_choices, compositor_labels, editor_choices, labels_for_flags,
-    labels_for_selection, selection_from_app_flags, selection_from_flags_for, terminal_choices,
-    AppSelectionFlags, PackageSelection,
+    browser_choices, compositor_choices, compositor_labels, editor_choices, gaming_packages,
+    labels_for_flags, labels_for_selection, selection_from_app_flags, selection_from_flags_for,
+    system_service_label, system_service_packages, terminal_choices, AppSelectionFlags,
+    BtrfsLayout, InstallProfile, Mitigations, PackageSelection, SystemService, SYSTEM_SERVICES,
 };
 use crate::timezones::{
-    detect_timezone_geoip, detect_timezone_local, find_timezone_index, load_timezones,
+    detect_timezone_from_keymap, detect_timezone_geoip, detect_timezone_local, find_timezone_index,
+    load_country_codes, load_timezones, timezone_country, timezone_error, wifi_country_error,
 };
 use crate::ui::{
-    draw_ui, render_text_input, render_timezone_loading, render_wifi_connecting,
-    render_wifi_searching, run_application_selector, run_confirm_selector, run_disk_selector,
-    run_keymap_selector, run_network_required, run_nvidia_selector, run_review, run_text_input,
-    run_timezone_selector, run_wifi_selector, ConfirmAction, InputAction, InstallSummary,
-    NetworkAction, NvidiaAction, ReviewAction, ReviewItem, SelectionAction, WifiAction, SPINNER,
+    draw_themed, draw_ui, prompt_choice, prompt_line, prompt_new_secret, prompt_yes_no,
+    render_partition_preview, render_text_input, render_timezone_loading, render_wifi_connecting,
+    render_wifi_searching, run_application_selector, run_aur_helper_selector,
+    run_btrfs_layout_selector, run_confirm_selector, run_disk_selector, run_list_selector,
+    run_mitigations_selector, run_multiselect, run_network_required, run_nvidia_selector,
+    run_profile_selector, run_review, run_text_input, run_timezone_selector, run_wifi_selector,
+    serial_console, theme, ConfirmAction, ConfirmMode, InputAction, InstallSummary, NetworkAction,
+    NvidiaAction, ReviewAction, ReviewItem, SelectGroup, SelectionAction, WifiAction, SPINNER,
     SPINNER_LEN, SUMMARY_STEP_COUNT,
 };
 
+// Where the review screen exports the selections by default
+const DEFAULT_ANSWER_FILE_PATH: &str = "/root/nebula-answers.toml";
+
+// Typed to confirm erasing a disk that already holds LUKS containers
+const DESTROY_LUKS_PHRASE: &str = "destroy";
+
+// Size of a NEBULA_TARGET_IMAGE created from scratch; the file is sparse
+const DEFAULT_TARGET_IMAGE_SIZE: &str = "20G";
+
 // Logging
 const LOG_CAPACITY: usize = 200;
 const LOG_FILE_PATH: &str = "/tmp/nebula-installer.log";
+// Panic messages and backtraces, appended so earlier crashes are kept for bug reports
+const CRASH_LOG_PATH: &str = "/tmp/nebula-installer-crash.log";
 
 // Pre-installation setup UI
-#[derive(Clone, Copy, Debug)]
-enum SetupStep {
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SetupStep {
     Network,
     Disk,
     ConfirmDisk,
+    BtrfsLayout,
     Keymap,
+    Locale,
     Timezone,
     Hostname,
     Username,
+    FullName,
     UserPassword,
     EncryptDisk,
     LuksPassword,
     Drivers,
     Swap,
+    ZramSize,
+    GrubPassword,
+    Mitigations,
+    BootSplash,
+    Profile,
     Applications,
+    AurHelper,
     Review,
 }
 
@@ -89,14 +127,14 @@ fn summary_current_index(step: SetupStep, include_drivers: bool) -> usize {
     match step {
         SetupStep::Network => 0,
         SetupStep::Drivers => 1,
-        SetupStep::Disk | SetupStep::ConfirmDisk => {
+        SetupStep::Disk | SetupStep::ConfirmDisk | SetupStep::BtrfsLayout => {
             if include_drivers {
                 2
             } else {
                 1
             }
         }
-        SetupStep::Keymap => {
+        SetupStep::Keymap | SetupStep::Locale => {
             if include_drivers {
                 3
             } else {
@@ -117,7 +155,7 @@ fn summary_current_index(step: SetupStep, include_drivers: bool) -> usize {
                 4
             }
         }
-        SetupStep::Username | SetupStep::UserPassword => {
+        SetupStep::Username | SetupStep::FullName | SetupStep::UserPassword => {
             if include_drivers {
                 6
             } else {
@@ -131,14 +169,69 @@ fn summary_current_index(step: SetupStep, include_drivers: bool) -> usize {
                 6
             }
         }
-        SetupStep::Swap => {
+        SetupStep::Swap
+        | SetupStep::ZramSize
+        | SetupStep::GrubPassword
+        | SetupStep::Mitigations
+        | SetupStep::BootSplash => {
             if include_drivers {
                 8
             } else {
                 7
             }
         }
-        SetupStep::Applications | SetupStep::Review => step_count,
+        SetupStep::Profile | SetupStep::Applications | SetupStep::AurHelper | SetupStep::Review => {
+            step_count
+        }
+    }
+}
+
+// Groups follow-up steps with the step that opens them
+fn setup_section(step: SetupStep) -> SetupStep {
+    match step {
+        SetupStep::ConfirmDisk | SetupStep::BtrfsLayout => SetupStep::Disk,
+        SetupStep::FullName | SetupStep::UserPassword => SetupStep::Username,
+        SetupStep::LuksPassword => SetupStep::EncryptDisk,
+        SetupStep::ZramSize => SetupStep::Swap,
+        SetupStep::Mitigations | SetupStep::BootSplash => SetupStep::GrubPassword,
+        other => other,
+    }
+}
+
+// Swap backing and the btrfs subvolumes to create; a preserved /home replaces @home
+fn target_layout(
+    btrfs_layout: BtrfsLayout,
+    keep_home: bool,
+    swap_enabled: bool,
+    swap_file: bool,
+    swapfile_size: &str,
+) -> (SwapMode, Vec<SubvolSpec>) {
+    // The swapfile gets its own subvolume so snapshots of @ never contain it
+    let swap_mode = match parse_swapfile_size(swapfile_size) {
+        Ok(size_mib) if swap_enabled && swap_file => SwapMode::File { size_mib },
+        _ => SwapMode::Zram,
+    };
+    let mut subvolumes: Vec<SubvolSpec> = btrfs_layout
+        .subvolumes()
+        .into_iter()
+        .filter(|subvol| !keep_home || subvol.mountpoint != "/home")
+        .collect();
+    if swap_mode != SwapMode::Zram {
+        subvolumes.push(SubvolSpec::new(SWAP_SUBVOLUME, SWAP_MOUNTPOINT, ""));
+    }
+    (swap_mode, subvolumes)
+}
+
+// Wi-Fi regulatory domain: NEBULA_WIFI_COUNTRY, else the timezone's country when there is Wi-Fi
+fn wifi_country_for(
+    override_code: Option<&str>,
+    wifi_hardware: bool,
+    timezone: &str,
+) -> Option<String> {
+    match override_code {
+        Some(code) => Some(code.to_string()),
+        None if wifi_hardware => timezone_country(timezone),
+        None => None,
     }
 }
 
@@ -197,8 +290,67 @@ fn build_install_summary(
     }
 }
 
+// Handled before the root check so they work anywhere without touching the terminal
+const USAGE: &str = "Usage: nebula [OPTIONS]
+
+Interactive installer for Nebula Linux. Run as root from the live ISO.
+
+Options:
+      --json     Print installer events as JSON lines instead of the progress screen
+  -V, --version  Print the version and build commit
+  -h, --help     Print this help
+
+Advanced settings are read from NEBULA_* environment variables, see the README.";
+
+// Version plus the git commit embedded by build.rs, e.g. `nebula 0.0.1 (1a2b3c4d5e6f)`
+fn version_string() -> String {
+    match option_env!("NEBULA_GIT_COMMIT") {
+        Some(commit) => format!("nebula {} ({})", env!("CARGO_PKG_VERSION"), commit),
+        None => format!("nebula {}", env!("CARGO_PKG_VERSION")),
+    }
+}
+
+// Command line flags; everything else is configured through NEBULA_* variables
+struct CliArgs {
+    json_events: bool, // Print installer events as JSON lines instead of drawing the progress screen
+}
+
+// Parses the flags, returning None once --version or --help has been printed
+fn parse_args() -> Result<Option<CliArgs>> {
+    let mut args = CliArgs { json_events: false };
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--json" => args.json_events = true,
+            "-V" | "--version" => {
+                println!("{}", version_string());
+                return Ok(None);
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return Ok(None);
+            }
+            _ => anyhow::bail!("unknown argument `{}`, see `nebula --help`", arg),
+        }
+    }
+    Ok(Some(args))
+}
+
 fn main() -> Result<()> {
+    let Some(args) = parse_args()? else {
+        return Ok(());
+    };
+    install_panic_hook();
     dotenvy::dotenv().ok();
+    let json_events = args.json_events;
+    // Dumb or tiny terminals get plain line prompts and step-by-step progress
+    let serial_console = serial_console();
+    let progress_output = if json_events {
+        ProgressOutput::Json
+    } else if serial_console {
+        ProgressOutput::Plain
+    } else {
+        ProgressOutput::Screen
+    };
 
     // The installer must be run as root
     let allow_nonroot = std::env::var("NEBULA_DEV_ALLOW_NONROOT").ok().as_deref() == Some("1");
@@ -208,45 +360,492 @@ fn main() -> Result<()> {
         return Ok(());
     }
 
+    // Test mode: a loop device backed by NEBULA_TARGET_IMAGE is the only install target
+    let target_image = match std::env::var("NEBULA_TARGET_IMAGE")
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+    {
+        Some(path) => {
+            let size = std::env::var("NEBULA_TARGET_IMAGE_SIZE")
+                .ok()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+                .unwrap_or_else(|| DEFAULT_TARGET_IMAGE_SIZE.to_string());
+            Some(ImageTarget::attach(&path, &size).context("attach target image")?)
+        }
+        None => None,
+    };
+
     // Initial data loading
-    let disks = list_disks().context("list disks")?;
+    let mut disks = match &target_image {
+        Some(image) => vec![image.disk.clone()],
+        None => list_disks().context("list disks")?,
+    };
     if disks.is_empty() {
         println!("No disks detected.");
         return Ok(());
     }
+    // The disk holding a detached LUKS header cannot be the install target
+    let luks_detached_header = std::env::var("NEBULA_LUKS_HEADER")
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    if let Some(header) = &luks_detached_header {
+        match luks_header_disk(header) {
+            Ok(header_disk) => disks.retain(|disk| disk.name != header_disk),
+            Err(err) => {
+                println!("Invalid NEBULA_LUKS_HEADER: {}", err);
+                return Ok(());
+            }
+        }
+        if disks.is_empty() {
+            println!("No disks left besides the one holding the LUKS header.");
+            return Ok(());
+        }
+    }
+    // GRUB has to find the LUKS1 header on the disk to unlock /boot
+    let full_disk_encryption =
+        std::env::var("NEBULA_FULL_DISK_ENCRYPTION").ok().as_deref() == Some("1");
+    if full_disk_encryption && luks_detached_header.is_some() {
+        println!("NEBULA_FULL_DISK_ENCRYPTION cannot be combined with NEBULA_LUKS_HEADER.");
+        return Ok(());
+    }
+    let luks_version = match std::env::var("NEBULA_LUKS_VERSION")
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+    {
+        Some(value) => match LuksVersion::parse(&value) {
+            Some(version) => Some(version),
+            None => {
+                println!("Invalid NEBULA_LUKS_VERSION: use luks1 or luks2");
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+    if full_disk_encryption && luks_version == Some(LuksVersion::Luks2) {
+        println!("NEBULA_FULL_DISK_ENCRYPTION requires NEBULA_LUKS_VERSION=luks1.");
+        return Ok(());
+    }
+    let luks_version = if full_disk_encryption {
+        LuksVersion::Luks1
+    } else {
+        luks_version.unwrap_or_default()
+    };
+    let luks_pbkdf = LuksPbkdf {
+        algorithm: std::env::var("NEBULA_LUKS_PBKDF")
+            .ok()
+            .map(|value| value.trim().to_ascii_lowercase())
+            .filter(|value| !value.is_empty()),
+        memory_kib: match std::env::var("NEBULA_LUKS_PBKDF_MEMORY")
+            .ok()
+            .filter(|value| !value.trim().is_empty())
+        {
+            Some(value) => match value.trim().parse::<u32>() {
+                Ok(kib) => Some(kib),
+                Err(_) => {
+                    println!("Invalid NEBULA_LUKS_PBKDF_MEMORY: use KiB, e.g. 262144");
+                    return Ok(());
+                }
+            },
+            None => None,
+        },
+    };
+    if let Err(err) = validate_luks_pbkdf(luks_version, &luks_pbkdf) {
+        println!("Invalid NEBULA_LUKS_PBKDF settings: {}", err);
+        return Ok(());
+    }
+    if let Some(message) = std::env::var("NEBULA_NTP_SERVER")
+        .ok()
+        .and_then(|value| ntp_server_error(value.trim()))
+    {
+        println!("Invalid NEBULA_NTP_SERVER: {}", message);
+        return Ok(());
+    }
+    let wifi_country_override = std::env::var("NEBULA_WIFI_COUNTRY")
+        .ok()
+        .map(|value| value.trim().to_ascii_uppercase())
+        .filter(|value| !value.is_empty());
+    if let Some(message) = wifi_country_override
+        .as_deref()
+        .and_then(|code| wifi_country_error(&load_country_codes(), code))
+    {
+        println!("Invalid NEBULA_WIFI_COUNTRY: {}", message);
+        return Ok(());
+    }
+    let sudo_timeout = match std::env::var("NEBULA_SUDO_TIMEOUT")
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+    {
+        Some(value) => match value.trim().parse::<i32>() {
+            Ok(minutes) if minutes >= -1 => Some(minutes),
+            _ => {
+                println!("Invalid NEBULA_SUDO_TIMEOUT: use minutes, 0 or -1, e.g. 15");
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+    let console_font = match std::env::var("NEBULA_CONSOLE_FONT")
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+    {
+        Some(value) => match parse_console_font(&value) {
+            Some(font) => font,
+            None => {
+                println!("Invalid NEBULA_CONSOLE_FONT: use normal, large or a font like ter-124b");
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+    // Escape hatches for misdetected monitors; they replace the GRUB and SDDM theme detection
+    let display_resolution = match std::env::var("NEBULA_RESOLUTION")
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+    {
+        Some(value) => match parse_display_resolution(&value) {
+            Some(resolution) => Some(resolution),
+            None => {
+                println!("Invalid NEBULA_RESOLUTION: use WIDTHxHEIGHT, e.g. 2560x1440");
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+    let display_scale = match std::env::var("NEBULA_DISPLAY_SCALE")
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+    {
+        Some(value) => match parse_display_scale(&value) {
+            Some(scale) => Some(scale),
+            None => {
+                println!("Invalid NEBULA_DISPLAY_SCALE: use a factor from 0.5 to 4, e.g. 1.5");
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+    // A preserved /home is mounted without formatting, so its disk cannot be the install target
+    let existing_home = match std::env::var("NEBULA_EXISTING_HOME") {
+        Ok(value) if !value.trim().is_empty() => match parse_existing_home(value.trim()) {
+            Ok(home) => Some(home),
+            Err(err) => {
+                println!("Invalid NEBULA_EXISTING_HOME: {}", err);
+                return Ok(());
+            }
+        },
+        _ => None,
+    };
+    if let Some(home) = &existing_home {
+        let confirmation = std::env::var("NEBULA_EXISTING_HOME_CONFIRM").unwrap_or_default();
+        if confirmation.trim() != home.device {
+            println!(
+                "Set NEBULA_EXISTING_HOME_CONFIRM={} to confirm keeping it as /home.",
+                home.device
+            );
+            return Ok(());
+        }
+        match existing_home_disk(&home.device) {
+            Ok(home_disk) => disks.retain(|disk| disk.name != home_disk),
+            Err(err) => {
+                println!("Invalid NEBULA_EXISTING_HOME: {}", err);
+                return Ok(());
+            }
+        }
+        if disks.is_empty() {
+            println!("No disks left besides the one holding the existing /home.");
+            return Ok(());
+        }
+    }
+    let parallel_downloads = match std::env::var("NEBULA_PARALLEL_DOWNLOADS")
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+    {
+        Some(value) => match value.trim().parse::<u8>() {
+            Ok(count) if count > 0 => count,
+            _ => {
+                println!("Invalid NEBULA_PARALLEL_DOWNLOADS: use a number from 1 to 255");
+                return Ok(());
+            }
+        },
+        None => 5,
+    };
+    let firewall = match std::env::var("NEBULA_FIREWALL")
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+    {
+        Some(value) => match FirewallChoice::parse(&value) {
+            Some(firewall) => firewall,
+            None => {
+                println!("Invalid NEBULA_FIREWALL: use none, ufw or firewalld");
+                return Ok(());
+            }
+        },
+        None => FirewallChoice::default(),
+    };
+    let shell = match std::env::var("NEBULA_SHELL")
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+    {
+        Some(value) => match Shell::parse(&value) {
+            Some(shell) => shell,
+            _ => {
+                println!("Invalid NEBULA_SHELL: use zsh, bash or fish");
+                return Ok(());
+            }
+        },
+        None => Shell::default(),
+    };
+    let firmware = match std::env::var("NEBULA_FIRMWARE")
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+    {
+        Some(value) => match FirmwareChoice::parse(&value) {
+            Some(firmware) => firmware,
+            _ => {
+                println!("Invalid NEBULA_FIRMWARE: use full, minimal or vendor");
+                return Ok(());
+            }
+        },
+        None => FirmwareChoice::default(),
+    };
+    let network_retries = match std::env::var("NEBULA_NETWORK_RETRIES")
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+    {
+        Some(value) => match value.trim().parse::<u32>() {
+            Ok(network_retries) => network_retries,
+            _ => {
+                println!("Invalid NEBULA_NETWORK_RETRIES: use a whole number");
+                return Ok(());
+            }
+        },
+        None => DEFAULT_NETWORK_RETRIES,
+    };
+    let optional_failure_policy = match std::env::var("NEBULA_OPTIONAL_FAILURES")
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+    {
+        Some(value) => match FailurePolicy::parse(&value) {
+            Some(optional_failure_policy) => optional_failure_policy,
+            _ => {
+                println!("Invalid NEBULA_OPTIONAL_FAILURES: use continue or abort");
+                return Ok(());
+            }
+        },
+        None => FailurePolicy::default(),
+    };
     let mut base_packages = required_packages();
+    let imported_packages = match std::env::var("NEBULA_EXTRA_PACKAGES_FILE") {
+        Ok(path) if !path.trim().is_empty() => load_package_list(path.trim())?,
+        _ => PackageSelection::default(),
+    };
 
-    // Set up the terminal for TUI interaction
-    enable_raw_mode().context("enable raw mode")?;
-    clear_screen()?;
+    // Set up the terminal for TUI interaction; the guard restores it however setup exits
+    let _terminal_guard = if serial_console {
+        None
+    } else {
+        Some(TerminalGuard::enable()?)
+    };
     let mut terminal =
         Terminal::new(CrosstermBackend::new(io::stdout())).context("init terminal")?;
 
+    // Offer to resume an interrupted install found on one of the disks
+    if let Some(state) = find_resumable_install(&disks).filter(|_| !serial_console) {
+        match prompt_resume(&mut terminal, state)? {
+            ResumeChoice::Resume(config) => {
+                return run_install_progress(&mut terminal, *config, progress_output)
+            }
+            ResumeChoice::Fresh => clear_screen()?,
+            ResumeChoice::Quit => return Ok(()),
+        }
+    }
+
     let mut selected_disk: Option<DiskInfo> = None;
     let mut keymap = "us".to_string();
     let keymaps = load_keymaps().unwrap_or_else(|_| vec!["us".to_string()]);
+    let mut locale = DEFAULT_LOCALE.to_string();
+    let locales = load_locales().unwrap_or_else(|_| vec![DEFAULT_LOCALE.to_string()]);
     let timezones = load_timezones().unwrap_or_else(|_| vec!["UTC".to_string()]);
     let mut timezone = detect_timezone_local(&timezones).unwrap_or_default();
     let mut hostname = "nebula".to_string();
+    let mut hostname_input_error: Option<&str> = None;
     let mut network_label: Option<String> = None;
     let mut username = String::new();
+    let mut username_input_error: Option<UsernameError> = None;
+    let mut full_name = String::new();
+    let mut full_name_input_error: Option<&str> = None;
     let mut user_password = String::new();
     let mut luks_password = String::new();
     let mut encrypt_disk = true;
     let mut swap_enabled = true;
+    let mut zram_size = std::env::var("NEBULA_ZRAM_SIZE")
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(|| "ram".to_string());
+    // The Swap step's checkbox picks a btrfs swapfile over zram
+    let mut swap_file = false;
+    let mut swapfile_size = default_swapfile_size_mib().to_string();
+    let mut swap_size_error: Option<&str> = None;
+    let mut grub_password: Option<String> = None;
     let mut app_flags = AppSelectionFlags::new();
     let mut app_selection = PackageSelection::default();
+    let mut aur_helper = AurHelper::Yay;
+    let mut install_profile = InstallProfile::Full;
+    let mut mitigations = Mitigations::Default;
+    let plymouth_themes = available_plymouth_themes();
+    let mut plymouth_theme: Option<String> = None;
+    let mut btrfs_layout = BtrfsLayout::Default;
     let gpu_vendors = detect_gpu_vendors().unwrap_or_default();
     let include_drivers = gpu_vendors.contains(&GpuVendor::Nvidia);
+    let wifi_hardware = has_wifi_device().unwrap_or(false);
     let mut nvidia_variant: Option<NvidiaVariant> = None;
     let kernel_package = "linux".to_string();
     let kernel_headers = "linux-headers".to_string();
     let mut force_network = false;
     let offline_only = std::env::var("NEBULA_OFFLINE_ONLY").ok().as_deref() == Some("1");
+    let rank_mirrors = std::env::var("NEBULA_RANK_MIRRORS").ok().as_deref() == Some("1");
+    let mut post_install_commands = config().hooks.post_install.clone();
+    if let Ok(command) = std::env::var("NEBULA_POST_INSTALL_COMMAND") {
+        if !command.trim().is_empty() {
+            post_install_commands.push(command);
+        }
+    }
+    let abort_on_hook_failure = config().hooks.abort_on_failure
+        || std::env::var("NEBULA_POST_INSTALL_ABORT").ok().as_deref() == Some("1");
+    let verbose_boot = std::env::var("NEBULA_VERBOSE_BOOT").ok().as_deref() == Some("1");
+    let secure_wipe = std::env::var("NEBULA_SECURE_WIPE").ok().as_deref() == Some("1");
+    let mut scan_bad_blocks = false;
+    let copy_installer = std::env::var("NEBULA_COPY_INSTALLER").ok().as_deref() == Some("1");
+    let compress_logs = std::env::var("NEBULA_COMPRESS_LOGS").ok().as_deref() == Some("1");
+    let makepkg = MakepkgTuning {
+        parallel: std::env::var("NEBULA_MAKEPKG_PARALLEL").ok().as_deref() != Some("0"),
+        ccache: std::env::var("NEBULA_MAKEPKG_CCACHE").ok().as_deref() == Some("1"),
+    };
+    let sudo_policy = SudoPolicy {
+        nopasswd: std::env::var("NEBULA_SUDO_NOPASSWD").ok().as_deref() == Some("1"),
+        timestamp_timeout: sudo_timeout,
+        insults: std::env::var("NEBULA_SUDO_INSULTS").ok().as_deref() == Some("1"),
+    };
+    // `1` prefetches into the default cache; an absolute path picks the cache directory
+    let package_cache = match std::env::var("NEBULA_PREFETCH_PACKAGES").ok() {
+        Some(value) if value == "1" => Some(DEFAULT_PACKAGE_CACHE_DIR.to_string()),
+        Some(value) if value.starts_with('/') => Some(value),
+        _ => None,
+    };
+    // SSD mount tuning follows the selected disk unless forced either way
+    let ssd_tuning_override = match std::env::var("NEBULA_SSD_TUNING").ok().as_deref() {
+        Some("1") => Some(true),
+        Some("0") => Some(false),
+        _ => None,
+    };
+    let ntp_server = std::env::var("NEBULA_NTP_SERVER")
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+
+    // Serial consoles answer the essentials line by line and keep defaults for the rest
+    if serial_console {
+        println!("Nebula installer (serial console mode)");
+        if !is_network_ready().unwrap_or(false) && !offline_only {
+            println!("Warning: no network connection detected; configure it before installing.");
+        }
+        let disk_labels: Vec<String> = disks
+            .iter()
+            .map(|disk| format!("{} [{}]", disk.label(), disk.kind_label()))
+            .collect();
+        let disk = disks[prompt_choice("Install disk", &disk_labels, 0)?].clone();
+        let question = format!("Erase all data on {}?", disk.device_path());
+        if !prompt_yes_no(&question, false)? {
+            println!("Nothing was changed.");
+            return Ok(());
+        }
+        let encrypted = luks_containers(&disk);
+        if !encrypted.is_empty() {
+            let question = format!(
+                "{} holds encrypted volumes ({}) that cannot be recovered. Destroy them?",
+                disk.device_path(),
+                encrypted.join(", ")
+            );
+            if !prompt_yes_no(&question, false)? {
+                println!("Nothing was changed.");
+                return Ok(());
+            }
+        }
+        selected_disk = Some(disk);
+        keymap = prompt_line("Keymap", &keymap, |value| {
+            (!keymaps.iter().any(|known| known == value)).then_some("Unknown keymap")
+        })?;
+        locale = prompt_line("Locale", &locale, |value| {
+            (!locales.iter().any(|known| known == value)).then_some("Unknown locale")
+        })?;
+        let default_timezone = if timezone.is_empty() {
+            "UTC".to_string()
+        } else {
+            timezone.clone()
+        };
+        timezone = prompt_line("Timezone", &default_timezone, |value| {
+            timezone_error(&timezones, value)
+        })?;
+        hostname = prompt_line("Hostname", &hostname, hostname_error)?;
+        username = prompt_line("Username", "", |value| {
+            validate_username(value).err().map(UsernameError::message)
+        })?;
+        full_name = prompt_line("Full name (optional)", "", full_name_error)?;
+        user_password = prompt_new_secret("User password")?;
+        encrypt_disk = prompt_yes_no("Encrypt the disk", encrypt_disk)?;
+        if encrypt_disk {
+            luks_password = prompt_new_secret("Disk encryption passphrase")?;
+        }
+        if include_drivers {
+            let variants = [
+                NvidiaVariant::Open,
+                NvidiaVariant::Proprietary,
+                NvidiaVariant::Nouveau,
+            ];
+            let labels: Vec<String> = variants
+                .iter()
+                .map(|variant| format!("NVIDIA {}", nvidia_variant_label(*variant)))
+                .collect();
+            nvidia_variant = Some(variants[prompt_choice("GPU driver", &labels, 0)?]);
+        }
+        swap_enabled = prompt_yes_no("Enable zram swap", swap_enabled)?;
+        let profiles = [InstallProfile::Full, InstallProfile::Minimal];
+        let labels: Vec<String> = profiles
+            .iter()
+            .map(|profile| profile.label().to_string())
+            .collect();
+        install_profile = profiles[prompt_choice("Install profile", &labels, 0)?];
+        app_selection = selection_from_app_flags(&app_flags);
+        if let Some(battery) = battery_status().filter(|status| status.is_low()) {
+            let question = format!(
+                "Battery is at {}% with no charger connected. Install anyway",
+                battery.capacity
+            );
+            if !prompt_yes_no(&question, false)? {
+                println!("Nothing was changed.");
+                return Ok(());
+            }
+        }
+        if !prompt_yes_no("Start the installation", false)? {
+            println!("Nothing was changed.");
+            return Ok(());
+        }
+    }
 
     // The main setup loop
     let mut step = SetupStep::Network;
+    let mut editing_from_review: Option<SetupStep> = None;
+    // Result of the last answer file export, shown on the review screen
+    let mut review_notice: Option<String> = None;
     'setup: loop {
+        // The serial flow above has already collected every answer
+        if serial_console {
+            break;
+        }
         match step {
             SetupStep::Network => {
                 if std::env::var("NEBULA_SKIP_NETWORK").ok().as_deref() == Some("1") {
@@ -296,19 +895,32 @@ fn main() -> Result<()> {
                 if !wifi_supported {
                     match run_network_required(&mut terminal, &summary)? {
                         NetworkAction::Retry => {}
-                        NetworkAction::Quit => {
-                            disable_raw_mode().context("disable raw mode")?;
-                            let _ = clear_screen();
-                            return Ok(());
-                        }
+                        NetworkAction::Quit => return Ok(()),
                     }
                     continue;
                 }
                 let mut status_message: Option<String> = None;
                 let mut wifi_connected = false;
                 let mut last_connect_at: Option<Instant> = None;
+                let mut internet_ready = false;
                 loop {
-                    let mut internet_ready = is_network_ready().unwrap_or(false);
+                    // A working connection only counts as lost once the background retries
+                    // fail as well; the selector returns as soon as a check finishes
+                    if let Some(ready) = take_network_check_result() {
+                        internet_ready = ready;
+                        status_message = (!ready && wifi_connected)
+                            .then(|| "Connected to Wi-Fi but no internet access.".to_string());
+                    } else if network_check() != NetworkCheck::Running {
+                        let ready_now = is_network_ready().unwrap_or(false);
+                        if internet_ready && !ready_now {
+                            start_network_check(network_retries);
+                        } else {
+                            internet_ready = ready_now;
+                        }
+                    }
+                    if internet_ready {
+                        start_speed_test();
+                    }
                     if internet_ready && network_label.is_none() {
                         network_label = active_connection_label().ok().flatten();
                         if network_label.is_none() {
@@ -337,7 +949,7 @@ fn main() -> Result<()> {
                         internet_ready,
                         &summary,
                     )?;
-                    let networks = match list_wifi_networks() {
+                    let mut networks = match list_wifi_networks() {
                         Ok(list) => list,
                         Err(err) => {
                             status_message = Some(err.to_string());
@@ -369,22 +981,130 @@ fn main() -> Result<()> {
                         swap_enabled,
                         nvidia_variant,
                     );
-                    match run_wifi_selector(
+                    let mut action = run_wifi_selector(
                         &mut terminal,
                         &networks,
                         status_message.as_deref(),
                         wifi_connected,
                         internet_ready,
                         &summary,
-                    )? {
+                    )?;
+                    // A hidden network is entered by hand, then connects like a listed one
+                    if let WifiAction::Hidden = action {
+                        let controls = vec![
+                            Line::from(vec![
+                                Span::styled("Ctrl+U", Style::default().fg(Color::Cyan)),
+                                Span::raw(" or "),
+                                Span::styled("Backspace", Style::default().fg(Color::Cyan)),
+                                Span::raw(" clears the input"),
+                            ]),
+                            Line::from("Enter the name (SSID) of the hidden network."),
+                        ];
+                        let info = vec![Line::from("Press Enter to continue.")];
+                        match run_text_input(
+                            &mut terminal,
+                            "Hidden network",
+                            &controls,
+                            &info,
+                            "SSID",
+                            None,
+                            false,
+                            &summary,
+                        )? {
+                            InputAction::Submit(value) if !value.trim().is_empty() => {
+                                // The security is unknown, so the password prompt always shows
+                                networks.push(WifiNetwork {
+                                    ssid: value.trim().to_string(),
+                                    signal: 0,
+                                    security: "hidden".to_string(),
+                                    in_use: false,
+                                    hidden: true,
+                                });
+                                action = WifiAction::Submit(networks.len() - 1);
+                            }
+                            InputAction::Submit(_) | InputAction::Back => continue,
+                            InputAction::Quit => {
+                                forget_session_wifi_connections();
+                                return Ok(());
+                            }
+                        }
+                    }
+                    match action {
                         WifiAction::Submit(index) => {
                             let Some(network) = networks.get(index) else {
                                 continue;
                             };
                             let needs_password = !network.is_open();
                             let mut password: Option<String> = None;
+                            // WPA2-Enterprise networks also ask for an identity (username)
+                            let mut identity: Option<String> = None;
+                            if network.is_enterprise() {
+                                let controls = vec![
+                                    Line::from(vec![
+                                        Span::styled("Ctrl+U", Style::default().fg(Color::Cyan)),
+                                        Span::raw(" or "),
+                                        Span::styled("Backspace", Style::default().fg(Color::Cyan)),
+                                        Span::raw(" clears the input"),
+                                    ]),
+                                    Line::from(format!(
+                                        "Enter your identity for \"{}\".",
+                                        network.ssid
+                                    )),
+                                ];
+                                let info = vec![Line::from("Press Enter to continue.")];
+                                loop {
+                                    let summary = build_install_summary(
+                                        step,
+                                        include_drivers,
+                                        network_label.as_deref(),
+                                        selected_disk.as_ref(),
+                                        &keymap,
+                                        &timezone,
+                                        &hostname,
+                                        &username,
+                                        &user_password,
+                                        &luks_password,
+                                        encrypt_disk,
+                                        swap_enabled,
+                                        nvidia_variant,
+                                    );
+                                    match run_text_input(
+                                        &mut terminal,
+                                        "Wi-Fi identity",
+                                        &controls,
+                                        &info,
+                                        "Identity",
+                                        None,
+                                        false,
+                                        &summary,
+                                    )? {
+                                        InputAction::Submit(value) => {
+                                            if value.trim().is_empty() {
+                                                continue;
+                                            }
+                                            identity = Some(value.trim().to_string());
+                                            break;
+                                        }
+                                        InputAction::Back => break,
+                                        InputAction::Quit => {
+                                            forget_session_wifi_connections();
+                                            return Ok(());
+                                        }
+                                    }
+                                }
+                                if identity.is_none() {
+                                    continue;
+                                }
+                            }
                             if needs_password {
                                 let mut password_error: Option<String> = None;
+                                let prompt = match &identity {
+                                    Some(identity) => format!(
+                                        "Enter the password for \"{}\" on \"{}\".",
+                                        identity, network.ssid
+                                    ),
+                                    None => format!("Enter password for \"{}\".", network.ssid),
+                                };
                                 let controls = vec![
                                     Line::from(vec![
                                         Span::styled("Ctrl+U", Style::default().fg(Color::Cyan)),
@@ -392,7 +1112,7 @@ fn main() -> Result<()> {
                                         Span::styled("Backspace", Style::default().fg(Color::Cyan)),
                                         Span::raw(" clears the input"),
                                     ]),
-                                    Line::from(format!("Enter password for \"{}\".", network.ssid)),
+                                    Line::from(prompt),
                                 ];
                                 loop {
                                     let info = if let Some(error_message) = &password_error {
@@ -400,6 +1120,10 @@ fn main() -> Result<()> {
                                             error_message,
                                             Style::default().fg(Color::Red),
                                         ))]
+                                    } else if network.hidden {
+                                        vec![Line::from(
+                                            "Press Enter to connect. Leave it empty for an open network.",
+                                        )]
                                     } else {
                                         vec![Line::from("Press Enter to connect.")]
                                     };
@@ -429,7 +1153,7 @@ fn main() -> Result<()> {
                                         &summary,
                                     )? {
                                         InputAction::Submit(value) => {
-                                            if value.is_empty() {
+                                            if value.is_empty() && !network.hidden {
                                                 continue;
                                             }
                                             let start = Instant::now();
@@ -449,16 +1173,27 @@ fn main() -> Result<()> {
                                                 &summary,
                                             )?;
                                             let _ = disconnect_wifi_device();
-                                            let _ = forget_wifi_connection(&network.ssid);
-                                            let device = wifi_device_name().ok().flatten();
                                             let connection_name =
                                                 format!("nebula-{}", network.ssid);
-                                            match connect_wifi_profile(
-                                                &network.ssid,
-                                                Some(&value),
-                                                device.as_deref(),
-                                                Some(&connection_name),
-                                            ) {
+                                            let _ = forget_wifi_connection(&connection_name);
+                                            let device = wifi_device_name().ok().flatten();
+                                            let connected = match &identity {
+                                                Some(identity) => connect_enterprise_wifi_profile(
+                                                    &network.ssid,
+                                                    identity,
+                                                    &value,
+                                                    device.as_deref(),
+                                                    Some(&connection_name),
+                                                ),
+                                                None => connect_wifi_profile(
+                                                    &network.ssid,
+                                                    Some(&value),
+                                                    device.as_deref(),
+                                                    Some(&connection_name),
+                                                    network.hidden,
+                                                ),
+                                            };
+                                            match connected {
                                                 Ok(()) => {
                                                     while start.elapsed() < Duration::from_secs(8) {
                                                         let spinner_idx =
@@ -515,10 +1250,17 @@ fn main() -> Result<()> {
                                                 Err(err) => {
                                                     let err_msg = err.to_string();
                                                     if is_wifi_auth_error(&err_msg) {
-                                                        password_error =
-                                                            Some("Incorrect password.".to_string());
-                                                        let _ =
-                                                            forget_wifi_connection(&network.ssid);
+                                                        password_error = Some(
+                                                            if identity.is_some() {
+                                                                "Incorrect identity or password."
+                                                            } else {
+                                                                "Incorrect password."
+                                                            }
+                                                            .to_string(),
+                                                        );
+                                                        let _ = forget_wifi_connection(
+                                                            &connection_name,
+                                                        );
                                                         continue;
                                                     }
                                                     status_message = Some(err_msg);
@@ -528,8 +1270,7 @@ fn main() -> Result<()> {
                                         }
                                         InputAction::Back => break,
                                         InputAction::Quit => {
-                                            disable_raw_mode().context("disable raw mode")?;
-                                            let _ = clear_screen();
+                                            forget_session_wifi_connections();
                                             return Ok(());
                                         }
                                     }
@@ -540,14 +1281,15 @@ fn main() -> Result<()> {
                             }
                             if network.is_open() {
                                 let _ = disconnect_wifi_device();
-                                let _ = forget_wifi_connection(&network.ssid);
-                                let device = wifi_device_name().ok().flatten();
                                 let connection_name = format!("nebula-{}", network.ssid);
+                                let _ = forget_wifi_connection(&connection_name);
+                                let device = wifi_device_name().ok().flatten();
                                 if let Err(err) = connect_wifi_profile(
                                     &network.ssid,
                                     None,
                                     device.as_deref(),
                                     Some(&connection_name),
+                                    network.hidden,
                                 ) {
                                     status_message = Some(err.to_string());
                                     continue;
@@ -595,23 +1337,19 @@ fn main() -> Result<()> {
                                     continue;
                                 }
                             }
-                            internet_ready = is_network_ready().unwrap_or(false);
-                            if internet_ready {
-                                network_label = active_connection_label().ok().flatten();
-                                if network_label.is_none() {
-                                    network_label = Some(network.ssid.clone());
-                                }
-                                status_message = None;
-                            } else {
-                                status_message =
-                                    Some("Connected to Wi-Fi but no internet access.".to_string());
-                            }
+                            // DHCP and DNS can lag behind the association, so retry in the
+                            // background before giving up; the loop picks up the result
+                            internet_ready = false;
+                            network_label = None;
+                            status_message = Some("Checking internet access...".to_string());
+                            start_network_check(network_retries);
                             continue;
                         }
                         WifiAction::Rescan => {
                             status_message = None;
                         }
                         WifiAction::Refresh => {} // No-op, handled by loop
+                        WifiAction::Hidden => {}  // Turned into a Submit above
                         WifiAction::Continue => {
                             if internet_ready {
                                 if gpu_vendors.contains(&GpuVendor::Nvidia) {
@@ -623,8 +1361,7 @@ fn main() -> Result<()> {
                             }
                         }
                         WifiAction::Quit => {
-                            disable_raw_mode().context("disable raw mode")?;
-                            let _ = clear_screen();
+                            forget_session_wifi_connections();
                             return Ok(());
                         }
                     }
@@ -659,11 +1396,7 @@ fn main() -> Result<()> {
                             step = SetupStep::Network;
                         }
                     }
-                    SelectionAction::Quit => {
-                        disable_raw_mode().context("disable raw mode")?;
-                        let _ = clear_screen();
-                        return Ok(());
-                    }
+                    SelectionAction::Quit => return Ok(()),
                 }
             }
             SetupStep::ConfirmDisk => {
@@ -692,8 +1425,11 @@ fn main() -> Result<()> {
                         Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
                     )),
                     Line::from(vec![
-                        Span::styled(" ", Style::default().fg(Color::White)),
-                        Span::styled(" 󰋊  ", Style::default().fg(Color::LightBlue)),
+                        Span::styled(theme().glyph(" ", "->"), Style::default().fg(Color::White)),
+                        Span::styled(
+                            theme().glyph(" 󰋊  ", " "),
+                            Style::default().fg(Color::LightBlue),
+                        ),
                         Span::styled(disk.label(), Style::default().add_modifier(Modifier::BOLD)),
                     ]),
                     Line::from(""),
@@ -704,25 +1440,93 @@ fn main() -> Result<()> {
                         Style::default().fg(Color::Magenta),
                     )),
                     Line::from(Span::styled(
-                        "Choose Yes to continue or No to go back",
+                        "Type the device path to continue or press Esc to go back",
                         Style::default().fg(Color::White),
                     )),
                 ];
+                let device_path = disk.device_path();
                 match run_confirm_selector(
                     &mut terminal,
                     "Confirm disk erase",
                     &warning_lines,
                     &info_lines,
+                    ConfirmMode::TypedPhrase(&device_path),
+                    Some((
+                        "Scan the disk for bad blocks first (read-only, slow)",
+                        &mut scan_bad_blocks,
+                    )),
                     &summary,
                 )? {
-                    ConfirmAction::Yes => step = SetupStep::Keymap,
+                    ConfirmAction::Yes => step = SetupStep::BtrfsLayout,
                     ConfirmAction::No => step = SetupStep::Disk,
                     ConfirmAction::Back => step = SetupStep::Disk,
-                    ConfirmAction::Quit => {
-                        disable_raw_mode().context("disable raw mode")?;
-                        let _ = clear_screen();
-                        return Ok(());
+                    ConfirmAction::Quit => return Ok(()),
+                }
+                // An existing LUKS container means encrypted data, so it gets its own confirmation
+                let encrypted = luks_containers(disk);
+                if step == SetupStep::BtrfsLayout && !encrypted.is_empty() {
+                    let warning_lines = vec![
+                        Line::from(Span::styled(
+                            "This disk holds encrypted LUKS volumes:",
+                            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                        )),
+                        Line::from(Span::styled(
+                            format!(" {}", encrypted.join(", ")),
+                            Style::default().add_modifier(Modifier::BOLD),
+                        )),
+                        Line::from(""),
+                    ];
+                    let info_lines = vec![
+                        Line::from(Span::styled(
+                            "Their headers are overwritten; the data cannot be recovered even with the passphrase.",
+                            Style::default().fg(Color::Magenta),
+                        )),
+                        Line::from(Span::styled(
+                            format!(
+                                "Type {} to continue or press Esc to go back",
+                                DESTROY_LUKS_PHRASE
+                            ),
+                            Style::default().fg(Color::White),
+                        )),
+                    ];
+                    match run_confirm_selector(
+                        &mut terminal,
+                        "Destroy encrypted volumes",
+                        &warning_lines,
+                        &info_lines,
+                        ConfirmMode::TypedPhrase(DESTROY_LUKS_PHRASE),
+                        None,
+                        &summary,
+                    )? {
+                        ConfirmAction::Yes => {}
+                        ConfirmAction::No | ConfirmAction::Back => step = SetupStep::Disk,
+                        ConfirmAction::Quit => return Ok(()),
+                    }
+                }
+            }
+            SetupStep::BtrfsLayout => {
+                let summary = build_install_summary(
+                    step,
+                    include_drivers,
+                    network_label.as_deref(),
+                    selected_disk.as_ref(),
+                    &keymap,
+                    &timezone,
+                    &hostname,
+                    &username,
+                    &user_password,
+                    &luks_password,
+                    encrypt_disk,
+                    swap_enabled,
+                    nvidia_variant,
+                );
+                match run_btrfs_layout_selector(&mut terminal, btrfs_layout, &summary)? {
+                    SelectionAction::Submit(layout) => {
+                        btrfs_layout = layout;
+                        step = SetupStep::Keymap;
                     }
+                    SelectionAction::Back => step = SetupStep::ConfirmDisk,
+                    SelectionAction::Quit => return Ok(()),
                 }
             }
             SetupStep::Keymap => {
@@ -742,19 +1546,57 @@ fn main() -> Result<()> {
                     swap_enabled,
                     nvidia_variant,
                 );
-                match run_keymap_selector(&mut terminal, &keymaps, initial, &summary)? {
+                match run_list_selector(
+                    &mut terminal,
+                    "Select keyboard layout",
+                    "Keymaps",
+                    &keymaps,
+                    initial,
+                    &summary,
+                )? {
                     SelectionAction::Submit(index) => {
                         if let Some(value) = keymaps.get(index) {
                             keymap = value.to_string();
                         }
-                        step = SetupStep::Timezone;
+                        step = SetupStep::Locale;
                     }
-                    SelectionAction::Back => step = SetupStep::ConfirmDisk,
-                    SelectionAction::Quit => {
-                        disable_raw_mode().context("disable raw mode")?;
-                        let _ = clear_screen();
-                        return Ok(());
+                    SelectionAction::Back => step = SetupStep::BtrfsLayout,
+                    SelectionAction::Quit => return Ok(()),
+                }
+            }
+            SetupStep::Locale => {
+                let initial = find_locale_index(&locales, &locale).unwrap_or(0);
+                let summary = build_install_summary(
+                    step,
+                    include_drivers,
+                    network_label.as_deref(),
+                    selected_disk.as_ref(),
+                    &keymap,
+                    &timezone,
+                    &hostname,
+                    &username,
+                    &user_password,
+                    &luks_password,
+                    encrypt_disk,
+                    swap_enabled,
+                    nvidia_variant,
+                );
+                match run_list_selector(
+                    &mut terminal,
+                    "Select system language",
+                    "Locales",
+                    &locales,
+                    initial,
+                    &summary,
+                )? {
+                    SelectionAction::Submit(index) => {
+                        if let Some(value) = locales.get(index) {
+                            locale = value.to_string();
+                        }
+                        step = SetupStep::Timezone;
                     }
+                    SelectionAction::Back => step = SetupStep::Keymap,
+                    SelectionAction::Quit => return Ok(()),
                 }
             }
             SetupStep::Timezone => {
@@ -791,6 +1633,9 @@ fn main() -> Result<()> {
                         });
                     if let Some(value) = detect_timezone_geoip(&timezones) {
                         timezone = value;
+                    } else if let Some(value) = detect_timezone_from_keymap(&keymap, &timezones) {
+                        // Offline best-effort guess, still editable in the selector
+                        timezone = value;
                     }
                 }
                 let initial = find_timezone_index(&timezones, &timezone).unwrap_or(0);
@@ -816,12 +1661,8 @@ fn main() -> Result<()> {
                         }
                         step = SetupStep::Hostname;
                     }
-                    SelectionAction::Back => step = SetupStep::Keymap,
-                    SelectionAction::Quit => {
-                        disable_raw_mode().context("disable raw mode")?;
-                        let _ = clear_screen();
-                        return Ok(());
-                    }
+                    SelectionAction::Back => step = SetupStep::Locale,
+                    SelectionAction::Quit => return Ok(()),
                 }
             }
             SetupStep::Hostname => {
@@ -836,10 +1677,17 @@ fn main() -> Result<()> {
                     ]),
                     Line::from("Type to enter a hostname"),
                 ];
-                let info = vec![
-                    Line::from("Enter hostname (letters, numbers, and hyphens)"),
-                    Line::from("Example: my-hostname"),
-                ];
+                let info = if let Some(error_message) = hostname_input_error {
+                    vec![
+                        Line::from(Span::styled(error_message, Style::default().fg(Color::Red))),
+                        Line::from("Example: my-hostname"),
+                    ]
+                } else {
+                    vec![
+                        Line::from("Enter hostname (letters, numbers, and hyphens)"),
+                        Line::from("Example: my-hostname"),
+                    ]
+                };
                 let summary = build_install_summary(
                     step,
                     include_drivers,
@@ -869,18 +1717,21 @@ fn main() -> Result<()> {
                         let value = value.trim();
                         if value.is_empty() {
                             hostname = "nebula".to_string();
+                            hostname_input_error = None;
                             step = SetupStep::Username;
-                        } else if valid_hostname(value) {
+                        } else if let Some(error_message) = hostname_error(value) {
+                            hostname_input_error = Some(error_message);
+                        } else {
                             hostname = value.to_string();
+                            hostname_input_error = None;
                             step = SetupStep::Username;
                         }
                     }
-                    InputAction::Back => step = SetupStep::Timezone,
-                    InputAction::Quit => {
-                        disable_raw_mode().context("disable raw mode")?;
-                        let _ = clear_screen();
-                        return Ok(());
+                    InputAction::Back => {
+                        hostname_input_error = None;
+                        step = SetupStep::Timezone;
                     }
+                    InputAction::Quit => return Ok(()),
                 }
             }
             SetupStep::Username => {
@@ -895,10 +1746,20 @@ fn main() -> Result<()> {
                     ]),
                     Line::from("Type to enter your username"),
                 ];
-                let info = vec![
-                    Line::from("Use lowercase letters, numbers, and hyphens only"),
-                    Line::from("Example: kevin"),
-                ];
+                let info = if let Some(error) = username_input_error {
+                    vec![
+                        Line::from(Span::styled(
+                            error.message(),
+                            Style::default().fg(Color::Red),
+                        )),
+                        Line::from("Example: kevin"),
+                    ]
+                } else {
+                    vec![
+                        Line::from("Use lowercase letters, numbers, and hyphens only"),
+                        Line::from("Example: kevin"),
+                    ]
+                };
                 let summary = build_install_summary(
                     step,
                     include_drivers,
@@ -926,17 +1787,82 @@ fn main() -> Result<()> {
                 )? {
                     InputAction::Submit(value) => {
                         let value = value.trim();
-                        if valid_username(value) {
-                            username = value.to_string();
+                        match validate_username(value) {
+                            Ok(()) => {
+                                username = value.to_string();
+                                username_input_error = None;
+                                step = SetupStep::FullName;
+                            }
+                            Err(error) => username_input_error = Some(error),
+                        }
+                    }
+                    InputAction::Back => {
+                        username_input_error = None;
+                        step = SetupStep::Hostname;
+                    }
+                    InputAction::Quit => return Ok(()),
+                }
+            }
+            SetupStep::FullName => {
+                let controls = vec![
+                    Line::from(vec![
+                        Span::styled("Ctrl+U", Style::default().fg(Color::Cyan)),
+                        Span::raw(" or "),
+                        Span::styled("Backspace", Style::default().fg(Color::Cyan)),
+                        Span::raw(" clears the input "),
+                        Span::styled("Esc", Style::default().fg(Color::Cyan)),
+                        Span::raw(" to go back"),
+                    ]),
+                    Line::from("Type your full name or leave it empty to skip"),
+                ];
+                let info = if let Some(error_message) = full_name_input_error {
+                    vec![
+                        Line::from(Span::styled(error_message, Style::default().fg(Color::Red))),
+                        Line::from("Example: Kevin Smith"),
+                    ]
+                } else {
+                    vec![
+                        Line::from("Shown as your display name on the login screen"),
+                        Line::from("Example: Kevin Smith"),
+                    ]
+                };
+                let summary = build_install_summary(
+                    step,
+                    include_drivers,
+                    network_label.as_deref(),
+                    selected_disk.as_ref(),
+                    &keymap,
+                    &timezone,
+                    &hostname,
+                    &username,
+                    &user_password,
+                    &luks_password,
+                    encrypt_disk,
+                    swap_enabled,
+                    nvidia_variant,
+                );
+                match run_text_input(
+                    &mut terminal,
+                    "User account",
+                    &controls,
+                    &info,
+                    "Full name",
+                    Some(&full_name),
+                    false,
+                    &summary,
+                )? {
+                    InputAction::Submit(value) => {
+                        full_name = value.trim().to_string();
+                        full_name_input_error = full_name_error(&full_name);
+                        if full_name_input_error.is_none() {
                             step = SetupStep::UserPassword;
                         }
                     }
-                    InputAction::Back => step = SetupStep::Hostname,
-                    InputAction::Quit => {
-                        disable_raw_mode().context("disable raw mode")?;
-                        let _ = clear_screen();
-                        return Ok(());
+                    InputAction::Back => {
+                        full_name_input_error = None;
+                        step = SetupStep::Username;
                     }
+                    InputAction::Quit => return Ok(()),
                 }
             }
             SetupStep::UserPassword => {
@@ -984,63 +1910,22 @@ fn main() -> Result<()> {
                         if value.is_empty() {
                             continue;
                         }
-                        let confirm_controls = vec![
-                            Line::from(vec![
-                                Span::styled("Ctrl+U", Style::default().fg(Color::Cyan)),
-                                Span::raw(" or "),
-                                Span::styled("Backspace", Style::default().fg(Color::Cyan)),
-                                Span::raw(" clears the input "),
-                                Span::styled("Esc", Style::default().fg(Color::Cyan)),
-                                Span::raw(" to go back"),
-                            ]),
-                            Line::from("Type to confirm your password"),
-                        ];
-                        let confirm_info = vec![Line::from("Re-enter the password to confirm")];
-                        let summary = build_install_summary(
-                            step,
-                            include_drivers,
-                            network_label.as_deref(),
-                            selected_disk.as_ref(),
-                            &keymap,
-                            &timezone,
-                            &hostname,
-                            &username,
-                            &user_password,
-                            &luks_password,
-                            encrypt_disk,
-                            swap_enabled,
-                            nvidia_variant,
-                        );
-                        match run_text_input(
+                        match confirm_secret(
                             &mut terminal,
-                            "Confirm password",
-                            &confirm_controls,
-                            &confirm_info,
-                            "Re-enter password",
-                            None,
-                            true,
+                            &USER_PASSWORD_CONFIRM,
+                            &value,
                             &summary,
                         )? {
-                            InputAction::Submit(confirm) => {
-                                if confirm == value {
-                                    user_password = value;
-                                    step = SetupStep::EncryptDisk;
-                                }
-                            }
-                            InputAction::Back => {} // Handled by outer match
-                            InputAction::Quit => {
-                                disable_raw_mode().context("disable raw mode")?;
-                                let _ = clear_screen();
-                                return Ok(());
+                            InputAction::Submit(_) => {
+                                user_password = value;
+                                step = SetupStep::EncryptDisk;
                             }
+                            InputAction::Back => {} // Restarts from the first entry
+                            InputAction::Quit => return Ok(()),
                         }
                     }
-                    InputAction::Back => step = SetupStep::Username,
-                    InputAction::Quit => {
-                        disable_raw_mode().context("disable raw mode")?;
-                        let _ = clear_screen();
-                        return Ok(());
-                    }
+                    InputAction::Back => step = SetupStep::FullName,
+                    InputAction::Quit => return Ok(()),
                 }
             }
             SetupStep::EncryptDisk => {
@@ -1070,6 +1955,8 @@ fn main() -> Result<()> {
                     "Disk encryption",
                     &warning_lines,
                     &info_lines,
+                    ConfirmMode::YesNo,
+                    None,
                     &summary,
                 )? {
                     ConfirmAction::Yes => {
@@ -1082,11 +1969,7 @@ fn main() -> Result<()> {
                         step = SetupStep::Swap;
                     }
                     ConfirmAction::Back => step = SetupStep::UserPassword,
-                    ConfirmAction::Quit => {
-                        disable_raw_mode().context("disable raw mode")?;
-                        let _ = clear_screen();
-                        return Ok(());
-                    }
+                    ConfirmAction::Quit => return Ok(()),
                 }
             }
             SetupStep::LuksPassword => {
@@ -1135,63 +2018,22 @@ fn main() -> Result<()> {
                         if value.is_empty() {
                             continue;
                         }
-                        let confirm_controls = vec![
-                            Line::from(vec![
-                                Span::styled("Ctrl+U", Style::default().fg(Color::Cyan)),
-                                Span::raw(" or "),
-                                Span::styled("Backspace", Style::default().fg(Color::Cyan)),
-                                Span::raw(" clears the input "),
-                                Span::styled("Esc", Style::default().fg(Color::Cyan)),
-                                Span::raw(" to go back"),
-                            ]),
-                            Line::from("Type to confirm the passphrase"),
-                        ];
-                        let confirm_info = vec![Line::from("Re-enter the passphrase to confirm")];
-                        let summary = build_install_summary(
-                            step,
-                            include_drivers,
-                            network_label.as_deref(),
-                            selected_disk.as_ref(),
-                            &keymap,
-                            &timezone,
-                            &hostname,
-                            &username,
-                            &user_password,
-                            &luks_password,
-                            encrypt_disk,
-                            swap_enabled,
-                            nvidia_variant,
-                        );
-                        match run_text_input(
+                        match confirm_secret(
                             &mut terminal,
-                            "Confirm passphrase",
-                            &confirm_controls,
-                            &confirm_info,
-                            "Re-enter encryption passphras",
-                            None,
-                            true,
+                            &LUKS_PASSPHRASE_CONFIRM,
+                            &value,
                             &summary,
                         )? {
-                            InputAction::Submit(confirm) => {
-                                if confirm == value {
-                                    luks_password = value;
-                                    step = SetupStep::Swap;
-                                }
-                            }
-                            InputAction::Back => {} // Handled by outer match
-                            InputAction::Quit => {
-                                disable_raw_mode().context("disable raw mode")?;
-                                let _ = clear_screen();
-                                return Ok(());
+                            InputAction::Submit(_) => {
+                                luks_password = value;
+                                step = SetupStep::Swap;
                             }
+                            InputAction::Back => {} // Restarts from the first entry
+                            InputAction::Quit => return Ok(()),
                         }
                     }
                     InputAction::Back => step = SetupStep::EncryptDisk,
-                    InputAction::Quit => {
-                        disable_raw_mode().context("disable raw mode")?;
-                        let _ = clear_screen();
-                        return Ok(());
-                    }
+                    InputAction::Quit => return Ok(()),
                 }
             }
             SetupStep::Drivers => {
@@ -1223,17 +2065,14 @@ fn main() -> Result<()> {
                         force_network = has_wifi_device().unwrap_or(false);
                         step = SetupStep::Network;
                     }
-                    NvidiaAction::Quit => {
-                        disable_raw_mode().context("disable raw mode")?;
-                        let _ = clear_screen();
-                        return Ok(());
-                    }
+                    NvidiaAction::Quit => return Ok(()),
                 }
             }
             SetupStep::Swap => {
                 let info_lines = vec![
                     Line::from("Enable zram-based swap (in-memory compressed)"),
                     Line::from("Recommended to improve responsiveness under memory pressure"),
+                    Line::from("A swapfile on disk is slower but lets the system hibernate"),
                 ];
                 let warning_lines: Vec<Line> = Vec::new();
                 let summary = build_install_summary(
@@ -1256,15 +2095,20 @@ fn main() -> Result<()> {
                     "Enable swap",
                     &warning_lines,
                     &info_lines,
+                    ConfirmMode::YesNo,
+                    Some((
+                        "Use a btrfs swapfile instead of zram (allows hibernation)",
+                        &mut swap_file,
+                    )),
                     &summary,
                 )? {
                     ConfirmAction::Yes => {
                         swap_enabled = true;
-                        step = SetupStep::Applications;
+                        step = SetupStep::ZramSize;
                     }
                     ConfirmAction::No => {
                         swap_enabled = false;
-                        step = SetupStep::Applications;
+                        step = SetupStep::GrubPassword;
                     }
                     ConfirmAction::Back => {
                         if encrypt_disk {
@@ -1273,14 +2117,53 @@ fn main() -> Result<()> {
                             step = SetupStep::EncryptDisk;
                         }
                     }
-                    ConfirmAction::Quit => {
-                        disable_raw_mode().context("disable raw mode")?;
-                        let _ = clear_screen();
-                        return Ok(());
-                    }
+                    ConfirmAction::Quit => return Ok(()),
                 }
             }
-            SetupStep::Applications => {
+            SetupStep::ZramSize => {
+                let (hint, examples, placeholder) = if swap_file {
+                    (
+                        "Type the swapfile size in MiB",
+                        "At least your RAM size for hibernation, e.g. 8192 or 16384",
+                        "swapfile size",
+                    )
+                } else {
+                    (
+                        "Type a zram size in MiB or as an expression of ram",
+                        "Examples: ram, ram/2, 8192, min(ram/2, 4096)",
+                        "zram size",
+                    )
+                };
+                let controls = vec![
+                    Line::from(vec![
+                        Span::styled("Ctrl+U", Style::default().fg(Color::Cyan)),
+                        Span::raw(" or "),
+                        Span::styled("Backspace", Style::default().fg(Color::Cyan)),
+                        Span::raw(" clears the input "),
+                        Span::styled("Esc", Style::default().fg(Color::Cyan)),
+                        Span::raw(" to go back"),
+                    ]),
+                    Line::from(hint),
+                ];
+                let info = if let Some(error_message) = swap_size_error {
+                    vec![
+                        Line::from(Span::styled(
+                            format!("Invalid size: {}", error_message),
+                            Style::default().fg(Color::Red),
+                        )),
+                        Line::from(examples),
+                    ]
+                } else if swap_file {
+                    vec![
+                        Line::from("Size of the swapfile in the @swap subvolume (default: RAM)"),
+                        Line::from(examples),
+                    ]
+                } else {
+                    vec![
+                        Line::from("Size of the compressed swap device (default: ram)"),
+                        Line::from(examples),
+                    ]
+                };
                 let summary = build_install_summary(
                     step,
                     include_drivers,
@@ -1296,96 +2179,523 @@ fn main() -> Result<()> {
                     swap_enabled,
                     nvidia_variant,
                 );
-                match run_application_selector(&mut terminal, &app_flags, &summary)? {
-                    SelectionAction::Submit(flags) => {
-                        app_flags = flags;
-                        app_selection = selection_from_app_flags(&app_flags);
-                        step = SetupStep::Review;
+                let current = if swap_file {
+                    &swapfile_size
+                } else {
+                    &zram_size
+                };
+                match run_text_input(
+                    &mut terminal,
+                    "Swap size",
+                    &controls,
+                    &info,
+                    placeholder,
+                    Some(current),
+                    false,
+                    &summary,
+                )? {
+                    InputAction::Submit(value) => {
+                        let value = value.trim();
+                        if swap_file {
+                            swapfile_size = if value.is_empty() {
+                                default_swapfile_size_mib().to_string()
+                            } else {
+                                value.to_string()
+                            };
+                            swap_size_error = parse_swapfile_size(&swapfile_size).err();
+                        } else {
+                            zram_size = if value.is_empty() {
+                                "ram".to_string()
+                            } else {
+                                value.to_string()
+                            };
+                            swap_size_error = validate_zram_size(&zram_size).err();
+                        }
+                        if swap_size_error.is_none() {
+                            step = SetupStep::GrubPassword;
+                        }
                     }
-                    SelectionAction::Back => step = SetupStep::Swap,
-                    SelectionAction::Quit => {
-                        disable_raw_mode().context("disable raw mode")?;
-                        let _ = clear_screen();
-                        return Ok(());
+                    InputAction::Back => {
+                        swap_size_error = None;
+                        step = SetupStep::Swap;
                     }
+                    InputAction::Quit => return Ok(()),
                 }
             }
-            SetupStep::Review => {
-                let Some(disk) = &selected_disk else {
-                    step = SetupStep::Disk;
-                    continue;
-                };
-                let compositor_labels =
-                    labels_for_flags(&app_flags.compositors, &compositor_labels());
-                let browser_labels = labels_for_selection(&app_selection, browser_choices());
-                let editor_labels = labels_for_selection(&app_selection, editor_choices());
-                let terminal_labels = labels_for_selection(&app_selection, terminal_choices());
-                let system_items = vec![
-                    ReviewItem {
-                        label: "Network".to_string(),
-                        value: network_label
-                            .clone()
-                            .unwrap_or_else(|| "Not connected".to_string()),
-                    },
-                    ReviewItem {
-                        label: "Disk".to_string(),
-                        value: disk.label(),
-                    },
-                    ReviewItem {
-                        label: "Filesystem".to_string(),
-                        value: if encrypt_disk {
-                            "Btrfs (LUKS encrypted)".to_string()
-                        } else {
-                            "Btrfs".to_string()
-                        },
-                    },
-                    ReviewItem {
-                        label: "GPU".to_string(),
-                        value: format_gpu_summary(&gpu_vendors, nvidia_variant)
-                            .unwrap_or_else(|| "Not detected".to_string()),
-                    },
-                    ReviewItem {
-                        label: "Swap".to_string(),
-                        value: if swap_enabled {
-                            "Enabled (zram)".to_string()
-                        } else {
-                            "Disabled".to_string()
-                        },
-                    },
-                    ReviewItem {
-                        label: "Hostname".to_string(),
-                        value: hostname.clone(),
-                    },
-                    ReviewItem {
-                        label: "Username".to_string(),
-                        value: username.clone(),
-                    },
-                    ReviewItem {
-                        label: "Keyboard".to_string(),
-                        value: keymap.clone(),
-                    },
-                    ReviewItem {
-                        label: "Timezone".to_string(),
-                        value: timezone.clone(),
-                    },
+            SetupStep::GrubPassword => {
+                let controls = vec![
+                    Line::from(vec![
+                        Span::styled("Ctrl+U", Style::default().fg(Color::Cyan)),
+                        Span::raw(" or "),
+                        Span::styled("Backspace", Style::default().fg(Color::Cyan)),
+                        Span::raw(" clears the input "),
+                        Span::styled("Esc", Style::default().fg(Color::Cyan)),
+                        Span::raw(" to go back"),
+                    ]),
+                    Line::from("Type to enter a boot menu password"),
                 ];
-                let package_items = vec![
-                    ReviewItem {
-                        label: "Compositor".to_string(),
-                        value: if compositor_labels.is_empty() {
-                            "None".to_string()
-                        } else {
-                            compositor_labels.join(", ")
-                        },
-                    },
-                    ReviewItem {
-                        label: "Browsers".to_string(),
-                        value: if browser_labels.is_empty() {
-                            "None".to_string()
-                        } else {
-                            browser_labels.join(", ")
-                        },
-                    },
+                let info = vec![
+                    Line::from("Optional: require a password to edit GRUB entries"),
+                    Line::from("Leave empty and press Enter to skip"),
+                ];
+                let summary = build_install_summary(
+                    step,
+                    include_drivers,
+                    network_label.as_deref(),
+                    selected_disk.as_ref(),
+                    &keymap,
+                    &timezone,
+                    &hostname,
+                    &username,
+                    &user_password,
+                    &luks_password,
+                    encrypt_disk,
+                    swap_enabled,
+                    nvidia_variant,
+                );
+                match run_text_input(
+                    &mut terminal,
+                    "GRUB password",
+                    &controls,
+                    &info,
+                    "Boot menu password",
+                    None,
+                    true,
+                    &summary,
+                )? {
+                    InputAction::Submit(value) => {
+                        if value.is_empty() {
+                            grub_password = None;
+                            step = SetupStep::Mitigations;
+                            continue;
+                        }
+                        // A typo here would lock the user out of editing boot entries
+                        match confirm_secret(
+                            &mut terminal,
+                            &GRUB_PASSWORD_CONFIRM,
+                            &value,
+                            &summary,
+                        )? {
+                            InputAction::Submit(_) => {
+                                grub_password = Some(value);
+                                step = SetupStep::Mitigations;
+                            }
+                            InputAction::Back => {} // Restarts from the first entry
+                            InputAction::Quit => return Ok(()),
+                        }
+                    }
+                    InputAction::Back => {
+                        step = if swap_enabled {
+                            SetupStep::ZramSize
+                        } else {
+                            SetupStep::Swap
+                        };
+                    }
+                    InputAction::Quit => return Ok(()),
+                }
+            }
+            SetupStep::Mitigations => {
+                let summary = build_install_summary(
+                    step,
+                    include_drivers,
+                    network_label.as_deref(),
+                    selected_disk.as_ref(),
+                    &keymap,
+                    &timezone,
+                    &hostname,
+                    &username,
+                    &user_password,
+                    &luks_password,
+                    encrypt_disk,
+                    swap_enabled,
+                    nvidia_variant,
+                );
+                match run_mitigations_selector(&mut terminal, mitigations, &summary)? {
+                    SelectionAction::Submit(choice) => {
+                        mitigations = choice;
+                        step = SetupStep::BootSplash;
+                    }
+                    SelectionAction::Back => step = SetupStep::GrubPassword,
+                    SelectionAction::Quit => return Ok(()),
+                }
+            }
+            SetupStep::BootSplash => {
+                let summary = build_install_summary(
+                    step,
+                    include_drivers,
+                    network_label.as_deref(),
+                    selected_disk.as_ref(),
+                    &keymap,
+                    &timezone,
+                    &hostname,
+                    &username,
+                    &user_password,
+                    &luks_password,
+                    encrypt_disk,
+                    swap_enabled,
+                    nvidia_variant,
+                );
+                // Automatic, disabled, then every theme found on the live system
+                let mut labels = vec![
+                    "Automatic (Nebula splash)".to_string(),
+                    "None (disable Plymouth)".to_string(),
+                ];
+                labels.extend(plymouth_themes.iter().cloned());
+                let current = match plymouth_theme.as_deref() {
+                    None => 0,
+                    Some(PLYMOUTH_DISABLED) => 1,
+                    Some(theme) => plymouth_themes
+                        .iter()
+                        .position(|candidate| candidate == theme)
+                        .map_or(0, |idx| idx + 2),
+                };
+                let mut checked = vec![false; labels.len()];
+                checked[current] = true;
+                let groups = [SelectGroup::new("Boot splash", labels, &checked).single()];
+                match run_multiselect(&mut terminal, "Boot Splash", &groups, &summary)? {
+                    SelectionAction::Submit(checked) => {
+                        let choice = checked[0].iter().position(|flag| *flag).unwrap_or(0);
+                        plymouth_theme = match choice {
+                            0 => None,
+                            1 => Some(PLYMOUTH_DISABLED.to_string()),
+                            idx => plymouth_themes.get(idx - 2).cloned(),
+                        };
+                        step = SetupStep::Profile;
+                    }
+                    SelectionAction::Back => step = SetupStep::Mitigations,
+                    SelectionAction::Quit => return Ok(()),
+                }
+            }
+            SetupStep::Profile => {
+                let summary = build_install_summary(
+                    step,
+                    include_drivers,
+                    network_label.as_deref(),
+                    selected_disk.as_ref(),
+                    &keymap,
+                    &timezone,
+                    &hostname,
+                    &username,
+                    &user_password,
+                    &luks_password,
+                    encrypt_disk,
+                    swap_enabled,
+                    nvidia_variant,
+                );
+                match run_profile_selector(&mut terminal, install_profile, &summary)? {
+                    SelectionAction::Submit(profile) => {
+                        install_profile = profile;
+                        step = match profile {
+                            InstallProfile::Full => SetupStep::Applications,
+                            InstallProfile::Minimal => SetupStep::AurHelper,
+                        };
+                        // Switching to Full from the review still needs the app selection
+                        if editing_from_review.is_some() && profile == InstallProfile::Full {
+                            editing_from_review = Some(SetupStep::Applications);
+                        }
+                    }
+                    SelectionAction::Back => step = SetupStep::BootSplash,
+                    SelectionAction::Quit => return Ok(()),
+                }
+            }
+            SetupStep::Applications => {
+                let summary = build_install_summary(
+                    step,
+                    include_drivers,
+                    network_label.as_deref(),
+                    selected_disk.as_ref(),
+                    &keymap,
+                    &timezone,
+                    &hostname,
+                    &username,
+                    &user_password,
+                    &luks_password,
+                    encrypt_disk,
+                    swap_enabled,
+                    nvidia_variant,
+                );
+                match run_application_selector(&mut terminal, &app_flags, &summary)? {
+                    SelectionAction::Submit(flags) => {
+                        app_flags = flags;
+                        app_selection = selection_from_app_flags(&app_flags);
+                        step = SetupStep::AurHelper;
+                    }
+                    SelectionAction::Back => step = SetupStep::Profile,
+                    SelectionAction::Quit => return Ok(()),
+                }
+            }
+            SetupStep::AurHelper => {
+                let summary = build_install_summary(
+                    step,
+                    include_drivers,
+                    network_label.as_deref(),
+                    selected_disk.as_ref(),
+                    &keymap,
+                    &timezone,
+                    &hostname,
+                    &username,
+                    &user_password,
+                    &luks_password,
+                    encrypt_disk,
+                    swap_enabled,
+                    nvidia_variant,
+                );
+                match run_aur_helper_selector(&mut terminal, aur_helper, &summary)? {
+                    SelectionAction::Submit(helper) => {
+                        aur_helper = helper;
+                        step = SetupStep::Review;
+                    }
+                    SelectionAction::Back => {
+                        step = match install_profile {
+                            InstallProfile::Full => SetupStep::Applications,
+                            InstallProfile::Minimal => SetupStep::Profile,
+                        };
+                    }
+                    SelectionAction::Quit => return Ok(()),
+                }
+            }
+            SetupStep::Review => {
+                let Some(disk) = &selected_disk else {
+                    step = SetupStep::Disk;
+                    continue;
+                };
+                let compositor_labels =
+                    labels_for_flags(&app_flags.compositors, &compositor_labels());
+                let browser_labels = labels_for_selection(&app_selection, browser_choices());
+                let editor_labels = labels_for_selection(&app_selection, editor_choices());
+                let terminal_labels = labels_for_selection(&app_selection, terminal_choices());
+                let mut system_items = vec![
+                    ReviewItem {
+                        label: "Network".to_string(),
+                        value: network_label
+                            .clone()
+                            .unwrap_or_else(|| "Not connected".to_string()),
+                        step: Some(SetupStep::Network),
+                    },
+                    ReviewItem {
+                        label: "Disk".to_string(),
+                        value: disk.label(),
+                        step: Some(SetupStep::Disk),
+                    },
+                    ReviewItem {
+                        label: "Filesystem".to_string(),
+                        value: if encrypt_disk && full_disk_encryption {
+                            "Btrfs (LUKS1 encrypted, including /boot)".to_string()
+                        } else if encrypt_disk {
+                            format!("Btrfs ({} encrypted)", luks_version.label())
+                        } else {
+                            "Btrfs".to_string()
+                        },
+                        step: Some(SetupStep::EncryptDisk),
+                    },
+                    ReviewItem {
+                        label: "Subvolumes".to_string(),
+                        value: btrfs_layout.label().to_string(),
+                        step: Some(SetupStep::BtrfsLayout),
+                    },
+                    ReviewItem {
+                        label: "GPU".to_string(),
+                        value: format_gpu_summary(&gpu_vendors, nvidia_variant)
+                            .unwrap_or_else(|| "Not detected".to_string()),
+                        step: include_drivers.then_some(SetupStep::Drivers),
+                    },
+                    ReviewItem {
+                        label: "Swap".to_string(),
+                        value: if swap_enabled && swap_file {
+                            format!("Enabled (swapfile, {} MiB)", swapfile_size)
+                        } else if swap_enabled {
+                            format!("Enabled (zram, {})", zram_size)
+                        } else {
+                            "Disabled".to_string()
+                        },
+                        step: Some(SetupStep::Swap),
+                    },
+                    ReviewItem {
+                        label: "GRUB password".to_string(),
+                        value: if grub_password.is_some() {
+                            "Set".to_string()
+                        } else {
+                            "None".to_string()
+                        },
+                        step: Some(SetupStep::GrubPassword),
+                    },
+                    ReviewItem {
+                        label: "CPU mitigations".to_string(),
+                        value: if mitigations == Mitigations::Off {
+                            "Off (warning: CPU vulnerabilities left exposed)".to_string()
+                        } else {
+                            mitigations.label().to_string()
+                        },
+                        step: Some(SetupStep::Mitigations),
+                    },
+                    ReviewItem {
+                        label: "Boot splash".to_string(),
+                        value: match plymouth_theme.as_deref() {
+                            None => "Automatic".to_string(),
+                            Some(PLYMOUTH_DISABLED) => "Disabled".to_string(),
+                            Some(theme) => theme.to_string(),
+                        },
+                        step: Some(SetupStep::BootSplash),
+                    },
+                    ReviewItem {
+                        label: "Bluetooth".to_string(),
+                        value: if app_flags.service_enabled(SystemService::Bluetooth) {
+                            "enabled".to_string()
+                        } else {
+                            "disabled".to_string()
+                        },
+                        step: Some(SetupStep::Applications),
+                    },
+                    ReviewItem {
+                        label: "Power".to_string(),
+                        value: if app_flags.service_enabled(SystemService::Tlp) {
+                            "TLP".to_string()
+                        } else if app_flags.service_enabled(SystemService::PowerProfiles) {
+                            "power-profiles-daemon".to_string()
+                        } else {
+                            "Default".to_string()
+                        },
+                        step: Some(SetupStep::Applications),
+                    },
+                    ReviewItem {
+                        label: "Hostname".to_string(),
+                        value: hostname.clone(),
+                        step: Some(SetupStep::Hostname),
+                    },
+                    ReviewItem {
+                        label: "Username".to_string(),
+                        value: username.clone(),
+                        step: Some(SetupStep::Username),
+                    },
+                    ReviewItem {
+                        label: "Full name".to_string(),
+                        value: if full_name.is_empty() {
+                            "Not set".to_string()
+                        } else {
+                            full_name.clone()
+                        },
+                        step: Some(SetupStep::FullName),
+                    },
+                    ReviewItem {
+                        label: "Shell".to_string(),
+                        value: shell.label().to_string(),
+                        step: None,
+                    },
+                    ReviewItem {
+                        label: "Firewall".to_string(),
+                        value: firewall.label().to_string(),
+                        step: None,
+                    },
+                    ReviewItem {
+                        label: "Keyboard".to_string(),
+                        value: keymap.clone(),
+                        step: Some(SetupStep::Keymap),
+                    },
+                    ReviewItem {
+                        label: "Console font".to_string(),
+                        value: console_font
+                            .clone()
+                            .unwrap_or_else(|| "Default".to_string()),
+                        step: None,
+                    },
+                    ReviewItem {
+                        label: "Locale".to_string(),
+                        value: locale.clone(),
+                        step: Some(SetupStep::Locale),
+                    },
+                    ReviewItem {
+                        label: "Timezone".to_string(),
+                        value: match timezone_error(&timezones, &timezone) {
+                            Some(message) => format!("{} ({})", timezone, message),
+                            None => timezone.clone(),
+                        },
+                        step: Some(SetupStep::Timezone),
+                    },
+                ];
+                if verbose_boot {
+                    system_items.push(ReviewItem {
+                        label: "Boot".to_string(),
+                        value: "Verbose (no splash)".to_string(),
+                        step: None,
+                    });
+                }
+                if let Some(header) = luks_detached_header.as_ref().filter(|_| encrypt_disk) {
+                    system_items.push(ReviewItem {
+                        label: "LUKS header".to_string(),
+                        value: header.clone(),
+                        step: None,
+                    });
+                }
+                if let Some(home) = &existing_home {
+                    system_items.push(ReviewItem {
+                        label: "Existing /home".to_string(),
+                        value: format!("{} (kept, not formatted)", home.device),
+                        step: None,
+                    });
+                }
+                let ssd_tuning = ssd_tuning_override.unwrap_or(!disk.rotational);
+                if ssd_tuning {
+                    system_items.push(ReviewItem {
+                        label: "SSD tuning".to_string(),
+                        value: "noatime, discard=async, fstrim.timer".to_string(),
+                        step: None,
+                    });
+                }
+                if let Some(country) =
+                    wifi_country_for(wifi_country_override.as_deref(), wifi_hardware, &timezone)
+                {
+                    system_items.push(ReviewItem {
+                        label: "Wi-Fi region".to_string(),
+                        value: country,
+                        step: Some(SetupStep::Timezone),
+                    });
+                }
+                if scan_bad_blocks {
+                    system_items.push(ReviewItem {
+                        label: "Bad block scan".to_string(),
+                        value: "Before partitioning (read-only)".to_string(),
+                        step: Some(SetupStep::ConfirmDisk),
+                    });
+                }
+                if secure_wipe {
+                    system_items.push(ReviewItem {
+                        label: "Secure wipe".to_string(),
+                        value: "Overwrite disk with random data".to_string(),
+                        step: None,
+                    });
+                }
+                // Only shown when a custom NTP server replaces the defaults
+                if let Some(server) = &ntp_server {
+                    system_items.push(ReviewItem {
+                        label: "NTP server".to_string(),
+                        value: server.clone(),
+                        step: None,
+                    });
+                }
+                let mut package_items = vec![
+                    ReviewItem {
+                        label: "Profile".to_string(),
+                        value: install_profile.label().to_string(),
+                        step: Some(SetupStep::Profile),
+                    },
+                    ReviewItem {
+                        label: "Compositor".to_string(),
+                        value: if compositor_labels.is_empty() {
+                            "None".to_string()
+                        } else {
+                            compositor_labels.join(", ")
+                        },
+                        step: Some(SetupStep::Applications),
+                    },
+                    ReviewItem {
+                        label: "Browsers".to_string(),
+                        value: if browser_labels.is_empty() {
+                            "None".to_string()
+                        } else {
+                            browser_labels.join(", ")
+                        },
+                        step: Some(SetupStep::Applications),
+                    },
                     ReviewItem {
                         label: "Editors".to_string(),
                         value: if editor_labels.is_empty() {
@@ -1393,37 +2703,309 @@ fn main() -> Result<()> {
                         } else {
                             editor_labels.join(", ")
                         },
+                        step: Some(SetupStep::Applications),
                     },
                     ReviewItem {
                         label: "Terminals".to_string(),
                         value: if terminal_labels.is_empty() {
                             "None".to_string()
                         } else {
-                            terminal_labels.join(", ")
-                        },
-                    },
-                ];
-                let selected_packages = compositor_labels.len()
-                    + browser_labels.len()
-                    + editor_labels.len()
-                    + terminal_labels.len();
-                match run_review(
-                    &mut terminal,
-                    &system_items,
-                    &package_items,
-                    selected_packages,
-                )? {
-                    ReviewAction::Confirm => break 'setup,
-                    ReviewAction::Back => step = SetupStep::Applications,
+                            terminal_labels.join(", ")
+                        },
+                        step: Some(SetupStep::Applications),
+                    },
+                    ReviewItem {
+                        label: "Extra packages".to_string(),
+                        value: if app_flags.extra_packages.is_empty() {
+                            "None".to_string()
+                        } else {
+                            app_flags.extra_packages.join(", ")
+                        },
+                        step: Some(SetupStep::Applications),
+                    },
+                ];
+                // The gaming bundle is listed with its GPU-specific 32-bit drivers
+                let gaming_bundle = app_flags
+                    .service_enabled(SystemService::Gaming)
+                    .then(|| gaming_packages(&gpu_vendors, nvidia_variant));
+                if let Some(packages) = &gaming_bundle {
+                    package_items.push(ReviewItem {
+                        label: "Gaming".to_string(),
+                        value: packages.join(", "),
+                        step: Some(SetupStep::Applications),
+                    });
+                }
+                package_items.extend([
+                    ReviewItem {
+                        label: "Package list".to_string(),
+                        value: if imported_packages.pacman.is_empty()
+                            && imported_packages.yay.is_empty()
+                        {
+                            "None".to_string()
+                        } else {
+                            format!(
+                                "{} repo, {} AUR",
+                                imported_packages.pacman.len(),
+                                imported_packages.yay.len()
+                            )
+                        },
+                        step: None,
+                    },
+                    ReviewItem {
+                        label: "AUR helper".to_string(),
+                        value: aur_helper.label().to_string(),
+                        step: Some(SetupStep::AurHelper),
+                    },
+                ]);
+                let mut selected_packages = compositor_labels.len()
+                    + browser_labels.len()
+                    + editor_labels.len()
+                    + terminal_labels.len()
+                    + app_flags.extra_packages.len()
+                    + gaming_bundle.as_ref().map_or(0, Vec::len)
+                    + imported_packages.pacman.len()
+                    + imported_packages.yay.len();
+                // Application choices are ignored by the minimal profile
+                if install_profile == InstallProfile::Minimal {
+                    package_items.retain(|item| item.step != Some(SetupStep::Applications));
+                    selected_packages =
+                        imported_packages.pacman.len() + imported_packages.yay.len();
+                }
+                match run_review(
+                    &mut terminal,
+                    &system_items,
+                    &package_items,
+                    selected_packages,
+                    review_notice.as_deref(),
+                )? {
+                    ReviewAction::Confirm => {
+                        // The install would only hit a bad timezone after pacstrap
+                        if let Some(message) = timezone_error(&timezones, &timezone) {
+                            review_notice = Some(format!(
+                                "{}: {}. Edit the timezone before installing",
+                                message, timezone
+                            ));
+                            continue;
+                        }
+                        // useradd would only reject the name after the disk is wiped
+                        if let Err(error) = validate_username(&username) {
+                            review_notice = Some(format!(
+                                "{}: {}. Edit the username before installing",
+                                error.message(),
+                                username
+                            ));
+                            continue;
+                        }
+                        let summary = build_install_summary(
+                            step,
+                            include_drivers,
+                            network_label.as_deref(),
+                            selected_disk.as_ref(),
+                            &keymap,
+                            &timezone,
+                            &hostname,
+                            &username,
+                            &user_password,
+                            &luks_password,
+                            encrypt_disk,
+                            swap_enabled,
+                            nvidia_variant,
+                        );
+                        // A power loss halfway through can leave the disk unbootable
+                        if let Some(battery) = battery_status().filter(|status| status.is_low()) {
+                            let warning_lines = vec![
+                                Line::from(Span::styled(
+                                    format!(
+                                        "Battery is at {}% and no charger is connected",
+                                        battery.capacity
+                                    ),
+                                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                                )),
+                                Line::from(
+                                    "The install can take a while; losing power mid-way can leave the disk unbootable",
+                                ),
+                            ];
+                            let info_lines = vec![Line::from(
+                                "Plug in a charger, or choose Yes to continue on battery",
+                            )];
+                            match run_confirm_selector(
+                                &mut terminal,
+                                "Low battery",
+                                &warning_lines,
+                                &info_lines,
+                                ConfirmMode::YesNo,
+                                None,
+                                &summary,
+                            )? {
+                                ConfirmAction::Yes => {}
+                                ConfirmAction::No | ConfirmAction::Back => continue,
+                                ConfirmAction::Quit => return Ok(()),
+                            }
+                        }
+                        // Last stop before the disk is touched: show exactly what changes on it
+                        let (swap_mode, subvolumes) = target_layout(
+                            btrfs_layout,
+                            existing_home.is_some(),
+                            swap_enabled,
+                            swap_file,
+                            &swapfile_size,
+                        );
+                        let preview = InstallConfig {
+                            disk: disk.clone(),
+                            luks_detached_header: luks_detached_header
+                                .clone()
+                                .filter(|_| encrypt_disk),
+                            encrypt_disk,
+                            full_disk_encryption: full_disk_encryption && encrypt_disk,
+                            swap_enabled,
+                            swap_mode,
+                            subvolumes,
+                            existing_home: existing_home.clone(),
+                            secure_wipe,
+                            scan_bad_blocks,
+                            ..Default::default()
+                        };
+                        let info_lines = vec![Line::from(
+                            "Choose Yes to start the install or No to return to the review",
+                        )];
+                        match run_confirm_selector(
+                            &mut terminal,
+                            "Confirm disk changes",
+                            &render_partition_preview(&preview),
+                            &info_lines,
+                            ConfirmMode::YesNo,
+                            None,
+                            &summary,
+                        )? {
+                            ConfirmAction::Yes => break 'setup,
+                            ConfirmAction::No | ConfirmAction::Back => continue,
+                            ConfirmAction::Quit => return Ok(()),
+                        }
+                    }
+                    ReviewAction::Export => {
+                        let controls = vec![
+                            Line::from(vec![
+                                Span::styled("Enter", Style::default().fg(Color::Cyan)),
+                                Span::raw(" saves, "),
+                                Span::styled("Esc", Style::default().fg(Color::Cyan)),
+                                Span::raw(" to go back"),
+                            ]),
+                            Line::from("Type where to save the selections"),
+                        ];
+                        let info = vec![
+                            Line::from("Writes a TOML answer file for a later unattended install"),
+                            Line::from("Passwords are left out and must be supplied separately"),
+                        ];
+                        let summary = build_install_summary(
+                            step,
+                            include_drivers,
+                            network_label.as_deref(),
+                            selected_disk.as_ref(),
+                            &keymap,
+                            &timezone,
+                            &hostname,
+                            &username,
+                            &user_password,
+                            &luks_password,
+                            encrypt_disk,
+                            swap_enabled,
+                            nvidia_variant,
+                        );
+                        let InputAction::Submit(path) = run_text_input(
+                            &mut terminal,
+                            "Export selections",
+                            &controls,
+                            &info,
+                            "answer file path",
+                            Some(DEFAULT_ANSWER_FILE_PATH),
+                            false,
+                            &summary,
+                        )?
+                        else {
+                            continue;
+                        };
+                        let path = path.trim();
+                        let path = if path.is_empty() {
+                            DEFAULT_ANSWER_FILE_PATH
+                        } else {
+                            path
+                        };
+                        let mut services: Vec<String> = Vec::new();
+                        for service in SYSTEM_SERVICES {
+                            if app_flags.service_enabled(service) {
+                                services.push(system_service_label(service).to_string());
+                            }
+                        }
+                        let answers = AnswerFile {
+                            disk: disk.device_path(),
+                            btrfs_layout: btrfs_layout.label().to_string(),
+                            keymap: keymap.clone(),
+                            locale: locale.clone(),
+                            timezone: timezone.clone(),
+                            hostname: hostname.clone(),
+                            username: username.clone(),
+                            encrypt_disk,
+                            nvidia_driver: nvidia_variant
+                                .map(|variant| nvidia_variant_label(variant).to_string()),
+                            swap_enabled,
+                            zram_size: zram_size.clone(),
+                            swap_mode: match parse_swapfile_size(&swapfile_size) {
+                                Ok(size_mib) if swap_file => SwapMode::File { size_mib },
+                                _ => SwapMode::Zram,
+                            },
+                            grub_password: grub_password.is_some(),
+                            mitigations,
+                            boot_splash: plymouth_theme.clone(),
+                            profile: install_profile,
+                            compositors: compositor_labels,
+                            browsers: browser_labels,
+                            editors: editor_labels,
+                            terminals: terminal_labels,
+                            extra_packages: app_flags.extra_packages.clone(),
+                            services,
+                            aur_helper,
+                            pacman_packages: app_selection
+                                .pacman
+                                .iter()
+                                .chain(&imported_packages.pacman)
+                                .cloned()
+                                .collect(),
+                            aur_packages: app_selection
+                                .yay
+                                .iter()
+                                .chain(&imported_packages.yay)
+                                .cloned()
+                                .collect(),
+                        };
+                        review_notice = Some(match write_answer_file(Path::new(path), &answers) {
+                            Ok(()) => format!("Selections saved to {}", path),
+                            Err(err) => format!("Export failed: {:#}", err),
+                        });
+                    }
+                    ReviewAction::Back => step = SetupStep::AurHelper,
                     ReviewAction::Edit => step = SetupStep::Network,
-                    ReviewAction::Quit => {
-                        disable_raw_mode().context("disable raw mode")?;
-                        let _ = clear_screen();
-                        return Ok(());
+                    ReviewAction::EditField(target) => {
+                        editing_from_review = Some(target);
+                        step = target;
                     }
+                    ReviewAction::Quit => return Ok(()),
                 }
             }
         }
+
+        // Return to the review once the flow leaves the edited section
+        if let Some(target) = editing_from_review {
+            if setup_section(step) != setup_section(target) {
+                editing_from_review = None;
+                step = SetupStep::Review;
+            }
+        }
+    }
+
+    // The minimal profile keeps only the base packages, kernel, and drivers
+    if install_profile == InstallProfile::Minimal {
+        app_flags.clear();
+        app_selection = PackageSelection::default();
     }
 
     // Compute compositor packages and selection
@@ -1443,8 +3025,40 @@ fn main() -> Result<()> {
     base_packages.extend(compositor_selection.pacman);
     let selected_browsers = labels_for_selection(&app_selection, browser_choices());
     let selected_editors = labels_for_selection(&app_selection, editor_choices());
+    let enable_printing = app_flags.service_enabled(SystemService::Printing);
+    let enable_bluetooth = app_flags.service_enabled(SystemService::Bluetooth);
+    let enable_flatpak = app_flags.service_enabled(SystemService::Flatpak);
+    let enable_mdns = app_flags.service_enabled(SystemService::Mdns);
+    let enable_tlp = app_flags.service_enabled(SystemService::Tlp);
+    let enable_power_profiles = app_flags.service_enabled(SystemService::PowerProfiles);
+    let enable_fingerprint = app_flags.service_enabled(SystemService::Fingerprint);
+    let enable_multilib = app_flags.service_enabled(SystemService::Multilib);
+    let flatpak_apps: Vec<String> = std::env::var("NEBULA_FLATPAK_APPS")
+        .ok()
+        .map(|value| {
+            value
+                .split(',')
+                .map(|app| app.trim().to_string())
+                .filter(|app| !app.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+    for service in SYSTEM_SERVICES {
+        if app_flags.service_enabled(service) {
+            for pkg in system_service_packages(service) {
+                base_packages.push(pkg.to_string());
+            }
+        }
+    }
+    let mut extra_pacman_packages = app_selection.pacman;
+    // Installed with the optional packages so the multilib repo is available
+    if app_flags.service_enabled(SystemService::Gaming) {
+        extra_pacman_packages.extend(gaming_packages(&gpu_vendors, nvidia_variant));
+    }
+    extra_pacman_packages.extend(imported_packages.pacman);
     let mut extra_aur_packages = app_selection.yay;
     extra_aur_packages.extend(compositor_selection.yay);
+    extra_aur_packages.extend(imported_packages.yay);
     let compositor_label = app_flags
         .compositors
         .iter()
@@ -1455,38 +3069,351 @@ fn main() -> Result<()> {
         .or_else(|| compositor_choices().first().map(|choice| choice.label.clone()))
         .unwrap_or_else(|| "Hyprland (Caelestia)".to_string());
 
+    // Hyprland input defaults; the layout follows the console keymap unless overridden
+    let env_number = |name: &str| {
+        std::env::var(name)
+            .ok()
+            .and_then(|value| value.trim().parse::<u32>().ok())
+            .filter(|value| *value > 0)
+    };
+    let default_input = HyprInput::default();
+    let hypr_input = HyprInput {
+        kb_layout: std::env::var("NEBULA_KB_LAYOUT")
+            .ok()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+            .unwrap_or_else(|| xkb_layout_for_keymap(&keymap)),
+        kb_variant: std::env::var("NEBULA_KB_VARIANT")
+            .map(|value| value.trim().to_string())
+            .unwrap_or_default(),
+        repeat_rate: env_number("NEBULA_KB_REPEAT_RATE").unwrap_or(default_input.repeat_rate),
+        repeat_delay: env_number("NEBULA_KB_REPEAT_DELAY").unwrap_or(default_input.repeat_delay),
+        natural_scroll: std::env::var("NEBULA_NATURAL_SCROLL").ok().as_deref() == Some("1"),
+    };
+
+    let (swap_mode, subvolumes) = target_layout(
+        btrfs_layout,
+        existing_home.is_some(),
+        swap_enabled,
+        swap_file,
+        &swapfile_size,
+    );
+
+    // SSD tuning follows the target's rotational flag unless overridden
+    let ssd_tuning = ssd_tuning_override
+        .unwrap_or_else(|| selected_disk.as_ref().is_some_and(|disk| !disk.rotational));
+    let wifi_country = wifi_country_for(wifi_country_override.as_deref(), wifi_hardware, &timezone);
+
     // Create the installation configuration
     let config = InstallConfig {
         disk: selected_disk.expect("disk selection"),
         keymap,
+        console_font,
+        locale,
         timezone,
         hostname,
         username,
+        full_name: Some(full_name).filter(|name| !name.is_empty()),
+        shell,
         user_password,
         luks_password,
+        luks_detached_header: luks_detached_header.filter(|_| encrypt_disk),
         encrypt_disk,
+        full_disk_encryption: full_disk_encryption && encrypt_disk,
+        luks_version,
+        luks_pbkdf,
         swap_enabled,
+        zram_size,
+        swap_mode,
+        subvolumes,
+        existing_home,
         driver_packages: driver_packages(&gpu_vendors, nvidia_variant),
+        firmware,
         kernel_package,
         kernel_headers,
         base_packages,
         selected_browsers,
         selected_editors,
-        extra_pacman_packages: app_selection.pacman,
+        extra_pacman_packages,
         extra_aur_packages,
+        optional_failure_policy,
+        aur_helper,
         compositor_label,
         offline_only,
         hyprland_selected: app_flags.compositors.iter().any(|flag| *flag),
+        enable_printing,
+        enable_bluetooth,
+        enable_flatpak,
+        enable_mdns,
+        enable_tlp,
+        enable_power_profiles,
+        enable_firewall: firewall,
+        enable_fingerprint,
+        enable_multilib,
+        configure_nebula_repo: std::env::var("NEBULA_CONFIGURE_REPO").ok().as_deref() != Some("0"),
+        secure_wipe,
+        scan_bad_blocks,
+        ssd_tuning,
+        package_cache,
+        copy_installer,
+        compress_logs,
+        keep_target_mounted: false,
+        verbose_boot,
+        mitigations,
+        plymouth_theme,
+        display_resolution,
+        display_scale,
+        hypr_input,
+        sudo_policy,
+        makepkg,
+        profile: install_profile,
+        flatpak_apps,
+        grub_password,
+        parallel_downloads,
+        rank_mirrors,
+        ntp_server,
+        wifi_country,
+        post_install_commands,
+        abort_on_hook_failure,
+        resume_from: 0,
     };
 
+    run_install_progress(&mut terminal, config, progress_output)
+}
+
+// Outcome of the resume prompt shown when an interrupted install is found
+enum ResumeChoice {
+    Resume(Box<InstallConfig>),
+    Fresh,
+    Quit,
+}
+
+// Offers to resume an interrupted install and collects the secrets it needs again
+fn prompt_resume(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    state: InstallState,
+) -> Result<ResumeChoice> {
+    let mut config = state.config;
+    let summary = build_install_summary(
+        SetupStep::Review,
+        false,
+        None,
+        Some(&config.disk),
+        &config.keymap,
+        &config.timezone,
+        &config.hostname,
+        &config.username,
+        "",
+        "",
+        config.encrypt_disk,
+        config.swap_enabled,
+        None,
+    );
+    let completed = STEP_NAMES
+        .get(state.completed_step)
+        .copied()
+        .unwrap_or("Unknown");
+    let warning = format!(
+        "An interrupted install was found on {}",
+        config.disk.label()
+    );
+    let warning_style = Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD);
+    let warning_lines = vec![Line::from(Span::styled(warning, warning_style))];
+    let info_lines = vec![
+        Line::from(format!("Last completed step: {}", completed)),
+        Line::from("Resume continues from the next step without repartitioning"),
+        Line::from("Choose No to start a fresh install instead"),
+    ];
+    match run_confirm_selector(
+        terminal,
+        "Resume install",
+        &warning_lines,
+        &info_lines,
+        ConfirmMode::YesNo,
+        None,
+        &summary,
+    )? {
+        ConfirmAction::Yes => {}
+        ConfirmAction::No | ConfirmAction::Back => return Ok(ResumeChoice::Fresh),
+        ConfirmAction::Quit => return Ok(ResumeChoice::Quit),
+    }
+
+    // Secrets are not persisted, so ask again for the ones the remaining steps need
+    if config.encrypt_disk {
+        match prompt_resume_secret(terminal, "Disk encryption", "LUKS passphrase", &summary)? {
+            InputAction::Submit(value) => config.luks_password = value,
+            InputAction::Back => return Ok(ResumeChoice::Fresh),
+            InputAction::Quit => return Ok(ResumeChoice::Quit),
+        }
+    }
+    if state.completed_step < 7 {
+        match prompt_resume_secret(terminal, "User password", "Password", &summary)? {
+            InputAction::Submit(value) => config.user_password = value,
+            InputAction::Back => return Ok(ResumeChoice::Fresh),
+            InputAction::Quit => return Ok(ResumeChoice::Quit),
+        }
+    }
+    if state.grub_password_set && state.completed_step < 9 {
+        match prompt_resume_secret(terminal, "GRUB password", "Boot menu password", &summary)? {
+            InputAction::Submit(value) => config.grub_password = Some(value),
+            InputAction::Back => return Ok(ResumeChoice::Fresh),
+            InputAction::Quit => return Ok(ResumeChoice::Quit),
+        }
+    }
+    config.resume_from = state.completed_step + 1;
+    Ok(ResumeChoice::Resume(Box::new(config)))
+}
+
+// Asks for a non-empty masked value when resuming an install
+fn prompt_resume_secret(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    title: &str,
+    input_title: &str,
+    summary: &InstallSummary,
+) -> Result<InputAction> {
+    let controls = vec![
+        Line::from(vec![
+            Span::styled("Enter", Style::default().fg(Color::Cyan)),
+            Span::raw(" to continue "),
+            Span::styled("Esc", Style::default().fg(Color::Cyan)),
+            Span::raw(" to start a fresh install"),
+        ]),
+        Line::from("Re-enter the value used for the interrupted install"),
+    ];
+    loop {
+        let action = run_text_input(
+            terminal,
+            title,
+            &controls,
+            &[],
+            input_title,
+            None,
+            true,
+            summary,
+        )?;
+        if !matches!(&action, InputAction::Submit(value) if value.is_empty()) {
+            return Ok(action);
+        }
+    }
+}
+
+// Wording of the confirmation entry for a masked password or passphrase
+struct ConfirmSecretText {
+    hint: &'static str,
+    info: &'static str,
+    title: &'static str,
+    input_title: &'static str,
+    mismatch: &'static str,
+}
+
+const USER_PASSWORD_CONFIRM: ConfirmSecretText = ConfirmSecretText {
+    hint: "Type to confirm your password",
+    info: "Re-enter the password to confirm",
+    title: "Confirm password",
+    input_title: "Re-enter password",
+    mismatch: "Passwords do not match, try again",
+};
+
+const LUKS_PASSPHRASE_CONFIRM: ConfirmSecretText = ConfirmSecretText {
+    hint: "Type to confirm the passphrase",
+    info: "Re-enter the passphrase to confirm",
+    title: "Confirm passphrase",
+    input_title: "Re-enter encryption passphras",
+    mismatch: "Passphrases do not match, try again",
+};
+
+const GRUB_PASSWORD_CONFIRM: ConfirmSecretText = ConfirmSecretText {
+    hint: "Type to confirm the boot menu password",
+    info: "Re-enter the password to confirm",
+    title: "Confirm password",
+    input_title: "Re-enter boot menu password",
+    mismatch: "Passwords do not match, try again",
+};
+
+// Asks for a secret a second time; Submit once it matches `value`, Back to start over.
+// A mismatch keeps the first entry so only the confirmation is re-typed
+fn confirm_secret(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    text: &ConfirmSecretText,
+    value: &str,
+    summary: &InstallSummary,
+) -> Result<InputAction> {
+    let controls = vec![
+        Line::from(vec![
+            Span::styled("Ctrl+U", Style::default().fg(Color::Cyan)),
+            Span::raw(" or "),
+            Span::styled("Backspace", Style::default().fg(Color::Cyan)),
+            Span::raw(" clears the input "),
+            Span::styled("Esc", Style::default().fg(Color::Cyan)),
+            Span::raw(" to go back"),
+        ]),
+        Line::from(text.hint),
+    ];
+    let mut mismatch = false;
+    loop {
+        let info = if mismatch {
+            vec![
+                Line::from(Span::styled(text.mismatch, Style::default().fg(Color::Red))),
+                Line::from("Re-type the confirmation, or Esc to start over"),
+            ]
+        } else {
+            vec![Line::from(text.info)]
+        };
+        match run_text_input(
+            terminal,
+            text.title,
+            &controls,
+            &info,
+            text.input_title,
+            None,
+            true,
+            summary,
+        )? {
+            InputAction::Submit(confirm) if confirm == value => {
+                return Ok(InputAction::Submit(confirm))
+            }
+            InputAction::Submit(_) => mismatch = true,
+            action => return Ok(action),
+        }
+    }
+}
+
+// How install progress is reported once setup is done
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ProgressOutput {
+    Screen, // Animated progress screen
+    Json,   // One JSON object per event for `--json`
+    Plain,  // Step transitions as text lines for serial consoles
+}
+
+// Runs the installer thread and shows the progress screen until the user exits
+fn run_install_progress(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    mut config: InstallConfig,
+    output: ProgressOutput,
+) -> Result<()> {
+    // The completion screen can open a chroot shell, so it unmounts the target itself
+    config.keep_target_mounted = output == ProgressOutput::Screen;
+    let encrypt_disk = config.encrypt_disk;
     let (tx, rx) = crossbeam_channel::unbounded();
-    let installer_tx = tx.clone();
+    let cancel = Arc::new(AtomicBool::new(false));
+    let installer_cancel = Arc::clone(&cancel);
     thread::spawn(move || {
-        if let Err(err) = run_installer(installer_tx, &config) {
-            let _ = tx.send(InstallerEvent::Done(Some(err.to_string())));
-        }
+        let _ = run_installer(tx, &config, installer_cancel);
     });
 
+    match output {
+        ProgressOutput::Json => {
+            disable_raw_mode().context("disable raw mode")?;
+            clear_screen()?;
+            return stream_install_events(rx);
+        }
+        ProgressOutput::Plain => return print_install_steps(rx),
+        ProgressOutput::Screen => {}
+    }
+
     // Set up the UI for the installation progress screen
     clear_screen()?;
     let step_names: Vec<String> = STEP_NAMES.iter().map(|name| (*name).to_string()).collect();
@@ -1513,7 +3440,14 @@ fn main() -> Result<()> {
         spinner_idx: 0,
         done: false,
         err: None,
+        last_running_step: None,
         log_file,
+        cancel_prompt: false,
+        cancelling: false,
+        log_filter: LogLevel::Command,
+        power_prompt: None,
+        target_mounted: false,
+        firmware_setup: Path::new("/sys/firmware/efi").exists(),
     };
     if app.log_file.is_some() {
         let line = format!("Logging to {}", LOG_FILE_PATH);
@@ -1522,14 +3456,14 @@ fn main() -> Result<()> {
     }
 
     terminal.clear().context("clear terminal")?;
-    terminal.draw(|f| draw_ui(f.size(), f, &app))?;
+    draw_themed(terminal, |f| draw_ui(f.size(), f, &app))?;
 
     // Installation progress screen
     let mut last_tick = Instant::now();
-    let mut reboot_requested = false;
-    let mut shutdown_requested = false;
+    let mut power_action: Option<PowerAction> = None;
+    let mut stay_live = false;
     loop {
-        terminal.draw(|f| draw_ui(f.size(), f, &app))?;
+        draw_themed(terminal, |f| draw_ui(f.size(), f, &app))?;
 
         let timeout = Duration::from_millis(100);
         if event::poll(timeout).context("poll events")? {
@@ -1541,18 +3475,64 @@ fn main() -> Result<()> {
                         {
                             break
                         }
+                        KeyCode::Esc if app.power_prompt.is_some() => {
+                            app.power_prompt = None;
+                        }
+                        KeyCode::Enter if app.power_prompt.is_some() => {
+                            power_action = app.power_prompt.map(|(action, _)| action);
+                            break;
+                        }
                         KeyCode::Char('r') | KeyCode::Char('R')
-                            if app.done && app.err.is_none() =>
+                            if app.done && app.err.is_none() && app.power_prompt.is_none() =>
                         {
-                            reboot_requested = true;
-                            break;
+                            app.power_prompt = Some((PowerAction::Reboot, Instant::now()));
                         }
                         KeyCode::Char('s') | KeyCode::Char('S')
-                            if app.done && app.err.is_none() =>
+                            if app.done && app.err.is_none() && app.power_prompt.is_none() =>
                         {
-                            shutdown_requested = true;
+                            app.power_prompt = Some((PowerAction::Shutdown, Instant::now()));
+                        }
+                        KeyCode::Char('f') | KeyCode::Char('F')
+                            if app.done
+                                && app.err.is_none()
+                                && app.firmware_setup
+                                && app.power_prompt.is_none() =>
+                        {
+                            app.power_prompt = Some((PowerAction::FirmwareSetup, Instant::now()));
+                        }
+                        KeyCode::Char('q') | KeyCode::Char('Q')
+                            if app.done && app.err.is_none() && app.power_prompt.is_none() =>
+                        {
+                            stay_live = true;
                             break;
                         }
+                        KeyCode::Char('c') | KeyCode::Char('C')
+                            if app.done && app.target_mounted && app.power_prompt.is_none() =>
+                        {
+                            run_chroot_shell(terminal, &mut app)?;
+                        }
+                        KeyCode::Char('c') | KeyCode::Char('C') if !app.done && !app.cancelling => {
+                            app.cancel_prompt = !cancel_too_late(&mut app);
+                        }
+                        KeyCode::Char('l') | KeyCode::Char('L') => {
+                            app.log_filter = app.log_filter.next();
+                        }
+                        KeyCode::Char('y') | KeyCode::Char('Y') if app.cancel_prompt => {
+                            app.cancel_prompt = false;
+                            // The bootloader step may have started while the prompt was open
+                            if !cancel_too_late(&mut app) {
+                                app.cancelling = true;
+                                cancel.store(true, Ordering::Relaxed);
+                                let line = "Cancelling after the current step finishes...";
+                                push_log(&mut app.logs, line.to_string());
+                                append_log_file(&mut app.log_file, line);
+                            }
+                        }
+                        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc
+                            if app.cancel_prompt =>
+                        {
+                            app.cancel_prompt = false;
+                        }
                         _ => {}
                     }
                 }
@@ -1560,7 +3540,11 @@ fn main() -> Result<()> {
         }
 
         while let Ok(evt) = rx.try_recv() {
+            let finished = matches!(evt, InstallerEvent::Done(None));
             handle_event(&mut app, evt);
+            if finished {
+                app.target_mounted = target_mounted();
+            }
         }
 
         // Update the spinner animation
@@ -1568,31 +3552,234 @@ fn main() -> Result<()> {
             app.spinner_idx = (app.spinner_idx + 1) % SPINNER_LEN;
             last_tick = Instant::now();
         }
+
+        // Act once the reboot/shutdown countdown runs out
+        if let Some((action, started)) = app.power_prompt {
+            if started.elapsed() >= Duration::from_secs(POWER_COUNTDOWN_SECS) {
+                power_action = Some(action);
+                break;
+            }
+        }
     }
 
     // Clean up the terminal before exiting
     disable_raw_mode().context("disable raw mode")?;
     let _ = clear_screen();
-    if reboot_requested {
-        Command::new("systemctl")
-            .arg("reboot")
-            .status()
-            .context("reboot system")?;
-    } else if shutdown_requested {
-        Command::new("systemctl")
-            .arg("poweroff")
-            .status()
-            .context("power off system")?;
+    if app.target_mounted {
+        println!("Unmounting the installed system...");
+        let (release_tx, release_rx) = crossbeam_channel::unbounded();
+        let released = release_target(&release_tx, encrypt_disk);
+        for evt in release_rx.try_iter() {
+            if let InstallerEvent::Log(line) = evt {
+                append_log_file(&mut app.log_file, &line);
+            }
+        }
+        if let Err(err) = released {
+            println!("Failed to unmount /mnt: {:#}", err);
+            return Ok(());
+        }
+    }
+    match power_action {
+        Some(PowerAction::Reboot) => {
+            Command::new("systemctl")
+                .arg("reboot")
+                .status()
+                .context("reboot system")?;
+        }
+        Some(PowerAction::Shutdown) => {
+            Command::new("systemctl")
+                .arg("poweroff")
+                .status()
+                .context("power off system")?;
+        }
+        Some(PowerAction::FirmwareSetup) => {
+            Command::new("systemctl")
+                .args(["reboot", "--firmware-setup"])
+                .status()
+                .context("reboot into firmware setup")?;
+        }
+        None if stay_live => {
+            println!("Staying in the live environment. Run `systemctl reboot` when you are done.");
+        }
+        None => {}
+    }
+    Ok(())
+}
+
+// True, with a log line, once the install has reached the last cancellable step; a cancel
+// raised during that step would only be checked by the bootloader step, which ignores it
+fn cancel_too_late(app: &mut App) -> bool {
+    if app
+        .last_running_step
+        .is_none_or(|index| index < LAST_CANCELLABLE_STEP)
+    {
+        return false;
     }
+    let line = "Cancelling is not possible once the bootloader step has started.";
+    push_log(&mut app.logs, line.to_string());
+    append_log_file(&mut app.log_file, line);
+    true
+}
+
+// Hands the terminal to a shell inside the installed system and returns to the completion screen
+fn run_chroot_shell(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    app: &mut App,
+) -> Result<()> {
+    disable_raw_mode().context("disable raw mode")?;
+    clear_screen()?;
+    println!("Chroot shell in the installed system. Type `exit` to return to the installer.");
+    let status = Command::new("arch-chroot").args(["/mnt", "bash"]).status();
+    enable_raw_mode().context("enable raw mode")?;
+    terminal.clear().context("clear terminal")?;
+    let line = match status {
+        Ok(_) => "Returned from the chroot shell.".to_string(),
+        Err(err) => format!("Failed to start the chroot shell: {}", err),
+    };
+    push_log(&mut app.logs, line.clone());
+    append_log_file(&mut app.log_file, &line);
     Ok(())
 }
 
+// Prints every installer event as one JSON object per line instead of the progress screen
+fn stream_install_events(rx: crossbeam_channel::Receiver<InstallerEvent>) -> Result<()> {
+    let started = Instant::now();
+    let mut log_file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .open(LOG_FILE_PATH)
+        .ok();
+    let mut stdout = io::stdout().lock();
+    for evt in rx {
+        if let InstallerEvent::Log(line) = &evt {
+            append_log_file(&mut log_file, line);
+        }
+        let mut value = serde_json::to_value(&evt).context("serialize installer event")?;
+        value["elapsed"] = serde_json::json!(started.elapsed().as_secs_f64());
+        writeln!(stdout, "{}", value).context("write installer event")?;
+        stdout.flush().context("flush stdout")?;
+        if let InstallerEvent::Done(err) = evt {
+            return match err {
+                Some(err) => Err(anyhow::anyhow!("Installation failed: {}", err)),
+                None => Ok(()),
+            };
+        }
+    }
+    anyhow::bail!("Installer thread exited without a result")
+}
+
+// Prints step transitions, warnings, and errors as plain lines instead of the progress screen
+fn print_install_steps(rx: crossbeam_channel::Receiver<InstallerEvent>) -> Result<()> {
+    let mut log_file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .open(LOG_FILE_PATH)
+        .ok();
+    println!("Installing; the full log is written to {}", LOG_FILE_PATH);
+    for evt in rx {
+        match evt {
+            InstallerEvent::Log(line) => {
+                append_log_file(&mut log_file, &line);
+                if LogLevel::of(&line) >= LogLevel::Warning {
+                    println!("  {}", line);
+                }
+            }
+            InstallerEvent::Step { index, status, err } => {
+                let name = STEP_NAMES.get(index).copied().unwrap_or("Unknown step");
+                let state = match status {
+                    StepStatus::Pending => continue,
+                    StepStatus::Running => "started",
+                    StepStatus::Done => "done",
+                    StepStatus::Skipped => "skipped",
+                    StepStatus::Failed => "failed",
+                };
+                println!("[{}/{}] {}: {}", index + 1, STEP_NAMES.len(), name, state);
+                if let Some(err) = err {
+                    println!("  {}", err);
+                }
+            }
+            InstallerEvent::Done(Some(err)) => {
+                anyhow::bail!("Installation failed: {}", err);
+            }
+            InstallerEvent::Done(None) => {
+                println!("Installation complete. Run `systemctl reboot` to boot the new system.");
+                return Ok(());
+            }
+            InstallerEvent::Progress(_) | InstallerEvent::StepProgress(_) => {}
+        }
+        io::stdout().flush().context("flush stdout")?;
+    }
+    anyhow::bail!("Installer thread exited without a result")
+}
+
 // Clear the terminal screen
 fn clear_screen() -> Result<()> {
     execute!(io::stdout(), Clear(ClearType::All), cursor::MoveTo(0, 0)).context("clear screen")?;
     Ok(())
 }
 
+// Raw mode for the setup screens, undone on drop so quits, errors and panics leave a usable shell
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enable() -> Result<Self> {
+        enable_raw_mode().context("enable raw mode")?;
+        clear_screen()?;
+        Ok(Self)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
+}
+
+// A panic on the UI thread restores the terminal before its message prints; every panic's
+// backtrace is appended to the crash log
+fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let current = thread::current();
+        let thread_name = current.name().unwrap_or("unnamed");
+        if thread_name == "main" {
+            restore_terminal();
+        }
+        default_hook(info);
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+        let entry = format!(
+            "=== {} at unix time {}, thread '{}' ===\n{}\n{}\n",
+            version_string(),
+            timestamp,
+            thread_name,
+            info,
+            std::backtrace::Backtrace::force_capture()
+        );
+        let written = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(CRASH_LOG_PATH)
+            .and_then(|mut file| file.write_all(entry.as_bytes()));
+        if written.is_ok() {
+            eprintln!("Crash details were written to {}", CRASH_LOG_PATH);
+        }
+    }));
+}
+
+// Leaves raw mode and clears the screen; nothing to do once raw mode is already off
+fn restore_terminal() {
+    if is_raw_mode_enabled().unwrap_or(false) {
+        let _ = disable_raw_mode();
+        let _ = execute!(io::stdout(), DisableMouseCapture);
+        let _ = clear_screen();
+    }
+}
+
 fn handle_event(app: &mut App, evt: InstallerEvent) {
     match evt {
         InstallerEvent::Log(line) => {
@@ -1600,7 +3787,23 @@ fn handle_event(app: &mut App, evt: InstallerEvent) {
             append_log_file(&mut app.log_file, &line);
         }
         InstallerEvent::Progress(value) => app.progress = value,
+        InstallerEvent::StepProgress(fraction) => {
+            // Scale into the running step's slice without moving the bar backwards
+            if let Some(index) = app
+                .steps
+                .iter()
+                .position(|step| step.status == StepStatus::Running)
+            {
+                let value = (index as f64 + fraction.clamp(0.0, 1.0)) / app.steps.len() as f64;
+                if value > app.progress {
+                    app.progress = value;
+                }
+            }
+        }
         InstallerEvent::Step { index, status, err } => {
+            if matches!(status, StepStatus::Running | StepStatus::Done) {
+                app.last_running_step = Some(index);
+            }
             if let Some(step) = app.steps.get_mut(index) {
                 step.status = status;
                 step.err = err.clone();
@@ -1650,28 +3853,138 @@ fn append_log_file(log_file: &mut Option<std::fs::File>, line: &str) {
     }
 }
 
-fn valid_username(value: &str) -> bool {
-    if value.is_empty() || value == "root" {
-        return false;
+// Reasons a username is rejected
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum UsernameError {
+    Empty,
+    TooLong,
+    InvalidStart,
+    InvalidCharacter,
+    TrailingSeparator,
+    Reserved,
+}
+
+impl UsernameError {
+    fn message(self) -> &'static str {
+        match self {
+            UsernameError::Empty => "Username cannot be empty",
+            UsernameError::TooLong => "Username too long (max 32 characters)",
+            UsernameError::InvalidStart => "Username must start with a lowercase letter",
+            UsernameError::InvalidCharacter => "Invalid character in username",
+            UsernameError::TrailingSeparator => "Username cannot end with - or _",
+            UsernameError::Reserved => "Username is reserved for a system account",
+        }
     }
+}
+
+// System accounts that already exist on a fresh Arch install
+const RESERVED_USERNAMES: [&str; 14] = [
+    "root",
+    "bin",
+    "daemon",
+    "mail",
+    "ftp",
+    "http",
+    "nobody",
+    "dbus",
+    "polkitd",
+    "avahi",
+    "sddm",
+    "systemd-network",
+    "systemd-resolve",
+    "systemd-timesync",
+];
+
+// Validates a username for useradd (max 32 characters)
+fn validate_username(value: &str) -> Result<(), UsernameError> {
     let mut chars = value.chars();
     let Some(first) = chars.next() else {
-        return false;
+        return Err(UsernameError::Empty);
     };
+    if value.len() > 32 {
+        return Err(UsernameError::TooLong);
+    }
     if !first.is_ascii_lowercase() {
-        return false;
+        return Err(UsernameError::InvalidStart);
+    }
+    if !chars.all(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit() || ch == '_' || ch == '-') {
+        return Err(UsernameError::InvalidCharacter);
+    }
+    if value.ends_with('-') || value.ends_with('_') {
+        return Err(UsernameError::TrailingSeparator);
     }
-    chars.all(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit() || ch == '_' || ch == '-')
+    if RESERVED_USERNAMES.contains(&value) {
+        return Err(UsernameError::Reserved);
+    }
+    Ok(())
 }
 
-// Validates a hostname
-fn valid_hostname(value: &str) -> bool {
-    if value.is_empty() || value.len() > 63 {
-        return false;
+// Full names go into the GECOS field, where `:` separates the passwd columns
+fn full_name_error(value: &str) -> Option<&'static str> {
+    if value.contains(':') {
+        return Some("Full name cannot contain :");
+    }
+    if value.chars().any(char::is_control) {
+        return Some("Full name cannot contain control characters");
+    }
+    if value.len() > 128 {
+        return Some("Full name must be at most 128 characters");
+    }
+    None
+}
+
+// Validates a hostname per RFC 1123, returning the reason it was rejected.
+// Uppercase letters are accepted since hostnames are case-insensitive.
+fn hostname_error(value: &str) -> Option<&'static str> {
+    if value.is_empty() {
+        return Some("Hostname cannot be empty");
     }
-    value
-        .chars()
-        .all(|ch| ch.is_ascii_alphanumeric() || ch == '-')
+    if value.len() > 63 {
+        return Some("Hostname must be at most 63 characters");
+    }
+    for label in value.split('.') {
+        if label.is_empty() {
+            return Some("Hostname cannot contain empty labels");
+        }
+        if !label
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '-')
+        {
+            return Some("Use only letters, numbers, and hyphens");
+        }
+        if label.starts_with('-') || label.ends_with('-') {
+            return Some("Hostname cannot start or end with a hyphen");
+        }
+    }
+    None
+}
+
+// Validates an NTP server given as an IP address or DNS name
+fn ntp_server_error(value: &str) -> Option<&'static str> {
+    if value.parse::<std::net::IpAddr>().is_ok() {
+        return None;
+    }
+    if value.is_empty() {
+        return Some("NTP server cannot be empty");
+    }
+    if value.len() > 253 {
+        return Some("NTP server name must be at most 253 characters");
+    }
+    for label in value.trim_end_matches('.').split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return Some("NTP server labels must be 1-63 characters");
+        }
+        if !label
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '-')
+        {
+            return Some("Use an IP address or a name with letters, numbers, and hyphens");
+        }
+        if label.starts_with('-') || label.ends_with('-') {
+            return Some("NTP server labels cannot start or end with a hyphen");
+        }
+    }
+    None
 }
 
 // Checks if an error message indicates a Wi-Fi authentication failure
@@ -1683,3 +3996,74 @@ fn is_wifi_auth_error(message: &str) -> bool {
         || msg.contains("authentication")
         || msg.contains("access denied")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hostname_rejects_edge_hyphens() {
+        assert!(hostname_error("-x").is_some());
+        assert!(hostname_error("x-").is_some());
+        assert!(hostname_error("nebula.-x").is_some());
+    }
+
+    #[test]
+    fn hostname_allows_inner_hyphens_and_uppercase() {
+        assert_eq!(hostname_error("a--b"), None);
+        assert_eq!(hostname_error("UPPER"), None);
+        assert_eq!(hostname_error("nebula.local"), None);
+    }
+
+    #[test]
+    fn hostname_rejects_empty_labels() {
+        assert!(hostname_error("").is_some());
+        assert!(hostname_error("a..b").is_some());
+        assert!(hostname_error(".a").is_some());
+    }
+
+    #[test]
+    fn hostname_length_limit() {
+        assert_eq!(hostname_error(&"a".repeat(63)), None);
+        assert!(hostname_error(&"a".repeat(64)).is_some());
+    }
+
+    #[test]
+    fn username_length_limit() {
+        assert_eq!(validate_username(&"a".repeat(32)), Ok(()));
+        assert_eq!(
+            validate_username(&"a".repeat(33)),
+            Err(UsernameError::TooLong)
+        );
+    }
+
+    #[test]
+    fn username_rejects_bad_edges() {
+        assert_eq!(validate_username(""), Err(UsernameError::Empty));
+        assert_eq!(validate_username("1user"), Err(UsernameError::InvalidStart));
+        assert_eq!(validate_username("User"), Err(UsernameError::InvalidStart));
+        assert_eq!(
+            validate_username("user-"),
+            Err(UsernameError::TrailingSeparator)
+        );
+        assert_eq!(
+            validate_username("user_"),
+            Err(UsernameError::TrailingSeparator)
+        );
+        assert_eq!(
+            validate_username("us.er"),
+            Err(UsernameError::InvalidCharacter)
+        );
+        assert_eq!(validate_username("nebula_user-2"), Ok(()));
+    }
+
+    #[test]
+    fn username_rejects_reserved_accounts() {
+        for name in ["root", "daemon", "bin"] {
+            assert_eq!(validate_username(name), Err(UsernameError::Reserved));
+        }
+        assert!(RESERVED_USERNAMES
+            .iter()
+            .all(|name| validate_username(name) == Err(UsernameError::Reserved)));
+    }
+}
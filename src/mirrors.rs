@@ -0,0 +1,70 @@
+/////////
+/// Mirror ranking by TCP connect latency, for when reflector is missing
+////////
+use std::net::{TcpStream, ToSocketAddrs};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Well-known Arch mirrors probed when reflector cannot rank the full list
+const CANDIDATE_MIRRORS: [&str; 9] = [
+    "https://geo.mirror.pkgbuild.com/$repo/os/$arch",
+    "https://mirrors.kernel.org/archlinux/$repo/os/$arch",
+    "https://mirror.rackspace.com/archlinux/$repo/os/$arch",
+    "https://mirror.leaseweb.net/archlinux/$repo/os/$arch",
+    "https://arch.mirror.constant.com/$repo/os/$arch",
+    "https://ftp.halifax.rwth-aachen.de/archlinux/$repo/os/$arch",
+    "https://mirror.aarnet.edu.au/pub/archlinux/$repo/os/$arch",
+    "https://mirrors.tuna.tsinghua.edu.cn/archlinux/$repo/os/$arch",
+    "https://mirror.xtom.com.hk/archlinux/$repo/os/$arch",
+];
+
+// Mirrors slower than this to accept a connection are treated as dead
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+// Host and port to probe for a mirror URL, e.g. `mirrors.kernel.org:443`
+fn mirror_address(url: &str) -> Option<String> {
+    let (scheme, rest) = url.split_once("://")?;
+    let host = rest.split('/').next().filter(|host| !host.is_empty())?;
+    if host.contains(':') {
+        return Some(host.to_string());
+    }
+    let port = match scheme {
+        "https" => 443,
+        "http" => 80,
+        _ => return None,
+    };
+    Some(format!("{}:{}", host, port))
+}
+
+// Time to open a TCP connection to the mirror; None if it does not resolve or answer in time
+fn connect_latency(url: &str) -> Option<Duration> {
+    let address = mirror_address(url)?.to_socket_addrs().ok()?.next()?;
+    let started = Instant::now();
+    TcpStream::connect_timeout(&address, CONNECT_TIMEOUT).ok()?;
+    Some(started.elapsed())
+}
+
+// Probes every candidate mirror at once; returns the reachable ones, fastest first
+pub(crate) fn rank_by_latency() -> Vec<(&'static str, Duration)> {
+    let mut ranked: Vec<(&'static str, Duration)> = thread::scope(|scope| {
+        let probes: Vec<_> = CANDIDATE_MIRRORS
+            .iter()
+            .map(|url| scope.spawn(move || connect_latency(url).map(|latency| (*url, latency))))
+            .collect();
+        probes
+            .into_iter()
+            .filter_map(|probe| probe.join().ok().flatten())
+            .collect()
+    });
+    ranked.sort_by_key(|(_, latency)| *latency);
+    ranked
+}
+
+// Mirrorlist with one `Server` line per ranked mirror and its latency as a comment
+pub(crate) fn render_mirrorlist(ranked: &[(&str, Duration)]) -> String {
+    let mut contents = String::from("# Ranked by TCP connect latency from the live environment\n");
+    for (url, latency) in ranked {
+        contents.push_str(&format!("# {} ms\nServer = {}\n", latency.as_millis(), url));
+    }
+    contents
+}
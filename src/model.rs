@@ -1,8 +1,13 @@
 use std::collections::VecDeque;
 use std::fs::File;
+use std::time::Instant;
+
+use serde::ser::{SerializeMap, Serializer};
+use serde::Serialize;
 
 // Single step in the installation process
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum StepStatus {
     Pending, // Not yet started
     Running, // Currently in progress
@@ -18,12 +23,88 @@ pub struct Step {
     pub err: Option<String>, // An error message if the step failed
 }
 
+// Severity of a log line, ordered from least to most important
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Command, // Echoed `$ ...` command lines
+    Info,    // Regular output
+    Warning, // pacman and tool warnings
+    Error,   // Errors and failed commands
+}
+
+impl LogLevel {
+    // Tags a log line by its prefix and wording
+    pub fn of(line: &str) -> Self {
+        if line.starts_with("$ ") {
+            return LogLevel::Command;
+        }
+        let lower = line.to_ascii_lowercase();
+        if lower.starts_with("error")
+            || lower.contains("error:")
+            || lower.contains("failed")
+            || lower.contains("fatal")
+        {
+            LogLevel::Error
+        } else if lower.starts_with("warning") || lower.contains("warning:") {
+            LogLevel::Warning
+        } else {
+            LogLevel::Info
+        }
+    }
+
+    // Next minimum level for the log filter, wrapping back to showing everything
+    pub fn next(self) -> Self {
+        match self {
+            LogLevel::Command => LogLevel::Info,
+            LogLevel::Info => LogLevel::Warning,
+            LogLevel::Warning => LogLevel::Error,
+            LogLevel::Error => LogLevel::Command,
+        }
+    }
+
+    // Log panel title suffix describing what the filter shows
+    pub fn filter_label(self) -> &'static str {
+        match self {
+            LogLevel::Command => "all",
+            LogLevel::Info => "no commands",
+            LogLevel::Warning => "warnings and errors",
+            LogLevel::Error => "errors only",
+        }
+    }
+}
+
+// Seconds the reboot/shutdown confirmation waits before acting
+pub const POWER_COUNTDOWN_SECS: u64 = 5;
+
+// What to do with the machine once the installation has finished
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PowerAction {
+    Reboot,
+    Shutdown,
+    FirmwareSetup, // Reboot straight into the UEFI setup, e.g. to toggle Secure Boot
+}
+
+impl PowerAction {
+    // Verb shown in the countdown overlay
+    pub fn label(self) -> &'static str {
+        match self {
+            PowerAction::Reboot => "Rebooting",
+            PowerAction::Shutdown => "Shutting down",
+            PowerAction::FirmwareSetup => "Rebooting into firmware setup",
+        }
+    }
+}
+
 // Events sent from the installer thread to the main UI
+// Frontends can rely on `Step` indexes matching `STEP_NAMES`, each step going Running then
+// Done/Failed (or straight to Skipped/Done), and exactly one `Done` as the last event
 pub enum InstallerEvent {
     // A log message to be displayed in the UI
     Log(String),
     // The overall installation progress, as a value between 0.0 and 1.0
     Progress(f64),
+    // Progress within the running step, as a value between 0.0 and 1.0
+    StepProgress(f64),
     // An update on the status of a specific step
     Step {
         index: usize,
@@ -34,6 +115,38 @@ pub enum InstallerEvent {
     Done(Option<String>),
 }
 
+// One flat object per event for `--json`, e.g. `{"event":"step","index":2,"status":"running"}`
+impl Serialize for InstallerEvent {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        match self {
+            InstallerEvent::Log(line) => {
+                map.serialize_entry("event", "log")?;
+                map.serialize_entry("line", line)?;
+            }
+            InstallerEvent::Progress(value) => {
+                map.serialize_entry("event", "progress")?;
+                map.serialize_entry("progress", value)?;
+            }
+            InstallerEvent::StepProgress(value) => {
+                map.serialize_entry("event", "step_progress")?;
+                map.serialize_entry("progress", value)?;
+            }
+            InstallerEvent::Step { index, status, err } => {
+                map.serialize_entry("event", "step")?;
+                map.serialize_entry("index", index)?;
+                map.serialize_entry("status", status)?;
+                map.serialize_entry("error", err)?;
+            }
+            InstallerEvent::Done(err) => {
+                map.serialize_entry("event", "done")?;
+                map.serialize_entry("error", err)?;
+            }
+        }
+        map.end()
+    }
+}
+
 // The main application state
 pub struct App {
     // The list of all installation steps
@@ -48,6 +161,20 @@ pub struct App {
     pub done: bool,
     // A final error message if the installation failed
     pub err: Option<String>,
+    // The last step that started running or finished, named on the failure panel
+    pub last_running_step: Option<usize>,
     // An optional handle to the log file for writing logs to disk
     pub log_file: Option<File>,
+    // Whether the cancel confirmation is shown
+    pub cancel_prompt: bool,
+    // Whether the user cancelled and cleanup is pending
+    pub cancelling: bool,
+    // Lowest log level shown in the log panel
+    pub log_filter: LogLevel,
+    // Pending reboot/shutdown and when the countdown started
+    pub power_prompt: Option<(PowerAction, Instant)>,
+    // Whether the installed system is still mounted, which enables the chroot shell
+    pub target_mounted: bool,
+    // Whether the machine booted via UEFI, which allows rebooting into firmware setup
+    pub firmware_setup: bool,
 }
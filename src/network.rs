@@ -1,5 +1,43 @@
 use anyhow::{Context, Result};
+use std::collections::BTreeSet;
 use std::process::Command;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+// Below this download speed the offline install is suggested
+pub const SLOW_DOWNLOAD_MBPS: f64 = 1.0;
+// Small file on the default mirror used to measure download speed
+const SPEED_TEST_URL: &str = "https://mirror.nebulalinux.com/stable/core/os/x86_64/core.db";
+// Extra readiness checks before a fresh connection counts as offline; DNS can take a moment
+pub const DEFAULT_NETWORK_RETRIES: u32 = 3;
+// Wait before the first retry, doubled after each one up to the cap
+const NETWORK_RETRY_DELAY: Duration = Duration::from_millis(500);
+const NETWORK_RETRY_MAX_DELAY: Duration = Duration::from_secs(8);
+
+// State of the background download speed test
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SpeedTest {
+    NotStarted,
+    Running,
+    Done(f64), // Measured speed in MB/s
+    Failed,
+}
+
+static SPEED_TEST: Mutex<SpeedTest> = Mutex::new(SpeedTest::NotStarted);
+
+// State of the background internet check that retries before reporting no access
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NetworkCheck {
+    Idle,
+    Running,
+    Done(bool), // Whether the internet turned out to be reachable
+}
+
+static NETWORK_CHECK: Mutex<NetworkCheck> = Mutex::new(NetworkCheck::Idle);
+
+// Wi-Fi profiles added by this installer run, so quitting only removes ours
+static CREATED_WIFI_CONNECTIONS: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
 
 // Detected Wi-Fi network
 #[derive(Clone, Debug)]
@@ -8,6 +46,7 @@ pub struct WifiNetwork {
     pub signal: u8,       // Signal strength in percentage
     pub security: String, // Security type (e.g., "WPA2")
     pub in_use: bool,     // Whether this network is currently connected
+    pub hidden: bool,     // Entered by hand because the SSID is not broadcast
 }
 
 impl WifiNetwork {
@@ -16,6 +55,38 @@ impl WifiNetwork {
         let security = self.security.trim();
         security.is_empty() || security == "--"
     }
+
+    // Checks if the network uses 802.1X (WPA2-Enterprise) authentication
+    pub fn is_enterprise(&self) -> bool {
+        self.security.contains("802.1X")
+    }
+
+    // Wi-Fi glyph with one more bar for every 20% of signal
+    pub fn signal_icon(&self) -> &'static str {
+        match self.signal {
+            0..=19 => "󰤯",
+            20..=39 => "󰤟",
+            40..=59 => "󰤢",
+            60..=79 => "󰤥",
+            _ => "󰤨",
+        }
+    }
+
+    // Short security label for the list, e.g. "WPA2/WPA3", "Enterprise" or "Open"
+    pub fn security_label(&self) -> String {
+        if self.is_open() {
+            "Open".to_string()
+        } else if self.is_enterprise() {
+            "Enterprise".to_string()
+        } else if self.security.trim().is_empty() {
+            "Secured".to_string()
+        } else {
+            self.security
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join("/")
+        }
+    }
 }
 
 // Current internet connectivity status
@@ -49,6 +120,78 @@ pub fn is_network_ready() -> Result<bool> {
     }
 }
 
+// Checks readiness up to `retries` more times with backoff, also asking the mirror directly
+// so a flaky DNS or NetworkManager probe does not count as no internet
+pub fn wait_for_network_ready(retries: u32) -> bool {
+    let mut delay = NETWORK_RETRY_DELAY;
+    for attempt in 0..=retries {
+        if is_network_ready().unwrap_or(false) || mirror_reachable() {
+            return true;
+        }
+        if attempt < retries {
+            thread::sleep(delay);
+            delay = (delay * 2).min(NETWORK_RETRY_MAX_DELAY);
+        }
+    }
+    false
+}
+
+// Runs `wait_for_network_ready` on a background thread unless a check is already running
+pub fn start_network_check(retries: u32) {
+    let Ok(mut state) = NETWORK_CHECK.lock() else {
+        return;
+    };
+    if *state == NetworkCheck::Running {
+        return;
+    }
+    *state = NetworkCheck::Running;
+    thread::spawn(move || {
+        let ready = wait_for_network_ready(retries);
+        if let Ok(mut state) = NETWORK_CHECK.lock() {
+            *state = NetworkCheck::Done(ready);
+        }
+    });
+}
+
+// Latest internet check state, read by the Wi-Fi screen on every frame
+pub fn network_check() -> NetworkCheck {
+    NETWORK_CHECK
+        .lock()
+        .map(|state| *state)
+        .unwrap_or(NetworkCheck::Done(false))
+}
+
+// Hands out a finished check's result once, leaving the check idle again
+pub fn take_network_check_result() -> Option<bool> {
+    let mut state = NETWORK_CHECK.lock().ok()?;
+    match *state {
+        NetworkCheck::Done(ready) => {
+            *state = NetworkCheck::Idle;
+            Some(ready)
+        }
+        _ => None,
+    }
+}
+
+// HEAD request to the mirror used for the speed test
+fn mirror_reachable() -> bool {
+    Command::new("curl")
+        .args([
+            "-fsS",
+            "--head",
+            "-o",
+            "/dev/null",
+            "--connect-timeout",
+            "3",
+            "--max-time",
+            "3",
+            &mirror_test_url(),
+        ])
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
 // Currently active network connection
 // For wired connections, it returns "Wired", for Wi-Fi, it returns the SSID
 pub fn active_connection_label() -> Result<Option<String>> {
@@ -168,6 +311,7 @@ pub fn list_wifi_networks() -> Result<Vec<WifiNetwork>> {
             signal,
             security,
             in_use,
+            hidden: false,
         });
     }
     networks.sort_by(|a, b| b.signal.cmp(&a.signal).then_with(|| a.ssid.cmp(&b.ssid)));
@@ -181,8 +325,56 @@ pub fn connect_wifi_profile(
     password: Option<&str>,
     device: Option<&str>,
     name: Option<&str>,
+    hidden: bool,
 ) -> Result<()> {
     let name = name.unwrap_or(ssid);
+    add_wifi_connection(ssid, device, name, hidden)?;
+    if let Some(password) = password {
+        if !password.trim().is_empty() {
+            run_nmcli_status(&[
+                "connection",
+                "modify",
+                name,
+                "wifi-sec.key-mgmt",
+                "wpa-psk",
+                "wifi-sec.psk",
+                password,
+            ])?;
+        }
+    }
+    run_nmcli_status(&["connection", "up", "id", name])
+}
+
+// Connects to a WPA2-Enterprise network using PEAP with MSCHAPv2
+pub fn connect_enterprise_wifi_profile(
+    ssid: &str,
+    identity: &str,
+    password: &str,
+    device: Option<&str>,
+    name: Option<&str>,
+) -> Result<()> {
+    let name = name.unwrap_or(ssid);
+    add_wifi_connection(ssid, device, name, false)?;
+    run_nmcli_status(&[
+        "connection",
+        "modify",
+        name,
+        "wifi-sec.key-mgmt",
+        "wpa-eap",
+        "802-1x.eap",
+        "peap",
+        "802-1x.phase2-auth",
+        "mschapv2",
+        "802-1x.identity",
+        identity,
+        "802-1x.password",
+        password,
+    ])?;
+    run_nmcli_status(&["connection", "up", "id", name])
+}
+
+// Adds a Wi-Fi connection profile, replacing any existing one with the same name
+fn add_wifi_connection(ssid: &str, device: Option<&str>, name: &str, hidden: bool) -> Result<()> {
     let _ = run_nmcli_status(&["connection", "delete", "id", name]);
     let mut add_args = vec![
         "connection",
@@ -200,37 +392,112 @@ pub fn connect_wifi_profile(
             add_args.push(device);
         }
     }
+    if hidden {
+        // Probe for the SSID instead of waiting for it to show up in a scan
+        add_args.push("wifi.hidden");
+        add_args.push("yes");
+    }
     run_nmcli_status(&add_args)?;
-    if let Some(password) = password {
-        if !password.trim().is_empty() {
-            run_nmcli_status(&[
-                "connection",
-                "modify",
-                name,
-                "wifi-sec.key-mgmt",
-                "wpa-psk",
-                "wifi-sec.psk",
-                password,
-            ])?;
-        }
+    if let Ok(mut created) = CREATED_WIFI_CONNECTIONS.lock() {
+        created.insert(name.to_string());
     }
-    run_nmcli_status(&["connection", "up", "id", name])
+    Ok(())
 }
 
-// Removes saved Wi-Fi connection profiles to avoid stale credentials
-pub fn forget_wifi_connection(_ssid: &str) -> Result<()> {
+// Removes a saved Wi-Fi connection profile to avoid stale credentials
+pub fn forget_wifi_connection(name: &str) -> Result<()> {
     let output = run_nmcli(&["-t", "-f", "NAME,TYPE", "connection", "show"])?;
     for line in output.lines() {
         let mut parts = line.split(':');
-        let name = parts.next().unwrap_or("").trim();
+        let existing = parts.next().unwrap_or("").trim();
         let conn_type = parts.next().unwrap_or("").trim();
-        if conn_type == "wifi" && !name.is_empty() {
+        if conn_type == "wifi" && existing == name {
             let _ = run_nmcli_status(&["connection", "delete", "id", name]);
         }
     }
+    if let Ok(mut created) = CREATED_WIFI_CONNECTIONS.lock() {
+        created.remove(name);
+    }
     Ok(())
 }
 
+// Removes every Wi-Fi profile this installer run created, leaving pre-existing ones alone
+pub fn forget_session_wifi_connections() {
+    let created = match CREATED_WIFI_CONNECTIONS.lock() {
+        Ok(mut created) => std::mem::take(&mut *created),
+        Err(_) => return,
+    };
+    for name in created {
+        let _ = forget_wifi_connection(&name);
+    }
+}
+
+// core.db on NEBULA_PACMAN_MIRROR, or on the default mirror
+fn mirror_test_url() -> String {
+    std::env::var("NEBULA_PACMAN_MIRROR")
+        .ok()
+        .map(|base| base.trim().trim_end_matches('/').to_string())
+        .filter(|base| !base.is_empty())
+        .map(|base| format!("{}/core/os/x86_64/core.db", base))
+        .unwrap_or_else(|| SPEED_TEST_URL.to_string())
+}
+
+// Downloads a small file from the mirror and returns the speed in MB/s
+pub fn measure_download_speed() -> Result<f64> {
+    let url = mirror_test_url();
+    let output = Command::new("curl")
+        .args([
+            "-fsS",
+            "-o",
+            "/dev/null",
+            "--connect-timeout",
+            "3",
+            "--max-time",
+            "8",
+            "-w",
+            "%{speed_download}",
+            &url,
+        ])
+        .output()
+        .context("run curl")?;
+    if !output.status.success() {
+        anyhow::bail!("speed test download failed");
+    }
+    let bytes_per_sec: f64 = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .context("parse download speed")?;
+    Ok(bytes_per_sec / 1_000_000.0)
+}
+
+// Starts the speed test on a background thread unless it already ran
+pub fn start_speed_test() {
+    let Ok(mut state) = SPEED_TEST.lock() else {
+        return;
+    };
+    if matches!(*state, SpeedTest::Running | SpeedTest::Done(_)) {
+        return;
+    }
+    *state = SpeedTest::Running;
+    thread::spawn(|| {
+        let result = match measure_download_speed() {
+            Ok(speed) => SpeedTest::Done(speed),
+            Err(_) => SpeedTest::Failed,
+        };
+        if let Ok(mut state) = SPEED_TEST.lock() {
+            *state = result;
+        }
+    });
+}
+
+// Latest speed test state, read by the network screens on every frame
+pub fn speed_test() -> SpeedTest {
+    SPEED_TEST
+        .lock()
+        .map(|state| *state)
+        .unwrap_or(SpeedTest::Failed)
+}
+
 // Checks if any network device is currently in a "connected" state
 fn has_connected_device() -> Result<bool> {
     let output = run_nmcli(&["-t", "-f", "DEVICE,TYPE,STATE", "dev", "status"])?;
@@ -1,5 +1,137 @@
+use std::fs;
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
 use crate::config::config;
+use crate::selection::PackageSelection;
+
+const SEARCH_RESULT_LIMIT: usize = 50;
 
 pub fn required_packages() -> Vec<String> {
     config().packages.required.clone()
 }
+
+// AUR helper installed on the target system
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AurHelper {
+    #[default]
+    Yay, // yay from the Nebula repo
+    Paru, // paru from the Nebula repo
+    None, // No helper, AUR packages are skipped
+}
+
+impl AurHelper {
+    // Package name of the helper, if any
+    pub fn package(self) -> Option<&'static str> {
+        match self {
+            AurHelper::Yay => Some("yay"),
+            AurHelper::Paru => Some("paru"),
+            AurHelper::None => None,
+        }
+    }
+
+    // Checks if a package name refers to this helper, including -bin builds
+    pub fn matches_package(self, pkg: &str) -> bool {
+        self.package()
+            .map(|name| pkg == name || pkg.strip_suffix("-bin") == Some(name))
+            .unwrap_or(false)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AurHelper::Yay => "yay",
+            AurHelper::Paru => "paru",
+            AurHelper::None => "None",
+        }
+    }
+}
+
+// Searches the sync databases for packages, falling back to the offline repo listing
+pub fn search_packages(query: &str) -> Vec<String> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let offline_only = std::env::var("NEBULA_OFFLINE_ONLY").ok().as_deref() == Some("1");
+    if !offline_only {
+        if let Ok(output) = Command::new("pacman").args(["-Ssq", query]).output() {
+            if output.status.success() {
+                let results: Vec<String> = String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .map(|line| line.trim().to_string())
+                    .filter(|line| !line.is_empty())
+                    .take(SEARCH_RESULT_LIMIT)
+                    .collect();
+                if !results.is_empty() {
+                    return results;
+                }
+            }
+        }
+    }
+
+    let needle = query.to_lowercase();
+    let mut results: Vec<String> = offline_repo_packages()
+        .into_iter()
+        .filter(|name| name.to_lowercase().contains(&needle))
+        .collect();
+    results.truncate(SEARCH_RESULT_LIMIT);
+    results
+}
+
+// Lists package names in the offline repo on the live ISO
+fn offline_repo_packages() -> Vec<String> {
+    // Package files are named <name>-<pkgver>-<pkgrel>-<arch>.pkg.tar.zst
+    let mut names: Vec<String> = fs::read_dir("/opt/nebula-repo")
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|entry| {
+                    let file_name = entry.file_name().to_string_lossy().to_string();
+                    let stem = file_name.strip_suffix(".pkg.tar.zst")?;
+                    let parts: Vec<&str> = stem.rsplitn(4, '-').collect();
+                    (parts.len() == 4).then(|| parts[3].to_string())
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names.dedup();
+    names
+}
+
+// Reads a newline-separated package list, splitting repo packages from AUR ones
+pub fn load_package_list(path: &str) -> Result<PackageSelection> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("read package list {}", path))?;
+    let offline_packages = offline_repo_packages();
+    let mut selection = PackageSelection::default();
+    for line in contents.lines() {
+        let name = line.trim();
+        if name.is_empty() || name.starts_with('#') {
+            continue;
+        }
+        let name = name.to_string();
+        if selection.pacman.contains(&name) || selection.yay.contains(&name) {
+            continue;
+        }
+        if offline_packages.contains(&name) || in_sync_repos(&name) {
+            selection.pacman.push(name);
+        } else {
+            selection.yay.push(name);
+        }
+    }
+    Ok(selection)
+}
+
+// Checks if a package is available in the configured pacman repos
+fn in_sync_repos(name: &str) -> bool {
+    Command::new("pacman")
+        .args(["-Si", name])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
@@ -0,0 +1,57 @@
+// Reading the battery and charger state from sysfs
+use std::fs;
+use std::path::Path;
+
+const POWER_SUPPLY_DIR: &str = "/sys/class/power_supply";
+
+// Below this charge an install on battery alone is worth a warning
+pub const LOW_BATTERY_PERCENT: u8 = 30;
+
+// Charge of the first battery and whether a charger is plugged in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatteryStatus {
+    pub capacity: u8,
+    pub on_ac: bool,
+}
+
+impl BatteryStatus {
+    // Low charge with nothing to top it up during the install
+    pub fn is_low(&self) -> bool {
+        !self.on_ac && self.capacity < LOW_BATTERY_PERCENT
+    }
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|value| value.trim().to_string())
+}
+
+// None when there is no battery or its capacity cannot be read
+pub fn battery_status() -> Option<BatteryStatus> {
+    let mut entries: Vec<_> = fs::read_dir(POWER_SUPPLY_DIR)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .collect();
+    entries.sort();
+    let name_starts = |path: &Path, prefix: &str| {
+        path.file_name()
+            .map(|name| name.to_string_lossy().starts_with(prefix))
+            .unwrap_or(false)
+    };
+    let capacity = entries
+        .iter()
+        .filter(|path| name_starts(path, "BAT"))
+        .find_map(|path| read_trimmed(&path.join("capacity"))?.parse::<u8>().ok())?;
+    // Chargers show up as AC*, ADP* or anything else of type Mains
+    let on_ac = entries
+        .iter()
+        .filter(|path| {
+            name_starts(path, "AC")
+                || name_starts(path, "ADP")
+                || read_trimmed(&path.join("type")).as_deref() == Some("Mains")
+        })
+        .any(|path| read_trimmed(&path.join("online")).as_deref() == Some("1"));
+    Some(BatteryStatus { capacity, on_ac })
+}
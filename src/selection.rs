@@ -7,7 +7,13 @@ pub struct PackageSelection {
 
 impl PackageSelection {}
 
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
 use crate::config::{config, ChoiceConfig};
+use crate::drivers::{lib32_driver_packages, GpuVendor, NvidiaVariant};
+use crate::installer::{default_subvolumes, SubvolSpec};
 
 // Single installable application choice in the UI
 pub type InstallChoice = ChoiceConfig;
@@ -19,6 +25,8 @@ pub struct AppSelectionFlags {
     pub browsers: Vec<bool>,
     pub editors: Vec<bool>,
     pub terminals: Vec<bool>,
+    pub services: Vec<bool>,
+    pub extra_packages: Vec<String>, // Packages added through the search panel
 }
 
 impl AppSelectionFlags {
@@ -53,6 +61,54 @@ impl AppSelectionFlags {
             browsers,
             editors,
             terminals: vec![false; terminal_choices().len()],
+            services: SYSTEM_SERVICES
+                .iter()
+                .map(|service| match service {
+                    SystemService::Bluetooth => has_bluetooth_controller(),
+                    SystemService::Tlp => is_laptop(),
+                    SystemService::Fingerprint => has_fingerprint_reader(),
+                    SystemService::Multilib => true,
+                    _ => false,
+                })
+                .collect(),
+            extra_packages: Vec::new(),
+        }
+    }
+
+    // Deselects every application and service, used by the minimal profile
+    pub fn clear(&mut self) {
+        for flags in [
+            &mut self.compositors,
+            &mut self.browsers,
+            &mut self.editors,
+            &mut self.terminals,
+            &mut self.services,
+        ] {
+            flags.fill(false);
+        }
+        self.extra_packages.clear();
+    }
+
+    // Toggles a service; TLP and power-profiles-daemon conflict, and gaming requires multilib
+    pub fn toggle_service(&mut self, index: usize) {
+        let Some(flag) = self.services.get_mut(index) else {
+            return;
+        };
+        if !*flag && !system_service_available(SYSTEM_SERVICES[index]) {
+            return;
+        }
+        *flag = !*flag;
+        let (other, other_enabled) = match (SYSTEM_SERVICES.get(index), *flag) {
+            (Some(SystemService::Tlp), true) => (SystemService::PowerProfiles, false),
+            (Some(SystemService::PowerProfiles), true) => (SystemService::Tlp, false),
+            (Some(SystemService::Gaming), true) => (SystemService::Multilib, true),
+            (Some(SystemService::Multilib), false) => (SystemService::Gaming, false),
+            _ => return,
+        };
+        if let Some(idx) = SYSTEM_SERVICES.iter().position(|service| *service == other) {
+            if let Some(flag) = self.services.get_mut(idx) {
+                *flag = other_enabled;
+            }
         }
     }
 
@@ -61,6 +117,232 @@ impl AppSelectionFlags {
             self.compositors[0] = true;
         }
     }
+
+    // Checks if an optional system service has been toggled on
+    pub fn service_enabled(&self, service: SystemService) -> bool {
+        SYSTEM_SERVICES
+            .iter()
+            .position(|candidate| *candidate == service)
+            .and_then(|idx| self.services.get(idx).copied())
+            .unwrap_or(false)
+    }
+}
+
+// What kind of system gets installed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InstallProfile {
+    #[default]
+    Full, // Nebula desktop with Hyprland, themes, and selected apps
+    Minimal, // Bootable base system with the kernel and drivers only
+}
+
+impl InstallProfile {
+    pub fn label(self) -> &'static str {
+        match self {
+            InstallProfile::Full => "Full desktop",
+            InstallProfile::Minimal => "Minimal (no desktop)",
+        }
+    }
+}
+
+// CPU vulnerability mitigations set on the kernel cmdline
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Mitigations {
+    #[default]
+    Default, // No parameter; the kernel picks its usual mitigations
+    Off,  // mitigations=off for speed on trusted single-user machines
+    Auto, // mitigations=auto,nosmt, which also turns SMT off on affected CPUs
+}
+
+impl Mitigations {
+    pub fn label(self) -> &'static str {
+        match self {
+            Mitigations::Default => "Kernel default",
+            Mitigations::Off => "Off",
+            Mitigations::Auto => "Auto, no SMT",
+        }
+    }
+
+    pub fn kernel_param(self) -> Option<&'static str> {
+        match self {
+            Mitigations::Default => None,
+            Mitigations::Off => Some("mitigations=off"),
+            Mitigations::Auto => Some("mitigations=auto,nosmt"),
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Mitigations::Default => "Keep the kernel's standard protections against Spectre-class CPU bugs",
+            Mitigations::Off => "Disable every CPU vulnerability mitigation for extra performance. Only for trusted, single-user machines",
+            Mitigations::Auto => "Apply all mitigations and disable SMT (hyper-threading) on CPUs that need it. Safest, but slower",
+        }
+    }
+}
+
+// Btrfs subvolume presets offered after the disk is chosen
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BtrfsLayout {
+    #[default]
+    Default, // @ and @home
+    Minimal, // A single @ subvolume for /
+    Snapper, // Separate logs, package cache, and snapshots so rollbacks keep them
+}
+
+impl BtrfsLayout {
+    pub fn label(self) -> &'static str {
+        match self {
+            BtrfsLayout::Default => "Default (@, @home)",
+            BtrfsLayout::Minimal => "Minimal (@ only)",
+            BtrfsLayout::Snapper => "Snapper-friendly",
+        }
+    }
+
+    pub fn subvolumes(self) -> Vec<SubvolSpec> {
+        match self {
+            BtrfsLayout::Default => default_subvolumes(),
+            BtrfsLayout::Minimal => vec![SubvolSpec::new("@", "/", "compress=zstd")],
+            BtrfsLayout::Snapper => vec![
+                SubvolSpec::new("@", "/", "compress=zstd"),
+                SubvolSpec::new("@home", "/home", "compress=zstd"),
+                SubvolSpec::new("@snapshots", "/.snapshots", "compress=zstd"),
+                SubvolSpec::new("@log", "/var/log", "compress=zstd"),
+                SubvolSpec::new("@cache", "/var/cache/pacman/pkg", "compress=zstd"),
+            ],
+        }
+    }
+}
+
+// Optional system services offered next to the application lists
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SystemService {
+    Printing,      // CUPS printing with network printer discovery
+    Bluetooth,     // BlueZ stack and bluetooth.service
+    Flatpak,       // Flatpak with the Flathub remote
+    Mdns,          // Avahi with nss-mdns for <hostname>.local resolution
+    Tlp,           // TLP battery tuning, on by default for laptops
+    PowerProfiles, // power-profiles-daemon, alternative to TLP
+    Fingerprint,   // fprintd with PAM login/sudo, only offered when a reader is found
+    Multilib,      // 32-bit [multilib] repo for Steam and Wine, on by default
+    Gaming,        // Steam, Lutris, GameMode, and MangoHud; requires multilib
+}
+
+pub const SYSTEM_SERVICES: [SystemService; 9] = [
+    SystemService::Printing,
+    SystemService::Bluetooth,
+    SystemService::Flatpak,
+    SystemService::Mdns,
+    SystemService::Tlp,
+    SystemService::PowerProfiles,
+    SystemService::Fingerprint,
+    SystemService::Multilib,
+    SystemService::Gaming,
+];
+
+// Display label for a system service
+pub fn system_service_label(service: SystemService) -> &'static str {
+    match service {
+        SystemService::Printing => "Printing (CUPS)",
+        SystemService::Bluetooth => "Bluetooth",
+        SystemService::Flatpak => "Flatpak (Flathub)",
+        SystemService::Mdns => "mDNS (.local names)",
+        SystemService::Tlp => "Power saving (TLP)",
+        SystemService::PowerProfiles => "Power profiles daemon",
+        SystemService::Fingerprint => "Fingerprint reader",
+        SystemService::Multilib => "32-bit libraries (multilib)",
+        SystemService::Gaming => "Gaming (Steam, Lutris)",
+    }
+}
+
+// Packages required by a system service
+pub fn system_service_packages(service: SystemService) -> &'static [&'static str] {
+    match service {
+        SystemService::Printing => &["cups", "cups-pdf", "system-config-printer", "avahi"],
+        SystemService::Bluetooth => &["bluez", "bluez-utils"],
+        SystemService::Flatpak => &["flatpak"],
+        SystemService::Mdns => &["avahi", "nss-mdns"],
+        SystemService::Tlp => &["tlp"],
+        SystemService::PowerProfiles => &["power-profiles-daemon"],
+        SystemService::Fingerprint => &["fprintd"],
+        // Gaming packages come from the multilib repo, see gaming_packages
+        SystemService::Multilib | SystemService::Gaming => &[],
+    }
+}
+
+// Packages in the gaming bundle, before the GPU-specific 32-bit drivers
+const GAMING_PACKAGES: [&str; 6] = [
+    "steam",
+    "lutris",
+    "gamemode",
+    "lib32-gamemode",
+    "mangohud",
+    "lib32-mangohud",
+];
+
+// Gaming bundle plus the 32-bit Vulkan drivers for the detected GPUs
+pub fn gaming_packages(
+    vendors: &HashSet<GpuVendor>,
+    nvidia_variant: Option<NvidiaVariant>,
+) -> Vec<String> {
+    let mut packages: Vec<String> = GAMING_PACKAGES.iter().map(|pkg| pkg.to_string()).collect();
+    extend_unique_owned(
+        &mut packages,
+        lib32_driver_packages(vendors, nvidia_variant),
+    );
+    packages
+}
+
+// Checks for a Bluetooth controller on the live system
+fn has_bluetooth_controller() -> bool {
+    std::fs::read_dir("/sys/class/bluetooth")
+        .map(|entries| entries.flatten().next().is_some())
+        .unwrap_or(false)
+}
+
+// USB vendor ids of common fingerprint sensors supported by libfprint
+const FINGERPRINT_VENDOR_IDS: [&str; 7] = [
+    "06cb", // Synaptics
+    "27c6", // Goodix
+    "138a", // Validity
+    "147e", // Upek
+    "08ff", // AuthenTec
+    "1c7a", // EgisTec
+    "10a5", // FPC
+];
+
+// Whether a service can be enabled on this machine
+pub fn system_service_available(service: SystemService) -> bool {
+    match service {
+        SystemService::Fingerprint => has_fingerprint_reader(),
+        _ => true,
+    }
+}
+
+// Looks for a fingerprint sensor among the connected USB devices (cached, the UI asks every frame)
+fn has_fingerprint_reader() -> bool {
+    static DETECTED: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+    *DETECTED.get_or_init(|| {
+        std::fs::read_dir("/sys/bus/usb/devices")
+            .map(|entries| {
+                entries.flatten().any(|entry| {
+                    std::fs::read_to_string(entry.path().join("idVendor"))
+                        .map(|vendor| FINGERPRINT_VENDOR_IDS.contains(&vendor.trim()))
+                        .unwrap_or(false)
+                })
+            })
+            .unwrap_or(false)
+    })
+}
+
+// Battery-powered machines expose a BAT* power supply
+pub fn is_laptop() -> bool {
+    std::fs::read_dir("/sys/class/power_supply")
+        .map(|entries| {
+            entries
+                .flatten()
+                .any(|entry| entry.file_name().to_string_lossy().starts_with("BAT"))
+        })
+        .unwrap_or(false)
 }
 
 // Default implementation for AppSelectionFlags
@@ -108,6 +390,7 @@ pub fn selection_from_app_flags(flags: &AppSelectionFlags) -> PackageSelection {
         &mut selection,
         selection_from_flags_for(&flags.terminals, terminal_choices()),
     );
+    extend_unique(&mut selection.pacman, &flags.extra_packages);
     selection
 }
 
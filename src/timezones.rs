@@ -3,6 +3,7 @@
 ////////
 use anyhow::Result;
 use std::fs;
+use std::path::Path;
 use std::process::Command;
 use std::thread::sleep;
 use std::time::Duration;
@@ -56,6 +57,85 @@ pub fn find_timezone_index(zones: &[String], value: &str) -> Option<usize> {
     zones.iter().position(|zone| zone == value)
 }
 
+// Checked before install so a stale selection fails before any disk is touched
+pub fn timezone_error(zones: &[String], value: &str) -> Option<&'static str> {
+    if value.is_empty() {
+        return Some("No timezone selected");
+    }
+    if value
+        .split('/')
+        .any(|part| part.is_empty() || part == "." || part == "..")
+    {
+        return Some("Invalid timezone name");
+    }
+    if find_timezone_index(zones, value).is_none() {
+        return Some("Timezone is not in the timezone list");
+    }
+    if !Path::new("/usr/share/zoneinfo").join(value).is_file() {
+        return Some("Timezone data is missing from /usr/share/zoneinfo");
+    }
+    None
+}
+
+// ISO 3166 alpha-2 codes from tzdata, the known list a Wi-Fi regulatory domain must come from
+pub fn load_country_codes() -> Vec<String> {
+    let content = fs::read_to_string("/usr/share/zoneinfo/iso3166.tab").unwrap_or_default();
+    content
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.split('\t').next())
+        .map(|code| code.trim().to_string())
+        .filter(|code| code.len() == 2)
+        .collect()
+}
+
+// Validates a Wi-Fi country code, e.g. `DE`; `00` is the world domain
+pub fn wifi_country_error(codes: &[String], value: &str) -> Option<&'static str> {
+    if value.len() != 2 || !value.chars().all(|c| c.is_ascii_uppercase() || c == '0') {
+        return Some("Use a two-letter uppercase country code, e.g. DE");
+    }
+    if value != "00" && !codes.iter().any(|code| code == value) {
+        return Some("Unknown country code");
+    }
+    None
+}
+
+// Country of a zone from zone.tab, e.g. `DE` for `Europe/Berlin`; None for UTC and friends
+pub fn timezone_country(zone: &str) -> Option<String> {
+    let content = fs::read_to_string("/usr/share/zoneinfo/zone.tab").ok()?;
+    content
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .find_map(|line| {
+            let mut parts = line.split('\t');
+            let code = parts.next()?;
+            let _coords = parts.next();
+            (parts.next()? == zone).then(|| code.to_string())
+        })
+}
+
+// Top-level regions of the sorted zone list (e.g. `America`), plus region-less zones like `UTC`
+pub fn timezone_regions(zones: &[String]) -> Vec<String> {
+    let mut regions: Vec<String> = zones
+        .iter()
+        .map(|zone| zone.split('/').next().unwrap_or(zone).to_string())
+        .collect();
+    regions.dedup();
+    regions
+}
+
+// Zones inside a region as (index into `zones`, name without the region), e.g. `Argentina/Salta`
+pub fn timezone_cities(zones: &[String], region: &str) -> Vec<(usize, String)> {
+    zones
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, zone)| {
+            let (zone_region, city) = zone.split_once('/')?;
+            (zone_region == region).then(|| (idx, city.to_string()))
+        })
+        .collect()
+}
+
 // Debug messages to a log file
 fn log_debug(message: &str) {
     let _ = fs::OpenOptions::new()
@@ -154,6 +234,75 @@ pub fn detect_timezone_geoip(zones: &[String]) -> Option<String> {
     None // All GeoIP attempts failed
 }
 
+// Console keymaps mapped to the timezone most of their users are in.
+// Ambiguous layouts such as `us` are deliberately left out.
+const KEYMAP_TIMEZONES: [(&str, &str); 33] = [
+    ("be", "Europe/Brussels"),
+    ("bg", "Europe/Sofia"),
+    ("br", "America/Sao_Paulo"),
+    ("cf", "America/Toronto"),
+    ("cz", "Europe/Prague"),
+    ("de", "Europe/Berlin"),
+    ("de_ch", "Europe/Zurich"),
+    ("dk", "Europe/Copenhagen"),
+    ("es", "Europe/Madrid"),
+    ("et", "Europe/Tallinn"),
+    ("fi", "Europe/Helsinki"),
+    ("fr", "Europe/Paris"),
+    ("fr_ch", "Europe/Zurich"),
+    ("gr", "Europe/Athens"),
+    ("hu", "Europe/Budapest"),
+    ("il", "Asia/Jerusalem"),
+    ("is", "Atlantic/Reykjavik"),
+    ("it", "Europe/Rome"),
+    ("jp106", "Asia/Tokyo"),
+    ("lt", "Europe/Vilnius"),
+    ("lv", "Europe/Riga"),
+    ("nl", "Europe/Amsterdam"),
+    ("no", "Europe/Oslo"),
+    ("pl", "Europe/Warsaw"),
+    ("pt", "Europe/Lisbon"),
+    ("ro", "Europe/Bucharest"),
+    ("ru", "Europe/Moscow"),
+    ("se", "Europe/Stockholm"),
+    ("sg", "Europe/Zurich"),
+    ("sk", "Europe/Bratislava"),
+    ("trq", "Europe/Istanbul"),
+    ("ua", "Europe/Kyiv"),
+    ("uk", "Europe/London"),
+];
+
+// Suggests a timezone from the console keymap when GeoIP is unavailable
+pub fn detect_timezone_from_keymap(keymap: &str, zones: &[String]) -> Option<String> {
+    let keymap = keymap.trim().to_ascii_lowercase();
+    // `de_CH-latin1` matches `de_ch` before falling back to `de`
+    let variant = keymap.split('-').next().unwrap_or_default();
+    let base = variant.split('_').next().unwrap_or_default();
+    let tz = [keymap.as_str(), variant, base]
+        .into_iter()
+        .find_map(|name| {
+            KEYMAP_TIMEZONES
+                .iter()
+                .find(|(candidate, _)| *candidate == name)
+                .map(|(_, tz)| *tz)
+        })?;
+
+    // Older zone lists still use the Kiev spelling
+    let legacy = if tz == "Europe/Kyiv" {
+        "Europe/Kiev"
+    } else {
+        tz
+    };
+    let value = [tz, legacy]
+        .into_iter()
+        .find(|candidate| zones.iter().any(|zone| zone == candidate))?;
+    log_debug(&format!(
+        "detect_timezone: keymap {} suggests {}",
+        keymap, value
+    ));
+    Some(value.to_string())
+}
+
 // Detect the local timezone from system files like `/etc/timezone` or `/etc/localtime`
 // === We should remove this in future === //
 pub fn detect_timezone_local(zones: &[String]) -> Option<String> {
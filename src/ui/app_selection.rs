@@ -13,12 +13,16 @@ use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Padding, Paragraph, Wrap};
 use ratatui::{Frame, Terminal};
 
+use crate::packages::search_packages;
 use crate::selection::{
-    browser_choices, compositor_choices, editor_choices, terminal_choices, AppSelectionFlags,
+    browser_choices, compositor_choices, editor_choices, system_service_available,
+    system_service_label, terminal_choices, AppSelectionFlags, InstallChoice, SYSTEM_SERVICES,
 };
 use crate::ui::colors::PURE_WHITE;
 
 use super::common::{aligned_summary_area, draw_install_summary, split_main_and_summary};
+use super::multiselect::{draw_select_group, toggle_checked, SelectGroup};
+use super::theme::{draw_themed, theme};
 use super::{InstallSummary, SelectionAction, NEBULA_ART};
 
 // Currently focused application columns
@@ -28,6 +32,30 @@ enum AppSelectionFocus {
     Browsers,
     Editors,
     Terminals,
+    Services,
+    Search,
+}
+
+impl AppSelectionFocus {
+    // Index of the focused column in `app_groups`
+    fn group(self) -> Option<usize> {
+        match self {
+            AppSelectionFocus::Compositors => Some(0),
+            AppSelectionFocus::Browsers => Some(1),
+            AppSelectionFocus::Editors => Some(2),
+            AppSelectionFocus::Terminals => Some(3),
+            AppSelectionFocus::Services => Some(4),
+            AppSelectionFocus::Search => None,
+        }
+    }
+}
+
+// State of the package search panel
+#[derive(Default)]
+struct PackageSearch {
+    query: String,
+    results: Vec<String>,
+    cursor: usize,
 }
 
 fn normalize_flags(flags: &mut Vec<bool>, len: usize) {
@@ -37,15 +65,53 @@ fn normalize_flags(flags: &mut Vec<bool>, len: usize) {
     }
 }
 
+fn choice_labels(choices: &[InstallChoice]) -> Vec<String> {
+    choices.iter().map(|choice| choice.label.clone()).collect()
+}
+
+// The checkbox columns, in `AppSelectionFocus::group` order
+fn app_groups(flags: &AppSelectionFlags) -> [SelectGroup; 5] {
+    let service_labels = SYSTEM_SERVICES
+        .iter()
+        .map(|service| system_service_label(*service).to_string())
+        .collect();
+    let unavailable = SYSTEM_SERVICES
+        .iter()
+        .map(|service| !system_service_available(*service))
+        .collect();
+    [
+        SelectGroup::new(
+            "Wayland compositor",
+            choice_labels(compositor_choices()),
+            &flags.compositors,
+        )
+        .single(),
+        SelectGroup::new(
+            "Web Browser",
+            choice_labels(browser_choices()),
+            &flags.browsers,
+        ),
+        SelectGroup::new(
+            "Code Editor",
+            choice_labels(editor_choices()),
+            &flags.editors,
+        ),
+        SelectGroup::new(
+            "Terminal",
+            choice_labels(terminal_choices()),
+            &flags.terminals,
+        ),
+        SelectGroup::new("System", service_labels, &flags.services).unavailable(unavailable),
+    ]
+}
+
 // Application selector UI
 fn draw_application_selector(
     area: Rect,
     f: &mut Frame<'_>,
     focus: AppSelectionFocus,
-    compositor_cursor: usize,
-    browser_cursor: usize,
-    editor_cursor: usize,
-    terminal_cursor: usize,
+    cursors: &[usize; 5],
+    search: &PackageSearch,
     flags: &AppSelectionFlags,
     summary: &InstallSummary,
 ) {
@@ -91,9 +157,9 @@ fn draw_application_selector(
     // Controls box
     let help = Paragraph::new(vec![
         Line::from(vec![
-            Span::styled("󰁞/󰁆", Style::default().fg(Color::Cyan)),
+            Span::styled(theme().up_down, Style::default().fg(Color::Cyan)),
             Span::raw(" move, "),
-            Span::styled("󰁎/󰁕", Style::default().fg(Color::Cyan)),
+            Span::styled(theme().left_right, Style::default().fg(Color::Cyan)),
             Span::raw(" switch column, "),
             Span::styled("Space", Style::default().fg(Color::Cyan)),
             Span::raw(" toggle."),
@@ -153,7 +219,7 @@ fn draw_application_selector(
         width: widths[1],
         height: columns_area.height,
     };
-    let terminal_area = Rect {
+    let right_area = Rect {
         x: columns_area.x + widths[0] + gap + widths[1] + gap,
         y: columns_area.y,
         width: widths[2],
@@ -168,199 +234,95 @@ fn draw_application_selector(
     let compositor_area = left_layout[0];
     let browser_area = left_layout[1];
 
-    // --- Render Compositor List ---
-    let compositor_items: Vec<ListItem> = compositor_choices()
-        .iter()
-        .enumerate()
-        .map(|(idx, choice)| {
-            let is_selected = flags.compositors.get(idx).copied().unwrap_or(false);
-            if is_selected {
-                ListItem::new(Line::from(vec![
-                    Span::styled("[󰸞]", Style::default().fg(Color::LightGreen)), // Checkbox checked
-                    Span::raw(" "),
-                    Span::styled(choice.label.as_str(), Style::default().fg(Color::Blue)),
-                ]))
-            } else {
-                ListItem::new(Line::from(format!("[ ] {}", choice.label))) // Checkbox unchecked
-            }
-        })
-        .collect();
-    let compositor_active = focus == AppSelectionFocus::Compositors;
-    let compositor_title_style = if compositor_active {
-        Style::default()
-            .fg(Color::Yellow)
-            .add_modifier(Modifier::BOLD)
-    } else {
-        Style::default().fg(PURE_WHITE).add_modifier(Modifier::BOLD)
-    };
-    let compositor_list = List::new(compositor_items)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Black))
-                .padding(Padding::new(1, 0, 1, 0))
-                .title(Line::from(vec![
-                    Span::styled("[", Style::default().fg(Color::Black)),
-                    Span::styled(" Wayland compositor ", compositor_title_style),
-                    Span::styled("]", Style::default().fg(Color::Black)),
-                ])),
-        )
-        .highlight_style(
-            Style::default()
-                .fg(Color::Yellow)
-                .add_modifier(Modifier::BOLD),
-        );
-    let mut compositor_state = ListState::default();
-    let compositor_len = compositor_choices().len();
-    if compositor_active && compositor_len > 0 {
-        compositor_state.select(Some(compositor_cursor.min(compositor_len - 1)));
-    }
-    f.render_stateful_widget(compositor_list, compositor_area, &mut compositor_state);
+    let editor_height = (editor_choices().len() as u16) + 4;
+    let middle_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(editor_height), Constraint::Min(5)])
+        .split(editor_area);
+    let editor_area = middle_layout[0];
+    let search_area = middle_layout[1];
 
-    // --- Render Browser List ---
-    let browser_items: Vec<ListItem> = browser_choices()
-        .iter()
-        .enumerate()
-        .map(|(idx, choice)| {
-            let is_selected = flags.browsers.get(idx).copied().unwrap_or(false);
-            if is_selected {
-                ListItem::new(Line::from(vec![
-                    Span::styled("[󰸞]", Style::default().fg(Color::LightGreen)),
-                    Span::raw(" "),
-                    Span::styled(choice.label.as_str(), Style::default().fg(Color::Blue)),
-                ]))
-            } else {
-                ListItem::new(Line::from(format!("[ ] {}", choice.label)))
-            }
-        })
-        .collect();
-    let browser_active = focus == AppSelectionFocus::Browsers;
-    let browser_title_style = if browser_active {
-        Style::default()
-            .fg(Color::Yellow)
-            .add_modifier(Modifier::BOLD)
-    } else {
-        Style::default().fg(PURE_WHITE).add_modifier(Modifier::BOLD)
-    };
-    let browser_list = List::new(browser_items)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Black))
-                .padding(Padding::new(1, 0, 1, 0))
-                .title(Line::from(vec![
-                    Span::styled("[", Style::default().fg(Color::Black)),
-                    Span::styled(" Web Browser ", browser_title_style),
-                    Span::styled("]", Style::default().fg(Color::Black)),
-                ])),
-        )
-        .highlight_style(
-            Style::default()
-                .fg(Color::Yellow)
-                .add_modifier(Modifier::BOLD),
-        );
-    let mut browser_state = ListState::default();
-    if browser_active && !browser_choices().is_empty() {
-        browser_state.select(Some(browser_cursor.min(browser_choices().len() - 1)));
+    let terminal_height = (terminal_choices().len() as u16) + 4;
+    let right_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(terminal_height), Constraint::Min(4)])
+        .split(right_area);
+    let terminal_area = right_layout[0];
+    let service_area = right_layout[1];
+
+    // --- Render the checkbox columns ---
+    let areas = [
+        compositor_area,
+        browser_area,
+        editor_area,
+        terminal_area,
+        service_area,
+    ];
+    for (idx, group) in app_groups(flags).iter().enumerate() {
+        let cursor = (focus.group() == Some(idx)).then(|| cursors[idx]);
+        draw_select_group(f, areas[idx], group, cursor);
     }
-    f.render_stateful_widget(browser_list, browser_area, &mut browser_state);
 
-    // --- Render Editor List ---
-    let editor_items: Vec<ListItem> = editor_choices()
-        .iter()
-        .enumerate()
-        .map(|(idx, choice)| {
-            let is_selected = flags.editors.get(idx).copied().unwrap_or(false);
-            if is_selected {
-                ListItem::new(Line::from(vec![
-                    Span::styled("[󰸞]", Style::default().fg(Color::LightGreen)),
-                    Span::raw(" "),
-                    Span::styled(choice.label.as_str(), Style::default().fg(Color::Blue)),
-                ]))
-            } else {
-                ListItem::new(Line::from(format!("[ ] {}", choice.label)))
-            }
-        })
-        .collect();
-    let editor_active = focus == AppSelectionFocus::Editors;
-    let editor_title_style = if editor_active {
+    // --- Render Package Search ---
+    let search_active = focus == AppSelectionFocus::Search;
+    let search_title_style = if search_active {
         Style::default()
             .fg(Color::Yellow)
             .add_modifier(Modifier::BOLD)
     } else {
         Style::default().fg(PURE_WHITE).add_modifier(Modifier::BOLD)
     };
-    let editor_list = List::new(editor_items)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Black))
-                .padding(Padding::new(1, 0, 1, 0))
-                .title(Line::from(vec![
-                    Span::styled("[", Style::default().fg(Color::Black)),
-                    Span::styled(" Code Editor ", editor_title_style),
-                    Span::styled("]", Style::default().fg(Color::Black)),
-                ])),
-        )
-        .highlight_style(
-            Style::default()
-                .fg(Color::Yellow)
-                .add_modifier(Modifier::BOLD),
-        );
-    let mut editor_state = ListState::default();
-    if editor_active && !editor_choices().is_empty() {
-        editor_state.select(Some(editor_cursor.min(editor_choices().len() - 1)));
+    let search_block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Black))
+        .padding(Padding::new(1, 0, 0, 0))
+        .title(Line::from(vec![
+            Span::styled("[", Style::default().fg(Color::Black)),
+            Span::styled(" Search packages ", search_title_style),
+            Span::styled("]", Style::default().fg(Color::Black)),
+        ]));
+    let search_inner = search_block.inner(search_area);
+    f.render_widget(search_block, search_area);
+    let search_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(1)])
+        .split(search_inner);
+    let mut query_spans = vec![
+        Span::styled("> ", Style::default().fg(Color::Cyan)),
+        Span::raw(search.query.as_str()),
+    ];
+    if search_active {
+        query_spans.push(Span::styled("_", Style::default().fg(Color::Cyan)));
     }
-    f.render_stateful_widget(editor_list, editor_area, &mut editor_state);
-
-    // --- Render Terminal List ---
-    let terminal_items: Vec<ListItem> = terminal_choices()
+    f.render_widget(Paragraph::new(Line::from(query_spans)), search_layout[0]);
+    let result_items: Vec<ListItem> = search
+        .results
         .iter()
-        .enumerate()
-        .map(|(idx, choice)| {
-            let is_selected = flags.terminals.get(idx).copied().unwrap_or(false);
-            if is_selected {
+        .map(|pkg| {
+            if flags.extra_packages.contains(pkg) {
                 ListItem::new(Line::from(vec![
-                    Span::styled("[󰸞]", Style::default().fg(Color::LightGreen)),
+                    Span::styled(
+                        format!("[{}]", theme().checked),
+                        Style::default().fg(Color::LightGreen),
+                    ),
                     Span::raw(" "),
-                    Span::styled(choice.label.as_str(), Style::default().fg(Color::Blue)),
+                    Span::styled(pkg.as_str(), Style::default().fg(Color::Blue)),
                 ]))
             } else {
-                ListItem::new(Line::from(format!("[ ] {}", choice.label)))
+                ListItem::new(Line::from(format!("[ ] {}", pkg)))
             }
         })
         .collect();
-    let terminal_active = focus == AppSelectionFocus::Terminals;
-    let terminal_title_style = if terminal_active {
+    let result_list = List::new(result_items).highlight_style(
         Style::default()
             .fg(Color::Yellow)
-            .add_modifier(Modifier::BOLD)
-    } else {
-        Style::default().fg(PURE_WHITE).add_modifier(Modifier::BOLD)
-    };
-
-    let terminal_list = List::new(terminal_items)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Black))
-                .padding(Padding::new(1, 0, 1, 0))
-                .title(Line::from(vec![
-                    Span::styled("[", Style::default().fg(Color::Black)),
-                    Span::styled(" Terminal ", terminal_title_style),
-                    Span::styled("]", Style::default().fg(Color::Black)),
-                ])),
-        )
-        .highlight_style(
-            Style::default()
-                .fg(Color::Yellow)
-                .add_modifier(Modifier::BOLD),
-        );
-    let mut terminal_state = ListState::default();
-    if terminal_active && !terminal_choices().is_empty() {
-        terminal_state.select(Some(terminal_cursor.min(terminal_choices().len() - 1)));
+            .add_modifier(Modifier::BOLD),
+    );
+    let mut result_state = ListState::default();
+    if search_active && !search.results.is_empty() {
+        result_state.select(Some(search.cursor.min(search.results.len() - 1)));
     }
-    f.render_stateful_widget(terminal_list, terminal_area, &mut terminal_state);
+    f.render_stateful_widget(result_list, search_layout[1], &mut result_state);
 
     // --- Render Confirmation Box ---
     let total_selected = flags
@@ -370,7 +332,8 @@ fn draw_application_selector(
         .chain(flags.editors.iter())
         .chain(flags.terminals.iter())
         .filter(|flag| **flag)
-        .count();
+        .count()
+        + flags.extra_packages.len();
     let confirm_title_style = Style::default()
         .fg(Color::LightGreen)
         .add_modifier(Modifier::BOLD);
@@ -395,8 +358,13 @@ fn draw_application_selector(
     );
     f.render_widget(confirm_block, main_layout[1]);
 
+    let footer_text = if search_active {
+        "Type a name, Enter to search, Space to add or remove, Esc to leave search"
+    } else {
+        "Selections apply to this run only"
+    };
     let footer = Paragraph::new(Line::from(Span::styled(
-        "Selections apply to this run only",
+        footer_text,
         Style::default().fg(Color::White),
     )));
     f.render_widget(footer, layout[5]);
@@ -419,28 +387,20 @@ pub fn run_application_selector(
     normalize_flags(&mut flags.browsers, browser_choices().len());
     normalize_flags(&mut flags.editors, editor_choices().len());
     normalize_flags(&mut flags.terminals, terminal_choices().len());
+    normalize_flags(&mut flags.services, SYSTEM_SERVICES.len());
 
     // State for the focused column and the cursor position in each column
     let mut focus = AppSelectionFocus::Browsers;
-    let mut compositor_cursor = flags.compositors.iter().position(|flag| *flag).unwrap_or(0);
-    let mut browser_cursor = flags.browsers.iter().position(|flag| *flag).unwrap_or(0);
-    let mut editor_cursor = flags.editors.iter().position(|flag| *flag).unwrap_or(0);
-    let mut terminal_cursor = flags.terminals.iter().position(|flag| *flag).unwrap_or(0);
+    let groups = app_groups(&flags);
+    let lens = groups.each_ref().map(|group| group.labels.len());
+    let mut cursors = groups.each_ref().map(SelectGroup::initial_cursor);
+    cursors[4] = 0; // Services start at the top
+    let mut search = PackageSearch::default();
 
     // Main loop for the application selection screen
     loop {
-        terminal.draw(|f| {
-            draw_application_selector(
-                f.size(),
-                f,
-                focus,
-                compositor_cursor,
-                browser_cursor,
-                editor_cursor,
-                terminal_cursor,
-                &flags,
-                summary,
-            )
+        draw_themed(terminal, |f| {
+            draw_application_selector(f.size(), f, focus, &cursors, &search, &flags, summary)
         })?;
 
         let timeout = Duration::from_millis(100);
@@ -449,6 +409,50 @@ pub fn run_application_selector(
                 if key.kind != KeyEventKind::Press {
                     continue;
                 }
+                // The search panel captures text input while focused
+                if focus == AppSelectionFocus::Search {
+                    match key.code {
+                        KeyCode::Char('q') | KeyCode::Char('Q')
+                            if key.modifiers.contains(KeyModifiers::CONTROL) =>
+                        {
+                            return Ok(SelectionAction::Quit);
+                        }
+                        KeyCode::Char(' ') => {
+                            if let Some(pkg) = search.results.get(search.cursor) {
+                                if let Some(pos) =
+                                    flags.extra_packages.iter().position(|extra| extra == pkg)
+                                {
+                                    flags.extra_packages.remove(pos);
+                                } else {
+                                    flags.extra_packages.push(pkg.clone());
+                                }
+                            }
+                        }
+                        KeyCode::Char(c) => search.query.push(c),
+                        KeyCode::Backspace => {
+                            search.query.pop();
+                        }
+                        KeyCode::Enter => {
+                            search.results = search_packages(&search.query);
+                            search.cursor = 0;
+                        }
+                        KeyCode::Up => {
+                            if search.cursor > 0 {
+                                search.cursor -= 1;
+                            } else if lens[2] > 0 {
+                                focus = AppSelectionFocus::Editors;
+                            }
+                        }
+                        KeyCode::Down if search.cursor + 1 < search.results.len() => {
+                            search.cursor += 1;
+                        }
+                        KeyCode::Left => focus = AppSelectionFocus::Browsers,
+                        KeyCode::Right => focus = AppSelectionFocus::Terminals,
+                        KeyCode::Esc => focus = AppSelectionFocus::Editors,
+                        _ => {}
+                    }
+                    continue;
+                }
                 match key.code {
                     // --- Focus and Navigation ---
                     KeyCode::Left => {
@@ -457,6 +461,8 @@ pub fn run_application_selector(
                             AppSelectionFocus::Browsers => AppSelectionFocus::Browsers,
                             AppSelectionFocus::Editors => AppSelectionFocus::Browsers,
                             AppSelectionFocus::Terminals => AppSelectionFocus::Editors,
+                            AppSelectionFocus::Services => AppSelectionFocus::Editors,
+                            AppSelectionFocus::Search => AppSelectionFocus::Browsers,
                         };
                     }
                     KeyCode::Right => {
@@ -465,79 +471,62 @@ pub fn run_application_selector(
                             AppSelectionFocus::Browsers => AppSelectionFocus::Editors,
                             AppSelectionFocus::Editors => AppSelectionFocus::Terminals,
                             AppSelectionFocus::Terminals => AppSelectionFocus::Terminals,
+                            AppSelectionFocus::Services => AppSelectionFocus::Services,
+                            AppSelectionFocus::Search => AppSelectionFocus::Terminals,
                         };
                     }
-                    KeyCode::Up => match focus {
-                        AppSelectionFocus::Compositors => {
-                            if compositor_cursor > 0 {
-                                compositor_cursor -= 1;
-                            }
-                        }
-                        AppSelectionFocus::Browsers => {
-                            if browser_cursor > 0 {
-                                browser_cursor -= 1;
-                            } else if !compositor_choices().is_empty() {
-                                focus = AppSelectionFocus::Compositors;
-                            }
-                        }
-                        AppSelectionFocus::Editors => {
-                            if editor_cursor > 0 {
-                                editor_cursor -= 1;
-                            }
-                        }
-                        AppSelectionFocus::Terminals => {
-                            if terminal_cursor > 0 {
-                                terminal_cursor -= 1;
-                            }
-                        }
-                    },
-                    KeyCode::Down => match focus {
-                        AppSelectionFocus::Compositors => {
-                            if compositor_cursor + 1 < compositor_choices().len() {
-                                compositor_cursor += 1;
-                            } else if !browser_choices().is_empty() {
-                                focus = AppSelectionFocus::Browsers;
+                    KeyCode::Up => {
+                        if let Some(group) = focus.group() {
+                            if cursors[group] > 0 {
+                                cursors[group] -= 1;
+                            } else {
+                                focus = match focus {
+                                    AppSelectionFocus::Browsers if lens[0] > 0 => {
+                                        AppSelectionFocus::Compositors
+                                    }
+                                    AppSelectionFocus::Services if lens[3] > 0 => {
+                                        AppSelectionFocus::Terminals
+                                    }
+                                    _ => focus,
+                                };
                             }
                         }
-                        AppSelectionFocus::Browsers => {
-                            if browser_cursor + 1 < browser_choices().len() {
-                                browser_cursor += 1;
-                            }
-                        }
-                        AppSelectionFocus::Editors => {
-                            if editor_cursor + 1 < editor_choices().len() {
-                                editor_cursor += 1;
-                            }
-                        }
-                        AppSelectionFocus::Terminals => {
-                            if terminal_cursor + 1 < terminal_choices().len() {
-                                terminal_cursor += 1;
+                    }
+                    KeyCode::Down => {
+                        if let Some(group) = focus.group() {
+                            if cursors[group] + 1 < lens[group] {
+                                cursors[group] += 1;
+                            } else {
+                                focus = match focus {
+                                    AppSelectionFocus::Compositors if lens[1] > 0 => {
+                                        AppSelectionFocus::Browsers
+                                    }
+                                    AppSelectionFocus::Editors => AppSelectionFocus::Search,
+                                    AppSelectionFocus::Terminals if lens[4] > 0 => {
+                                        AppSelectionFocus::Services
+                                    }
+                                    _ => focus,
+                                };
                             }
                         }
-                    },
+                    }
                     // --- Selection and Actions ---
                     KeyCode::Char(' ') => match focus {
                         AppSelectionFocus::Compositors => {
-                            if compositor_cursor < flags.compositors.len() {
-                                flags.compositors.iter_mut().for_each(|flag| *flag = false);
-                                flags.compositors[compositor_cursor] = true;
-                            }
+                            toggle_checked(&mut flags.compositors, cursors[0], true)
                         }
                         AppSelectionFocus::Browsers => {
-                            if let Some(flag) = flags.browsers.get_mut(browser_cursor) {
-                                *flag = !*flag;
-                            }
+                            toggle_checked(&mut flags.browsers, cursors[1], false)
                         }
                         AppSelectionFocus::Editors => {
-                            if let Some(flag) = flags.editors.get_mut(editor_cursor) {
-                                *flag = !*flag;
-                            }
+                            toggle_checked(&mut flags.editors, cursors[2], false)
                         }
                         AppSelectionFocus::Terminals => {
-                            if let Some(flag) = flags.terminals.get_mut(terminal_cursor) {
-                                *flag = !*flag;
-                            }
+                            toggle_checked(&mut flags.terminals, cursors[3], false)
                         }
+                        // Services have conflicts and requirements of their own
+                        AppSelectionFocus::Services => flags.toggle_service(cursors[4]),
+                        AppSelectionFocus::Search => {}
                     },
                     KeyCode::Enter => {
                         flags.enforce_defaults();
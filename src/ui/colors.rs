@@ -3,3 +3,21 @@ use ratatui::style::Color;
 pub const PURE_WHITE: Color = Color::Rgb(255, 255, 255);
 //pub const LIGHT_BLACK: Color = Color::Rgb(206, 184, 255);
 //pub const LIGHT_BLACK: Color = Color::Rgb(169, 157, 207);
+
+// High-contrast palette: the black frame chrome and grey hints become readable on any background
+pub const HIGH_CONTRAST_BORDER: Color = Color::White;
+pub const HIGH_CONTRAST_MUTED: Color = Color::Gray;
+
+// Maps a foreground color onto the high-contrast palette
+pub fn high_contrast(color: Color) -> Color {
+    match color {
+        Color::Black => HIGH_CONTRAST_BORDER,
+        Color::DarkGray => HIGH_CONTRAST_MUTED,
+        Color::Blue => Color::LightCyan,
+        Color::Red => Color::LightRed,
+        Color::Green => Color::LightGreen,
+        Color::Magenta => Color::LightMagenta,
+        Color::Rgb(..) => Color::White,
+        other => other,
+    }
+}
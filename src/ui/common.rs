@@ -7,6 +7,7 @@ use ratatui::Frame;
 use crate::ui::colors::PURE_WHITE;
 
 use super::keybinds::{draw_keybinds, keybinds_height};
+use super::theme::theme;
 use super::InstallSummary;
 
 #[derive(Clone, Copy, Debug)]
@@ -47,6 +48,7 @@ fn summary_lines(summary: &InstallSummary) -> Vec<Line<'_>> {
     let mut lines = Vec::with_capacity(entries.len());
 
     for (idx, (label, icon, value)) in entries.iter().enumerate() {
+        let icon = theme().glyph(icon, theme().bullet);
         // Determine the status of the current entry
         let status = if summary.current_index >= entries.len() {
             SummaryStatus::Done
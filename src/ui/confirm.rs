@@ -13,31 +13,54 @@ use ratatui::{Frame, Terminal};
 use crate::ui::colors::PURE_WHITE;
 
 use super::common::{aligned_summary_area, draw_install_summary, split_main_and_summary};
-use super::{ConfirmAction, InstallSummary, NEBULA_ART};
+use super::theme::{draw_themed, theme};
+use super::{ConfirmAction, ConfirmMode, InstallSummary, NEBULA_ART};
 
-// Waiting for the user to select "Yes" or "No".
+// Waiting for the user to select "Yes" or "No", or to type the confirmation phrase.
+// An optional checkbox below the info lines is flipped with Tab.
 pub fn run_confirm_selector(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     title: &str,
     warning_lines: &[Line<'_>],
     info_lines: &[Line<'_>],
+    mode: ConfirmMode<'_>,
+    mut toggle: Option<(&str, &mut bool)>,
     summary: &InstallSummary,
 ) -> Result<ConfirmAction> {
     let options = ["Yes", "No"];
     let mut cursor = 0usize;
+    let mut typed = String::new();
 
     // Main loop for the confirmation screen
     loop {
+        let mut lines = info_lines.to_vec();
+        if let Some((label, checked)) = &toggle {
+            lines.push(Line::from(vec![
+                if **checked {
+                    Span::styled(
+                        format!("[{}] ", theme().checked),
+                        Style::default().fg(Color::LightGreen),
+                    )
+                } else {
+                    Span::raw("[ ] ")
+                },
+                Span::raw(*label),
+                Span::styled(" (Tab)", Style::default().fg(Color::Cyan)),
+            ]));
+        }
+
         // Draw the UI.
-        terminal.draw(|f| {
+        draw_themed(terminal, |f| {
             draw_confirm_selector(
                 f.size(),
                 f,
                 title,
                 warning_lines,
-                info_lines,
+                &lines,
                 cursor,
                 &options,
+                mode,
+                &typed,
                 summary,
             )
         })?;
@@ -49,6 +72,32 @@ pub fn run_confirm_selector(
                 if key.kind != KeyEventKind::Press {
                     continue;
                 }
+                if let (KeyCode::Tab, Some((_, checked))) = (key.code, toggle.as_mut()) {
+                    **checked = !**checked;
+                    continue;
+                }
+                if let ConfirmMode::TypedPhrase(phrase) = mode {
+                    match key.code {
+                        KeyCode::Char('q') | KeyCode::Char('Q')
+                            if key.modifiers.contains(KeyModifiers::CONTROL) =>
+                        {
+                            return Ok(ConfirmAction::Quit)
+                        }
+                        KeyCode::Char('u') | KeyCode::Char('U')
+                            if key.modifiers.contains(KeyModifiers::CONTROL) =>
+                        {
+                            typed.clear();
+                        }
+                        KeyCode::Char(c) => typed.push(c),
+                        KeyCode::Backspace => {
+                            typed.pop();
+                        }
+                        KeyCode::Enter if typed == phrase => return Ok(ConfirmAction::Yes),
+                        KeyCode::Esc => return Ok(ConfirmAction::Back),
+                        _ => {}
+                    }
+                    continue;
+                }
                 match key.code {
                     KeyCode::Up => {
                         if cursor > 0 {
@@ -91,6 +140,8 @@ fn draw_confirm_selector(
     info_lines: &[Line<'_>],
     cursor: usize,
     options: &[&str],
+    mode: ConfirmMode<'_>,
+    typed: &str,
     summary: &InstallSummary,
 ) {
     let (main_area, summary_area) = split_main_and_summary(area);
@@ -134,35 +185,51 @@ fn draw_confirm_selector(
     f.render_widget(title_block, layout[1]);
 
     // Controls box
-    let help = Paragraph::new(vec![
-        Line::from(vec![
-            Span::styled("󰁞/󰁆", Style::default().fg(Color::Cyan)),
-            Span::raw(" to move, "),
-            Span::styled("Enter", Style::default().fg(Color::Cyan)),
-            Span::raw(" to select, "),
-            Span::styled("1/2", Style::default().fg(Color::Cyan)),
-            Span::raw(" quick select"),
-        ]),
-        Line::from(vec![
-            Span::styled("Esc", Style::default().fg(Color::Cyan)),
-            Span::raw(" to go back"),
-        ]),
-    ])
-    .block(
-        Block::default()
-            .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::Black))
-            .padding(Padding::new(1, 0, 1, 0))
-            .title(Line::from(vec![
-                Span::styled("[", Style::default().fg(Color::Black)),
-                Span::styled(
-                    " Controls ",
-                    Style::default().fg(PURE_WHITE).add_modifier(Modifier::BOLD),
-                ),
-                Span::styled("]", Style::default().fg(Color::Black)),
-            ])),
-    )
-    .wrap(Wrap { trim: false });
+    let help_lines = match mode {
+        ConfirmMode::YesNo => vec![
+            Line::from(vec![
+                Span::styled(theme().up_down, Style::default().fg(Color::Cyan)),
+                Span::raw(" to move, "),
+                Span::styled("Enter", Style::default().fg(Color::Cyan)),
+                Span::raw(" to select, "),
+                Span::styled("1/2", Style::default().fg(Color::Cyan)),
+                Span::raw(" quick select"),
+            ]),
+            Line::from(vec![
+                Span::styled("Esc", Style::default().fg(Color::Cyan)),
+                Span::raw(" to go back"),
+            ]),
+        ],
+        ConfirmMode::TypedPhrase(_) => vec![
+            Line::from(vec![
+                Span::raw("Type the phrase, then "),
+                Span::styled("Enter", Style::default().fg(Color::Cyan)),
+                Span::raw(" to confirm, "),
+                Span::styled("Ctrl+U", Style::default().fg(Color::Cyan)),
+                Span::raw(" clears"),
+            ]),
+            Line::from(vec![
+                Span::styled("Esc", Style::default().fg(Color::Cyan)),
+                Span::raw(" to go back"),
+            ]),
+        ],
+    };
+    let help = Paragraph::new(help_lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Black))
+                .padding(Padding::new(1, 0, 1, 0))
+                .title(Line::from(vec![
+                    Span::styled("[", Style::default().fg(Color::Black)),
+                    Span::styled(
+                        " Controls ",
+                        Style::default().fg(PURE_WHITE).add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled("]", Style::default().fg(Color::Black)),
+                ])),
+        )
+        .wrap(Wrap { trim: false });
     f.render_widget(help, layout[3]);
 
     // Layout for the main content area
@@ -230,6 +297,48 @@ fn draw_confirm_selector(
         section_idx += 1;
     }
 
+    // Typed confirmation phrase
+    if let ConfirmMode::TypedPhrase(phrase) = mode {
+        let matches = typed == phrase;
+        let input_lines = vec![
+            Line::from(vec![
+                Span::raw("Type "),
+                Span::styled(phrase, Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" to proceed"),
+            ]),
+            Line::from(vec![
+                Span::styled("> ", Style::default().fg(Color::Cyan)),
+                Span::styled(
+                    typed,
+                    Style::default().fg(if matches { Color::Green } else { Color::Yellow }),
+                ),
+                Span::styled("_", Style::default().fg(Color::Cyan)),
+            ]),
+        ];
+        let input = Paragraph::new(input_lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Black))
+                .padding(Padding::new(1, 0, 1, 0))
+                .title(Line::from(vec![
+                    Span::styled("[", Style::default().fg(Color::Black)),
+                    Span::styled(
+                        " Confirm ",
+                        Style::default()
+                            .fg(Color::Green)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled("]", Style::default().fg(Color::Black)),
+                ])),
+        );
+        f.render_widget(input, main_layout[section_idx]);
+
+        // Installation summary on the right side
+        let summary_area = aligned_summary_area(summary_area, main_area, layout[3]);
+        draw_install_summary(summary_area, f, summary);
+        return;
+    }
+
     // "Yes"/"No" selection list
     let items: Vec<ListItem> = options
         .iter()
@@ -13,10 +13,12 @@ use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Padding, Paragraph, Wrap};
 use ratatui::{Frame, Terminal};
 
-use crate::disks::DiskInfo;
+use crate::disks::{is_live_media, DiskHealth, DiskInfo};
+use crate::installer::{InstallConfig, SwapMode, SWAP_SUBVOLUME};
 
 use super::colors::PURE_WHITE;
 use super::common::{aligned_summary_area, draw_install_summary, split_main_and_summary};
+use super::theme::{draw_themed, theme};
 use super::{InstallSummary, SelectionAction, NEBULA_ART};
 
 // Disk selector
@@ -31,10 +33,19 @@ pub fn run_disk_selector(
         return Ok(SelectionAction::Quit);
     }
     let mut cursor = initial.min(disks.len() - 1);
+    // Never start on the boot media
+    if is_live_media(&disks[cursor]) {
+        cursor = disks
+            .iter()
+            .position(|disk| !is_live_media(disk))
+            .unwrap_or(cursor);
+    }
 
     // Main loop for the disk selection screen
     loop {
-        terminal.draw(|f| draw_disk_selector(f.size(), f, disks, cursor, summary))?;
+        draw_themed(terminal, |f| {
+            draw_disk_selector(f.size(), f, disks, cursor, summary)
+        })?;
 
         // User input
         let timeout = Duration::from_millis(100);
@@ -54,7 +65,10 @@ pub fn run_disk_selector(
                             cursor += 1;
                         }
                     }
-                    KeyCode::Enter => return Ok(SelectionAction::Submit(cursor)),
+                    // Wiping the boot media would pull the live system out from under the install
+                    KeyCode::Enter if !is_live_media(&disks[cursor]) => {
+                        return Ok(SelectionAction::Submit(cursor))
+                    }
                     KeyCode::Esc => return Ok(SelectionAction::Back),
                     KeyCode::Char('q') | KeyCode::Char('Q')
                         if key.modifiers.contains(KeyModifiers::CONTROL) =>
@@ -121,17 +135,29 @@ fn draw_disk_selector(
     // Controls box
     let help = Paragraph::new(vec![
         Line::from(vec![
-            Span::styled("󰁞/󰁆", Style::default().fg(Color::Cyan)),
+            Span::styled(theme().up_down, Style::default().fg(Color::Cyan)),
             Span::raw(" to move, "),
             Span::styled("Enter", Style::default().fg(Color::Cyan)),
             Span::raw(" to select, "),
             Span::styled("Esc", Style::default().fg(Color::Cyan)),
             Span::raw(" to go back."),
         ]),
-        Line::from(vec![Span::styled(
-            "Warning: selecting the wrong disk will erase its data",
-            Style::default().fg(Color::White),
-        )]),
+        if disks.get(cursor).is_some_and(is_live_media) {
+            Line::from(vec![Span::styled(
+                "This is the live media the installer booted from",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            )])
+        } else if disks.get(cursor).map(|disk| disk.health) == Some(DiskHealth::Failed) {
+            Line::from(vec![Span::styled(
+                "Warning: SMART reports this disk is failing",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            )])
+        } else {
+            Line::from(vec![Span::styled(
+                "Warning: selecting the wrong disk will erase its data",
+                Style::default().fg(Color::White),
+            )])
+        },
     ])
     .block(
         Block::default()
@@ -155,10 +181,34 @@ fn draw_disk_selector(
         .iter()
         .enumerate()
         .map(|(idx, disk)| {
+            if is_live_media(disk) {
+                return ListItem::new(Line::from(Span::styled(
+                    format!(
+                        "{:>2}) {}{}  [live media, cannot install here]",
+                        idx + 1,
+                        theme().glyph("󰋊  ", ""),
+                        disk.label()
+                    ),
+                    Style::default().fg(Color::DarkGray),
+                )));
+            }
+            let health_color = match disk.health {
+                DiskHealth::Passed => Color::LightGreen,
+                DiskHealth::Failed => Color::Red,
+                DiskHealth::Unknown => Color::DarkGray,
+            };
             let line = Line::from(vec![
                 Span::raw(format!("{:>2}) ", idx + 1)),
-                Span::styled("󰋊  ", Style::default().fg(Color::Blue)),
+                Span::styled(theme().glyph("󰋊  ", ""), Style::default().fg(Color::Blue)),
                 Span::raw(disk.label()),
+                Span::styled(
+                    format!("  [{}]", disk.kind_label()),
+                    Style::default().fg(Color::Cyan),
+                ),
+                Span::styled(
+                    format!("  {} {}", theme().bullet, disk.health.label()),
+                    Style::default().fg(health_color),
+                ),
             ]);
             ListItem::new(line)
         })
@@ -195,3 +245,90 @@ fn draw_disk_selector(
     let summary_area = aligned_summary_area(summary_area, main_area, layout[3]);
     draw_install_summary(summary_area, f, summary);
 }
+
+// One row of the partition preview: device, size, and what goes on it
+fn partition_preview_row(device: String, size: &str, contents: String) -> Line<'static> {
+    Line::from(vec![
+        Span::styled(
+            format!("  {:<16}", device),
+            Style::default().fg(Color::Cyan),
+        ),
+        Span::raw(format!("{:<14}", size)),
+        Span::raw(contents),
+    ])
+}
+
+// Spells out what the install does to the target disk before anything is written
+pub fn render_partition_preview(config: &InstallConfig) -> Vec<Line<'static>> {
+    let disk = &config.disk;
+    let mut lines = vec![Line::from(Span::styled(
+        format!("Erased: {}, every partition and all data", disk.label()),
+        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+    ))];
+    if config.scan_bad_blocks {
+        lines.push(Line::from("  Scanned for bad blocks first (read-only)"));
+    }
+    if config.secure_wipe {
+        lines.push(Line::from(
+            "  Securely wiped first, overwriting every block",
+        ));
+    }
+    lines.push(Line::from(format!(
+        "New GPT partition table on {}:",
+        disk.device_path()
+    )));
+    let esp_mountpoint = if config.full_disk_encryption {
+        "/efi"
+    } else {
+        "/boot"
+    };
+    lines.push(partition_preview_row(
+        disk.partition_path(1),
+        "512 MiB",
+        format!("FAT32 EFI system partition, mounted at {}", esp_mountpoint),
+    ));
+    let root = if config.full_disk_encryption {
+        "LUKS1 encrypted btrfs root, /boot included"
+    } else if config.encrypt_disk {
+        "LUKS2 encrypted btrfs root"
+    } else {
+        "btrfs root, not encrypted"
+    };
+    lines.push(partition_preview_row(
+        disk.partition_path(2),
+        "rest of disk",
+        root.to_string(),
+    ));
+    if let Some(header) = &config.luks_detached_header {
+        lines.push(Line::from(format!("  LUKS header written to {}", header)));
+    }
+    let subvolumes: Vec<String> = config
+        .subvolumes
+        .iter()
+        .map(|subvol| format!("{} ({})", subvol.name, subvol.mountpoint))
+        .collect();
+    lines.push(Line::from(format!(
+        "Btrfs subvolumes: {}",
+        subvolumes.join(", ")
+    )));
+    let swap = match config.swap_mode {
+        _ if !config.swap_enabled => "Swap: off".to_string(),
+        SwapMode::Zram => "Swap: zram in RAM, nothing on disk".to_string(),
+        SwapMode::File { size_mib } => {
+            format!("Swap: {} MiB swapfile in {}", size_mib, SWAP_SUBVOLUME)
+        }
+    };
+    lines.push(Line::from(swap));
+    if let Some(home) = &config.existing_home {
+        let subvolume = home
+            .subvolume
+            .as_ref()
+            .map(|name| format!(" subvolume {}", name))
+            .unwrap_or_default();
+        lines.push(Line::from(format!(
+            "/home: {}{} is kept and never formatted",
+            home.device, subvolume
+        )));
+    }
+    lines
+}
@@ -1,17 +1,21 @@
 /////////
 /// Installation progress screen
 ////////
-use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Clear, Gauge, Padding, Paragraph, Wrap};
 use ratatui::Frame;
 
-use crate::model::{App, Step, StepStatus};
+use crate::installer::{step_log_path, LAST_CANCELLABLE_STEP};
+use crate::model::{App, LogLevel, Step, StepStatus, POWER_COUNTDOWN_SECS};
 use crate::ui::colors::PURE_WHITE;
 
 use super::{NEBULA_ART, SPINNER};
 
+// Log lines shown on the failure panel
+const FAILURE_LOG_LINES: usize = 12;
+
 // Installation progress UI
 pub fn draw_ui(area: Rect, f: &mut Frame<'_>, app: &App) {
     let layout = Layout::default()
@@ -92,8 +96,17 @@ pub fn draw_ui(area: Rect, f: &mut Frame<'_>, app: &App) {
     let log_lines: Vec<Line> = app
         .logs
         .iter()
-        .map(|line| Line::from(Span::raw(line.clone())))
+        .filter_map(|line| {
+            let level = LogLevel::of(line);
+            (level >= app.log_filter)
+                .then(|| Line::from(Span::styled(line.clone(), style_for_log_level(level))))
+        })
         .collect();
+    let log_title = if app.log_filter == LogLevel::Command {
+        " Logs ".to_string()
+    } else {
+        format!(" Logs ({}) ", app.log_filter.filter_label())
+    };
     let log_height = layout[6].height.saturating_sub(2) as usize;
     let scroll_offset = log_lines.len().saturating_sub(log_height);
     let scroll_offset = scroll_offset.min(u16::MAX as usize) as u16;
@@ -107,7 +120,7 @@ pub fn draw_ui(area: Rect, f: &mut Frame<'_>, app: &App) {
                 .title(Line::from(vec![
                     Span::styled("[", Style::default().fg(Color::Black)),
                     Span::styled(
-                        " Logs ",
+                        log_title,
                         Style::default().fg(PURE_WHITE).add_modifier(Modifier::BOLD),
                     ),
                     Span::styled("]", Style::default().fg(Color::Black)),
@@ -117,33 +130,196 @@ pub fn draw_ui(area: Rect, f: &mut Frame<'_>, app: &App) {
         .scroll((scroll_offset, 0));
     f.render_widget(logs, layout[6]);
 
+    // A failed install replaces the logs with the failed step and its last output
+    if let Some(err) = app.err.as_deref().filter(|_| app.done && !app.cancelling) {
+        draw_failure_panel(f, layout[6], app, err);
+    }
+
     // Final status message at the bottom when the installation is done
-    let status_line = if app.done {
+    let status_line = if app.done && app.cancelling {
+        Line::from(Span::styled(
+            "Installation cancelled.",
+            Style::default().fg(Color::Yellow),
+        ))
+    } else if app.cancelling {
+        Line::from(Span::styled(
+            "Cleaning up...",
+            Style::default().fg(Color::Yellow),
+        ))
+    } else if app.cancel_prompt {
+        Line::from(vec![
+            Span::styled(
+                "Cancel the installation?",
+                Style::default().fg(Color::Yellow),
+            ),
+            Span::raw(" "),
+            Span::styled(
+                "Press Y to cancel or N to continue",
+                Style::default()
+                    .fg(Color::Magenta)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ])
+    } else if app.done {
         if app.err.is_some() {
             Line::from(Span::styled(
                 "Installation failed.",
                 Style::default().fg(Color::LightRed),
             ))
         } else {
-            Line::from(vec![
+            let action_style = Style::default()
+                .fg(Color::Magenta)
+                .add_modifier(Modifier::BOLD);
+            // Greyed out on BIOS boots, where there is no firmware setup to reboot into
+            let firmware_style = if app.firmware_setup {
+                action_style
+            } else {
+                Style::default().fg(Color::DarkGray)
+            };
+            let mut spans = vec![
                 Span::styled(
                     "Installation complete!",
                     Style::default().fg(Color::LightGreen),
                 ),
                 Span::raw(" "),
-                Span::styled(
-                    "Press R to reboot or S to shut down",
-                    Style::default()
-                        .fg(Color::Magenta)
-                        .add_modifier(Modifier::BOLD),
-                ),
-            ])
+                Span::styled("Press R to reboot, S to shut down, ", action_style),
+                Span::styled("F for firmware setup", firmware_style),
+            ];
+            if app.target_mounted {
+                spans.push(Span::styled(", C for a chroot shell", action_style));
+            }
+            spans.push(Span::styled(
+                " or Q to stay in the live system",
+                action_style,
+            ));
+            Line::from(spans)
         }
+    } else if app
+        .last_running_step
+        .is_none_or(|index| index < LAST_CANCELLABLE_STEP)
+    {
+        Line::from(Span::styled(
+            "Press C to cancel, L to filter logs",
+            Style::default().fg(Color::DarkGray),
+        ))
     } else {
-        Line::from(" ")
+        Line::from(Span::styled(
+            "Press L to filter logs",
+            Style::default().fg(Color::DarkGray),
+        ))
     };
     let status_line = Paragraph::new(status_line);
     f.render_widget(status_line, layout[7]);
+
+    // Reboot/shutdown countdown drawn over the log panel
+    if let Some((action, started)) = app.power_prompt {
+        let remaining = POWER_COUNTDOWN_SECS.saturating_sub(started.elapsed().as_secs());
+        draw_power_prompt(f, layout[6], action.label(), remaining);
+    }
+}
+
+// Names the failed step, shows the error and the output that led up to it
+fn draw_failure_panel(f: &mut Frame<'_>, area: Rect, app: &App, err: &str) {
+    let step_name = app
+        .last_running_step
+        .and_then(|index| app.steps.get(index))
+        .map(|step| step.name.as_str())
+        .unwrap_or("Installation");
+    let mut lines = vec![
+        Line::from(Span::styled(
+            err.to_string(),
+            Style::default()
+                .fg(Color::LightRed)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Last output:",
+            Style::default().fg(PURE_WHITE).add_modifier(Modifier::BOLD),
+        )),
+    ];
+    let tail_start = app.logs.len().saturating_sub(FAILURE_LOG_LINES);
+    lines.extend(app.logs.iter().skip(tail_start).map(|line| {
+        let level = LogLevel::of(line);
+        let style = if level == LogLevel::Error {
+            style_for_log_level(level).add_modifier(Modifier::BOLD)
+        } else {
+            style_for_log_level(level)
+        };
+        Line::from(Span::styled(line.clone(), style))
+    }));
+    lines.push(Line::from(""));
+    if let Some(index) = app.last_running_step {
+        lines.push(Line::from(vec![
+            Span::raw("Step log: "),
+            Span::styled(step_log_path(index), Style::default().fg(Color::Cyan)),
+        ]));
+    }
+    lines.push(Line::from(vec![
+        Span::raw("Full log: "),
+        Span::styled(crate::LOG_FILE_PATH, Style::default().fg(Color::Cyan)),
+    ]));
+
+    // Keep the log path visible when the tail does not fit
+    let inner_height = area.height.saturating_sub(3) as usize;
+    let scroll_offset = lines
+        .len()
+        .saturating_sub(inner_height)
+        .min(u16::MAX as usize) as u16;
+    f.render_widget(Clear, area);
+    let panel = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Red))
+                .padding(Padding::new(1, 0, 1, 0))
+                .title(Line::from(vec![
+                    Span::styled("[", Style::default().fg(Color::Red)),
+                    Span::styled(
+                        format!(" {} failed ", step_name),
+                        Style::default()
+                            .fg(Color::LightRed)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled("]", Style::default().fg(Color::Red)),
+                ])),
+        )
+        .wrap(Wrap { trim: false })
+        .scroll((scroll_offset, 0));
+    f.render_widget(panel, area);
+}
+
+// Confirmation box shown while the reboot/shutdown countdown runs
+fn draw_power_prompt(f: &mut Frame<'_>, area: Rect, label: &str, remaining: u64) {
+    let width = 48.min(area.width);
+    let height = 6.min(area.height);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+    let lines = vec![
+        Line::from(Span::styled(
+            format!("{} in {}s...", label, remaining),
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Enter to continue now, Esc to cancel",
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+    f.render_widget(Clear, popup);
+    let prompt = Paragraph::new(lines).alignment(Alignment::Center).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Magenta))
+            .padding(Padding::new(1, 1, 1, 0)),
+    );
+    f.render_widget(prompt, popup);
 }
 
 fn render_step(step: &Step, spinner_idx: usize) -> Line<'static> {
@@ -177,6 +353,16 @@ fn render_step(step: &Step, spinner_idx: usize) -> Line<'static> {
     Line::from(spans)
 }
 
+// Returns a style (color) for a given log level
+fn style_for_log_level(level: LogLevel) -> Style {
+    match level {
+        LogLevel::Command => Style::default().fg(Color::DarkGray),
+        LogLevel::Info => Style::default(),
+        LogLevel::Warning => Style::default().fg(Color::Yellow),
+        LogLevel::Error => Style::default().fg(Color::Red),
+    }
+}
+
 // Returns a style (color) for a given step status
 fn style_for_status(status: StepStatus) -> Style {
     match status {
@@ -1,5 +1,5 @@
 /////////
-/// Keymap selection
+/// Searchable list selection (keymaps, locales)
 ////////
 use std::io;
 use std::time::Duration;
@@ -18,28 +18,43 @@ use crate::ui::colors::PURE_WHITE;
 use super::common::{
     aligned_summary_area, draw_install_summary, filter_items, split_main_and_summary,
 };
+use super::theme::{draw_themed, theme};
 use super::{InstallSummary, SelectionAction, NEBULA_ART};
 
-// Keymap selector
-pub fn run_keymap_selector(
+// Searchable single-choice list; `list_name` heads the list box, e.g. "Keymaps"
+pub fn run_list_selector(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
-    keymaps: &[String],
+    title: &str,
+    list_name: &str,
+    items: &[String],
     initial: usize,
     summary: &InstallSummary,
 ) -> Result<SelectionAction<usize>> {
-    if keymaps.is_empty() {
+    if items.is_empty() {
         return Ok(SelectionAction::Quit);
     }
 
     // State for the search/filter
     let mut query = String::new();
-    let mut filtered = filter_items(keymaps, &query);
+    let mut filtered = filter_items(items, &query);
     let mut cursor = filtered.iter().position(|idx| *idx == initial).unwrap_or(0);
 
-    // Main loop for the keymap selection screen
+    // Main loop for the selection screen
     loop {
-        terminal.draw(|f| {
-            draw_keymap_selector(f.size(), f, cursor, keymaps, &filtered, &query, summary)
+        draw_themed(terminal, |f| {
+            draw_list_selector(
+                f.size(),
+                f,
+                ListView {
+                    title,
+                    list_name,
+                    items,
+                    filtered: &filtered,
+                    cursor,
+                    query: &query,
+                },
+                summary,
+            )
         })?;
 
         // User input
@@ -51,30 +66,14 @@ pub fn run_keymap_selector(
                 }
                 match key.code {
                     // Navigation controls
-                    KeyCode::Up => {
-                        if cursor > 0 {
-                            cursor -= 1;
-                        }
-                    }
-                    KeyCode::Down => {
-                        if cursor + 1 < filtered.len() {
-                            cursor += 1;
-                        }
-                    }
-                    KeyCode::PageUp => {
-                        cursor = cursor.saturating_sub(15);
-                    }
-                    KeyCode::PageDown => {
-                        if !filtered.is_empty() {
-                            cursor = (cursor + 15).min(filtered.len() - 1);
-                        }
+                    KeyCode::Up => cursor = cursor.saturating_sub(1),
+                    KeyCode::Down if cursor + 1 < filtered.len() => cursor += 1,
+                    KeyCode::PageUp => cursor = cursor.saturating_sub(15),
+                    KeyCode::PageDown if !filtered.is_empty() => {
+                        cursor = (cursor + 15).min(filtered.len() - 1);
                     }
                     KeyCode::Home => cursor = 0,
-                    KeyCode::End => {
-                        if !filtered.is_empty() {
-                            cursor = filtered.len() - 1;
-                        }
-                    }
+                    KeyCode::End if !filtered.is_empty() => cursor = filtered.len() - 1,
                     // Action controls
                     KeyCode::Enter => {
                         if let Some(idx) = filtered.get(cursor) {
@@ -91,22 +90,22 @@ pub fn run_keymap_selector(
                     // Search/filter controls
                     KeyCode::Backspace => {
                         query.pop();
-                        filtered = filter_items(keymaps, &query);
+                        filtered = filter_items(items, &query);
                         cursor = 0;
                     }
                     KeyCode::Char('/') => {
                         query.clear();
-                        filtered = filter_items(keymaps, &query);
+                        filtered = filter_items(items, &query);
                         cursor = 0;
                     }
                     KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                         query.clear();
-                        filtered = filter_items(keymaps, &query);
+                        filtered = filter_items(items, &query);
                         cursor = 0;
                     }
                     KeyCode::Char(ch) if ch.is_ascii() && !ch.is_ascii_control() => {
                         query.push(ch);
-                        filtered = filter_items(keymaps, &query);
+                        filtered = filter_items(items, &query);
                         cursor = 0;
                     }
                     _ => {}
@@ -116,16 +115,26 @@ pub fn run_keymap_selector(
     }
 }
 
-// Main keymap selector UI
-fn draw_keymap_selector(
-    area: Rect,
-    f: &mut Frame<'_>,
+// What the list screen shows; `filtered` holds indexes into `items`
+struct ListView<'a> {
+    title: &'a str,
+    list_name: &'a str,
+    items: &'a [String],
+    filtered: &'a [usize],
     cursor: usize,
-    keymaps: &[String],
-    filtered: &[usize],
-    query: &str,
-    summary: &InstallSummary,
-) {
+    query: &'a str,
+}
+
+// Main list selector UI
+fn draw_list_selector(area: Rect, f: &mut Frame<'_>, view: ListView<'_>, summary: &InstallSummary) {
+    let ListView {
+        title,
+        list_name,
+        items,
+        filtered,
+        cursor,
+        query,
+    } = view;
     let (main_area, summary_area) = split_main_and_summary(area);
     let layout = Layout::default()
         .direction(Direction::Vertical)
@@ -157,7 +166,7 @@ fn draw_keymap_selector(
     let title = Line::from(vec![
         Span::raw("/- "),
         Span::styled(
-            "Select keyboard layout",
+            title,
             Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
         ),
         Span::raw(" -/"),
@@ -168,7 +177,7 @@ fn draw_keymap_selector(
     // Controls box
     let help = Paragraph::new(vec![
         Line::from(vec![
-            Span::styled("󰁞/󰁆", Style::default().fg(Color::Cyan)),
+            Span::styled(theme().up_down, Style::default().fg(Color::Cyan)),
             Span::raw(" to move, "),
             Span::styled("PgUp/PgDn", Style::default().fg(Color::Cyan)),
             Span::raw(" to scroll, "),
@@ -210,22 +219,22 @@ fn draw_keymap_selector(
     let visible = &filtered[start..end];
 
     // Create the list items from the visible part of the filtered list
-    let items: Vec<ListItem> = visible
+    let rows: Vec<ListItem> = visible
         .iter()
         .enumerate()
-        .map(|(idx, keymap_idx)| {
-            let keymap = keymaps.get(*keymap_idx).map(|s| s.as_str()).unwrap_or("");
+        .map(|(idx, item_idx)| {
+            let item = items.get(*item_idx).map(|s| s.as_str()).unwrap_or("");
             let line = Line::from(vec![
                 Span::raw(format!("{:>4}) ", start + idx + 1)),
-                Span::raw(keymap),
+                Span::raw(item),
             ]);
             ListItem::new(line)
         })
         .collect();
 
-    // List of keymaps
-    let title = format!("Keymaps ({} / {} total)", filtered.len(), keymaps.len());
-    let list = List::new(items)
+    // The items left after searching
+    let title = format!("{} ({} / {} total)", list_name, filtered.len(), items.len());
+    let list = List::new(rows)
         .block(
             Block::default()
                 .borders(Borders::ALL)
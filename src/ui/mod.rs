@@ -14,6 +14,7 @@ pub(crate) const NEBULA_ART: [&str; 6] = [
 pub struct ReviewItem {
     pub label: String,
     pub value: String,
+    pub step: Option<crate::SetupStep>, // Setup step that edits this item
 }
 
 // The number of steps shown in the summary view
@@ -40,6 +41,8 @@ pub enum ReviewAction {
     Confirm,
     Back,
     Edit,
+    EditField(crate::SetupStep),
+    Export, // Save the selections to an answer file
     Quit,
 }
 
@@ -73,9 +76,17 @@ pub enum ConfirmAction {
     Quit,
 }
 
+// How a confirmation screen is answered
+#[derive(Clone, Copy)]
+pub enum ConfirmMode<'a> {
+    YesNo,
+    TypedPhrase(&'a str), // The phrase must be typed exactly to confirm
+}
+
 // Actions for the Wi-Fi selection screen
 pub enum WifiAction {
     Submit(usize),
+    Hidden,
     Rescan,
     Refresh,
     Continue,
@@ -96,24 +107,33 @@ mod confirm;
 mod disk;
 mod installer;
 mod keybinds;
-mod keymap;
+mod list_selector;
+mod multiselect;
 mod network;
 mod review;
 mod selectors;
+mod serial;
 mod text_input;
+mod theme;
 mod timezone;
 mod wifi;
 
 pub use app_selection::run_application_selector;
 pub use confirm::run_confirm_selector;
-pub use disk::run_disk_selector;
+pub use disk::{render_partition_preview, run_disk_selector};
 pub use installer::draw_ui;
-pub use keymap::run_keymap_selector;
+pub use list_selector::run_list_selector;
+pub use multiselect::{run_multiselect, SelectGroup};
 pub use network::run_network_required;
 pub use review::run_review;
 #[allow(unused_imports)]
-pub use selectors::run_nvidia_selector;
+pub use selectors::{
+    run_aur_helper_selector, run_btrfs_layout_selector, run_mitigations_selector,
+    run_nvidia_selector, run_profile_selector,
+};
+pub use serial::{prompt_choice, prompt_line, prompt_new_secret, prompt_yes_no, serial_console};
 pub use text_input::{render_text_input, run_text_input};
+pub use theme::{draw_themed, theme};
 pub use timezone::{render_timezone_loading, run_timezone_selector};
 pub use wifi::render_wifi_connecting;
 pub use wifi::{render_wifi_searching, run_wifi_selector};
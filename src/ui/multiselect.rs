@@ -0,0 +1,326 @@
+/////////
+/// Reusable checkbox columns
+////////
+use std::io;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Padding, Paragraph, Wrap};
+use ratatui::{Frame, Terminal};
+
+use crate::ui::colors::PURE_WHITE;
+
+use super::common::{aligned_summary_area, draw_install_summary, split_main_and_summary};
+use super::theme::{draw_themed, theme};
+use super::{InstallSummary, SelectionAction, NEBULA_ART};
+
+// One column of checkboxes
+#[derive(Clone, Debug)]
+pub struct SelectGroup {
+    pub title: String,
+    pub labels: Vec<String>,
+    pub checked: Vec<bool>,
+    pub single: bool, // Exactly one item stays checked, like the compositor list
+    pub unavailable: Vec<bool>, // Greyed out as "not detected" and never checked
+}
+
+impl SelectGroup {
+    pub fn new(title: &str, labels: Vec<String>, checked: &[bool]) -> Self {
+        let mut checked = checked.to_vec();
+        checked.resize(labels.len(), false);
+        Self {
+            title: title.to_string(),
+            unavailable: vec![false; labels.len()],
+            labels,
+            checked,
+            single: false,
+        }
+    }
+
+    pub fn single(mut self) -> Self {
+        self.single = true;
+        self
+    }
+
+    pub fn unavailable(mut self, unavailable: Vec<bool>) -> Self {
+        self.unavailable = unavailable;
+        self.unavailable.resize(self.labels.len(), false);
+        self
+    }
+
+    // Flips an item; single-select groups move the check instead
+    pub fn toggle(&mut self, index: usize) {
+        if self.unavailable.get(index).copied().unwrap_or(true) {
+            return;
+        }
+        toggle_checked(&mut self.checked, index, self.single);
+    }
+
+    // Where the cursor starts: the first checked item
+    pub(crate) fn initial_cursor(&self) -> usize {
+        self.checked.iter().position(|flag| *flag).unwrap_or(0)
+    }
+}
+
+// Checkbox toggle shared by every multi-select list
+pub(crate) fn toggle_checked(checked: &mut [bool], index: usize, single: bool) {
+    if index >= checked.len() {
+        return;
+    }
+    if single {
+        checked.fill(false);
+        checked[index] = true;
+    } else {
+        checked[index] = !checked[index];
+    }
+}
+
+// Renders a group as a bordered checkbox list; `cursor` is set while the group has focus
+pub(crate) fn draw_select_group(
+    f: &mut Frame<'_>,
+    area: Rect,
+    group: &SelectGroup,
+    cursor: Option<usize>,
+) {
+    let items: Vec<ListItem> = group
+        .labels
+        .iter()
+        .enumerate()
+        .map(|(idx, label)| {
+            if group.unavailable.get(idx).copied().unwrap_or(false) {
+                ListItem::new(Line::from(Span::styled(
+                    format!("[-] {} (not detected)", label),
+                    Style::default().fg(Color::DarkGray),
+                )))
+            } else if group.checked.get(idx).copied().unwrap_or(false) {
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!("[{}]", theme().checked),
+                        Style::default().fg(Color::LightGreen),
+                    ), // Checkbox checked
+                    Span::raw(" "),
+                    Span::styled(label.as_str(), Style::default().fg(Color::Blue)),
+                ]))
+            } else {
+                ListItem::new(Line::from(format!("[ ] {}", label))) // Checkbox unchecked
+            }
+        })
+        .collect();
+    let title_style = if cursor.is_some() {
+        Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(PURE_WHITE).add_modifier(Modifier::BOLD)
+    };
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Black))
+                .padding(Padding::new(1, 0, 1, 0))
+                .title(Line::from(vec![
+                    Span::styled("[", Style::default().fg(Color::Black)),
+                    Span::styled(format!(" {} ", group.title), title_style),
+                    Span::styled("]", Style::default().fg(Color::Black)),
+                ])),
+        )
+        .highlight_style(
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        );
+    let mut state = ListState::default();
+    if let Some(cursor) = cursor.filter(|_| !group.labels.is_empty()) {
+        state.select(Some(cursor.min(group.labels.len() - 1)));
+    }
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+// Multi-select screen with the groups side by side
+fn draw_multiselect(
+    area: Rect,
+    f: &mut Frame<'_>,
+    title: &str,
+    groups: &[SelectGroup],
+    focus: usize,
+    cursors: &[usize],
+    summary: &InstallSummary,
+) {
+    let (main_area, summary_area) = split_main_and_summary(area);
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(0)
+        .constraints([
+            Constraint::Length(NEBULA_ART.len() as u16),
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(5),
+            Constraint::Min(6),
+            Constraint::Length(5),
+        ])
+        .split(main_area);
+
+    let art_lines: Vec<Line> = NEBULA_ART
+        .iter()
+        .map(|line| {
+            Line::from(Span::styled(
+                *line,
+                Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+            ))
+        })
+        .collect();
+    f.render_widget(Paragraph::new(art_lines).block(Block::default()), layout[0]);
+
+    let title = Line::from(vec![
+        Span::raw("/- "),
+        Span::styled(
+            title,
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(" -/"),
+    ]);
+    f.render_widget(Paragraph::new(title).block(Block::default()), layout[1]);
+
+    // Controls box
+    let help = Paragraph::new(vec![
+        Line::from(vec![
+            Span::styled(theme().up_down, Style::default().fg(Color::Cyan)),
+            Span::raw(" move, "),
+            Span::styled(theme().left_right, Style::default().fg(Color::Cyan)),
+            Span::raw(" switch column, "),
+            Span::styled("Space", Style::default().fg(Color::Cyan)),
+            Span::raw(" toggle."),
+        ]),
+        Line::from(vec![
+            Span::styled("Enter", Style::default().fg(Color::Cyan)),
+            Span::raw(" confirm, "),
+            Span::styled("B", Style::default().fg(Color::Cyan)),
+            Span::raw(" back."),
+        ]),
+    ])
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Black))
+            .padding(Padding::new(1, 0, 1, 0))
+            .title(Line::from(vec![
+                Span::styled("[", Style::default().fg(Color::Black)),
+                Span::styled(
+                    " Controls ",
+                    Style::default().fg(PURE_WHITE).add_modifier(Modifier::BOLD),
+                ),
+                Span::styled("]", Style::default().fg(Color::Black)),
+            ])),
+    )
+    .wrap(Wrap { trim: false });
+    f.render_widget(help, layout[3]);
+
+    // One column per group
+    if !groups.is_empty() {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![
+                Constraint::Ratio(1, groups.len() as u32);
+                groups.len()
+            ])
+            .split(layout[4]);
+        for (idx, group) in groups.iter().enumerate() {
+            let cursor = (idx == focus).then(|| cursors[idx]);
+            draw_select_group(f, columns[idx], group, cursor);
+        }
+    }
+
+    let total_selected = groups
+        .iter()
+        .flat_map(|group| group.checked.iter())
+        .filter(|flag| **flag)
+        .count();
+    let confirm_text_style = Style::default().fg(Color::White);
+    let confirm = Paragraph::new(vec![
+        Line::from(Span::styled("Press Enter to continue", confirm_text_style)),
+        Line::from(Span::styled(
+            format!("Selected: {total_selected}"),
+            confirm_text_style,
+        )),
+    ])
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Black))
+            .padding(Padding::new(1, 0, 1, 0))
+            .title(Line::from(vec![
+                Span::styled("[", Style::default().fg(Color::Black)),
+                Span::styled(
+                    " Confirm ",
+                    Style::default()
+                        .fg(Color::LightGreen)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled("]", Style::default().fg(Color::Black)),
+            ])),
+    );
+    f.render_widget(confirm, layout[5]);
+
+    // Installation summary on the right side
+    let summary_area = aligned_summary_area(summary_area, main_area, layout[3]);
+    draw_install_summary(summary_area, f, summary);
+}
+
+// Generic checkbox screen; returns the checked state of every group
+pub fn run_multiselect(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    title: &str,
+    groups: &[SelectGroup],
+    summary: &InstallSummary,
+) -> Result<SelectionAction<Vec<Vec<bool>>>> {
+    let mut groups = groups.to_vec();
+    let mut cursors: Vec<usize> = groups.iter().map(SelectGroup::initial_cursor).collect();
+    let mut focus = 0usize;
+
+    loop {
+        draw_themed(terminal, |f| {
+            draw_multiselect(f.size(), f, title, &groups, focus, &cursors, summary)
+        })?;
+
+        if !event::poll(Duration::from_millis(100)).context("poll events")? {
+            continue;
+        }
+        let Event::Key(key) = event::read().context("read event")? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press || groups.is_empty() {
+            continue;
+        }
+        match key.code {
+            KeyCode::Left => focus = focus.saturating_sub(1),
+            KeyCode::Right if focus + 1 < groups.len() => focus += 1,
+            KeyCode::Up => cursors[focus] = cursors[focus].saturating_sub(1),
+            KeyCode::Down if cursors[focus] + 1 < groups[focus].labels.len() => {
+                cursors[focus] += 1;
+            }
+            KeyCode::Char(' ') => groups[focus].toggle(cursors[focus]),
+            KeyCode::Enter => {
+                return Ok(SelectionAction::Submit(
+                    groups.into_iter().map(|group| group.checked).collect(),
+                ));
+            }
+            KeyCode::Char('b') | KeyCode::Char('B') | KeyCode::Esc => {
+                return Ok(SelectionAction::Back);
+            }
+            KeyCode::Char('q') | KeyCode::Char('Q')
+                if key.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                return Ok(SelectionAction::Quit);
+            }
+            _ => {}
+        }
+    }
+}
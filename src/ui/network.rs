@@ -13,10 +13,39 @@ use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
 use ratatui::{Frame, Terminal};
 
+use crate::network::{speed_test, SpeedTest, SLOW_DOWNLOAD_MBPS};
+
 use super::colors::PURE_WHITE;
 use super::common::{aligned_summary_area, draw_install_summary, split_main_and_summary};
+use super::theme::draw_themed;
 use super::{InstallSummary, NetworkAction, NEBULA_ART};
 
+// Line describing the download speed test, if one has run
+pub(crate) fn speed_test_line() -> Option<Line<'static>> {
+    match speed_test() {
+        SpeedTest::NotStarted => None,
+        SpeedTest::Running => Some(Line::from(Span::styled(
+            "Testing download speed...",
+            Style::default().fg(Color::White),
+        ))),
+        SpeedTest::Done(speed) if speed < SLOW_DOWNLOAD_MBPS => Some(Line::from(Span::styled(
+            format!(
+                "Slow connection ({:.1} MB/s); the offline install may be faster.",
+                speed
+            ),
+            Style::default().fg(Color::Yellow),
+        ))),
+        SpeedTest::Done(speed) => Some(Line::from(Span::styled(
+            format!("Download speed: {:.1} MB/s", speed),
+            Style::default().fg(Color::Green),
+        ))),
+        SpeedTest::Failed => Some(Line::from(Span::styled(
+            "Download speed test failed.",
+            Style::default().fg(Color::Yellow),
+        ))),
+    }
+}
+
 // Runs the "Network Required" screen, waiting for the user to retry or quit
 pub fn run_network_required(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
@@ -24,7 +53,7 @@ pub fn run_network_required(
 ) -> Result<NetworkAction> {
     // Main loop for the screen
     loop {
-        terminal.draw(|f| draw_network_required(f.size(), f, summary))?;
+        draw_themed(terminal, |f| draw_network_required(f.size(), f, summary))?;
 
         // User input
         let timeout = Duration::from_millis(100);
@@ -57,7 +86,7 @@ fn draw_network_required(area: Rect, f: &mut Frame<'_>, summary: &InstallSummary
             Constraint::Length(NEBULA_ART.len() as u16),
             Constraint::Length(1),
             Constraint::Length(1),
-            Constraint::Length(4),
+            Constraint::Length(5),
             Constraint::Min(6),
             Constraint::Length(1),
         ])
@@ -91,24 +120,26 @@ fn draw_network_required(area: Rect, f: &mut Frame<'_>, summary: &InstallSummary
     f.render_widget(title_block, layout[1]);
 
     // Info box explaining the issue
-    let info = Paragraph::new(vec![
+    let mut info_lines = vec![
         Line::from("A Wi-Fi device was not detected"),
         Line::from("Connect ethernet and press R to retry"),
-    ])
-    .block(
-        Block::default()
-            .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::Black))
-            .title(Line::from(vec![
-                Span::styled("[", Style::default().fg(Color::Black)),
-                Span::styled(
-                    " Info ",
-                    Style::default().fg(PURE_WHITE).add_modifier(Modifier::BOLD),
-                ),
-                Span::styled("]", Style::default().fg(Color::Black)),
-            ])),
-    )
-    .wrap(Wrap { trim: false });
+    ];
+    info_lines.extend(speed_test_line());
+    let info = Paragraph::new(info_lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Black))
+                .title(Line::from(vec![
+                    Span::styled("[", Style::default().fg(Color::Black)),
+                    Span::styled(
+                        " Info ",
+                        Style::default().fg(PURE_WHITE).add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled("]", Style::default().fg(Color::Black)),
+                ])),
+        )
+        .wrap(Wrap { trim: false });
     f.render_widget(info, layout[3]);
 
     // Controls box
@@ -11,19 +11,35 @@ use ratatui::widgets::{Block, Borders, Padding, Paragraph, Wrap};
 use ratatui::{Frame, Terminal};
 
 use super::colors::PURE_WHITE;
+use super::theme::{draw_themed, theme};
 use super::{ReviewAction, ReviewItem, NEBULA_ART};
 
-// Review screen, waiting for the user to confirm, go back, or quit
+// Review screen, waiting for the user to confirm, go back, or quit.
+// `notice` replaces the selected-apps line, e.g. after an export.
 pub fn run_review(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     system_items: &[ReviewItem],
     package_items: &[ReviewItem],
     selected_packages: usize,
+    notice: Option<&str>,
 ) -> Result<ReviewAction> {
+    // Cursor over the system rows followed by the package rows
+    let row_count = system_items.len() + package_items.len();
+    let mut cursor = 0usize;
+
     // Main loop for the review screen
     loop {
-        terminal
-            .draw(|f| draw_review(f.size(), f, system_items, package_items, selected_packages))?;
+        draw_themed(terminal, |f| {
+            draw_review(
+                f.size(),
+                f,
+                system_items,
+                package_items,
+                selected_packages,
+                cursor,
+                notice,
+            )
+        })?;
 
         // User input
         let timeout = Duration::from_millis(100);
@@ -33,9 +49,18 @@ pub fn run_review(
                     continue;
                 }
                 match key.code {
+                    KeyCode::Up => cursor = cursor.saturating_sub(1),
+                    KeyCode::Down if cursor + 1 < row_count => cursor += 1,
+                    KeyCode::Char('e') | KeyCode::Char('E') => {
+                        let item = system_items.iter().chain(package_items.iter()).nth(cursor);
+                        if let Some(step) = item.and_then(|item| item.step) {
+                            return Ok(ReviewAction::EditField(step));
+                        }
+                    }
                     KeyCode::Enter => return Ok(ReviewAction::Confirm),
                     KeyCode::Esc => return Ok(ReviewAction::Back),
                     KeyCode::Char('s') | KeyCode::Char('S') => return Ok(ReviewAction::Edit),
+                    KeyCode::Char('x') | KeyCode::Char('X') => return Ok(ReviewAction::Export),
                     KeyCode::Char('q') | KeyCode::Char('Q')
                         if key.modifiers.contains(KeyModifiers::CONTROL) =>
                     {
@@ -55,6 +80,8 @@ fn draw_review(
     system_items: &[ReviewItem],
     package_items: &[ReviewItem],
     selected_packages: usize,
+    cursor: usize,
+    notice: Option<&str>,
 ) {
     let layout = Layout::default()
         .direction(Direction::Vertical)
@@ -102,7 +129,13 @@ fn draw_review(
             Span::styled("Esc", Style::default().fg(Color::Cyan)),
             Span::raw(" to go back, "),
             Span::styled("S", Style::default().fg(Color::Cyan)),
-            Span::raw(" to start over."),
+            Span::raw(" to start over, "),
+            Span::styled(theme().up_down, Style::default().fg(Color::Cyan)),
+            Span::raw(" + "),
+            Span::styled("E", Style::default().fg(Color::Cyan)),
+            Span::raw(" to edit a row, "),
+            Span::styled("X", Style::default().fg(Color::Cyan)),
+            Span::raw(" to export."),
         ]),
         Line::from(vec![
             Span::styled("SuperKey", Style::default().fg(Color::Cyan)),
@@ -150,12 +183,13 @@ fn draw_review(
         height: grid_area.height,
     };
 
-    let system_block = Paragraph::new(review_lines(system_items))
+    let system_block = Paragraph::new(review_lines(system_items, Some(cursor)))
         .block(review_block("System"))
         .wrap(Wrap { trim: false });
     f.render_widget(system_block, left_area);
 
-    let packages_block = Paragraph::new(review_lines(package_items))
+    let package_cursor = cursor.checked_sub(system_items.len());
+    let packages_block = Paragraph::new(review_lines(package_items, package_cursor))
         .block(review_block("Packages"))
         .wrap(Wrap { trim: false });
     f.render_widget(packages_block, right_area);
@@ -169,10 +203,13 @@ fn draw_review(
             "Press Enter to start installation process",
             confirm_text_style,
         )),
-        Line::from(Span::styled(
-            format!("Selected: {selected_packages} apps."),
-            confirm_text_style,
-        )),
+        match notice {
+            Some(notice) => Line::from(Span::styled(notice, Style::default().fg(Color::Yellow))),
+            None => Line::from(Span::styled(
+                format!("Selected: {selected_packages} apps."),
+                confirm_text_style,
+            )),
+        },
     ];
     let confirm_block = Paragraph::new(confirm_lines).block(
         Block::default()
@@ -206,11 +243,17 @@ fn review_block(title: &str) -> Block<'_> {
         ]))
 }
 
-fn review_lines(items: &[ReviewItem]) -> Vec<Line<'_>> {
+fn review_lines(items: &[ReviewItem], cursor: Option<usize>) -> Vec<Line<'_>> {
     items
         .iter()
-        .map(|item| {
-            let icon = review_icon(&item.label);
+        .enumerate()
+        .map(|(idx, item)| {
+            let icon = theme().glyph(review_icon(&item.label), theme().bullet);
+            let label_color = if cursor == Some(idx) {
+                Color::Yellow
+            } else {
+                Color::White
+            };
             Line::from(vec![
                 // Span::styled(
                 //     " ",
@@ -224,7 +267,7 @@ fn review_lines(items: &[ReviewItem]) -> Vec<Line<'_>> {
                 Span::styled(
                     format!("{}:", item.label),
                     Style::default()
-                        .fg(Color::White)
+                        .fg(label_color)
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::styled(format!(" {}", item.value), Style::default().fg(Color::Blue)),
@@ -238,16 +281,33 @@ fn review_icon(label: &str) -> &'static str {
         "Network" => " ",
         "Disk" => " ",
         "Filesystem" => " ",
+        "Subvolumes" => "󰉋 ",
         "GPU" => " ",
         "Swap" => " ",
+        "GRUB password" => "󰌾 ",
+        "CPU mitigations" => " ",
+        "Boot splash" => "󰹑 ",
+        "Power" => "󰂄 ",
         "Hostname" => " ",
         "Username" => " ",
         "Keyboard" => " ",
+        "Locale" => "󰗊 ",
+        "Boot" => "󰑓 ",
         "Timezone" => " ",
+        "LUKS header" => "󰌆 ",
+        "Secure wipe" => "󰃢 ",
+        "Bad block scan" => "󰋊 ",
+        "SSD tuning" => "󰓅 ",
+        "NTP server" => "󰥔 ",
         "Compositor" => " ",
         "Browsers" => " ",
         "Editors" => " ",
         "Terminals" => " ",
+        "Extra packages" => "󰏖 ",
+        "Gaming" => "󰊴 ",
+        "Package list" => "󰉹 ",
+        "Profile" => "󰍹 ",
+        "AUR helper" => "󰏗 ",
         _ => " ",
     }
 }
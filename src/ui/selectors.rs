@@ -11,10 +11,13 @@ use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Padding, Parag
 use ratatui::{Frame, Terminal};
 
 use crate::drivers::NvidiaVariant;
+use crate::packages::AurHelper;
+use crate::selection::{BtrfsLayout, InstallProfile, Mitigations};
 use crate::ui::colors::PURE_WHITE;
 
 use super::common::{aligned_summary_area, draw_install_summary, split_main_and_summary};
-use super::{InstallSummary, NvidiaAction, NEBULA_ART};
+use super::theme::{draw_themed, theme};
+use super::{InstallSummary, NvidiaAction, SelectionAction, NEBULA_ART};
 
 // NVIDIA driver selector
 pub fn run_nvidia_selector(
@@ -30,7 +33,9 @@ pub fn run_nvidia_selector(
 
     // Main loop for the selector screen
     loop {
-        terminal.draw(|f| draw_nvidia_selector(f.size(), f, cursor, &options, summary))?;
+        draw_themed(terminal, |f| {
+            draw_nvidia_selector(f.size(), f, cursor, &options, summary)
+        })?;
 
         // User input
         let timeout = Duration::from_millis(100);
@@ -120,7 +125,7 @@ fn draw_nvidia_selector(
     // Controls box
     let help = Paragraph::new(vec![
         Line::from(vec![
-            Span::styled("󰁞/󰁆", Style::default().fg(Color::Cyan)),
+            Span::styled(theme().up_down, Style::default().fg(Color::Cyan)),
             Span::raw(" to move, "),
             Span::styled("Enter", Style::default().fg(Color::Cyan)),
             Span::raw(" to select."),
@@ -255,3 +260,313 @@ fn draw_nvidia_selector(
     let summary_area = aligned_summary_area(summary_area, main_area, layout[3]);
     draw_install_summary(summary_area, f, summary);
 }
+
+// Screen text and per-option help for a single-choice list
+pub struct SingleSelect<'a, T> {
+    pub title: &'a str,
+    pub list_title: &'a str,
+    pub footer: &'a str,
+    pub options: &'a [T],
+    pub label: fn(T) -> &'static str,
+    pub help: fn(T) -> Vec<Line<'static>>,
+    pub help_height: u16, // Info box height, sized for the longest help text
+}
+
+// Generic single-choice selector shared by the simple option screens
+pub fn run_single_select<T: Copy + PartialEq>(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    select: &SingleSelect<'_, T>,
+    current: T,
+    summary: &InstallSummary,
+) -> Result<SelectionAction<T>> {
+    let options = select.options;
+    let mut cursor = options
+        .iter()
+        .position(|option| *option == current)
+        .unwrap_or(0);
+
+    // Main loop for the selector screen
+    loop {
+        draw_themed(terminal, |f| {
+            draw_single_select(f.size(), f, select, cursor, summary)
+        })?;
+
+        // User input
+        let timeout = Duration::from_millis(100);
+        if event::poll(timeout).context("poll events")? {
+            if let Event::Key(key) = event::read().context("read event")? {
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                match key.code {
+                    KeyCode::Up => cursor = cursor.saturating_sub(1),
+                    KeyCode::Down if cursor + 1 < options.len() => cursor += 1,
+                    KeyCode::Enter if !options.is_empty() => {
+                        return Ok(SelectionAction::Submit(options[cursor]));
+                    }
+                    KeyCode::Esc => return Ok(SelectionAction::Back),
+                    KeyCode::Char('q') | KeyCode::Char('Q')
+                        if key.modifiers.contains(KeyModifiers::CONTROL) =>
+                    {
+                        return Ok(SelectionAction::Quit);
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+// Single-choice selector UI
+fn draw_single_select<T: Copy>(
+    area: Rect,
+    f: &mut Frame<'_>,
+    select: &SingleSelect<'_, T>,
+    cursor: usize,
+    summary: &InstallSummary,
+) {
+    let (main_area, summary_area) = split_main_and_summary(area);
+    // Layout of the main area
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(0)
+        .constraints([
+            Constraint::Length(NEBULA_ART.len() as u16),
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(5),
+            Constraint::Min(6),
+            Constraint::Length(1),
+        ])
+        .split(main_area);
+
+    // Nebula ASCII art
+    let art_lines: Vec<Line> = NEBULA_ART
+        .iter()
+        .map(|line| {
+            Line::from(Span::styled(
+                *line,
+                Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+            ))
+        })
+        .collect();
+    let art = Paragraph::new(art_lines).block(Block::default());
+    f.render_widget(art, layout[0]);
+
+    // Step title
+    let title = Line::from(vec![
+        Span::raw("/- "),
+        Span::styled(
+            select.title,
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(" -/"),
+    ]);
+    let title_block = Paragraph::new(title).block(Block::default());
+    f.render_widget(title_block, layout[1]);
+
+    // Controls box
+    let help = Paragraph::new(vec![
+        Line::from(vec![
+            Span::styled(theme().up_down, Style::default().fg(Color::Cyan)),
+            Span::raw(" to move, "),
+            Span::styled("Enter", Style::default().fg(Color::Cyan)),
+            Span::raw(" to select."),
+        ]),
+        Line::from(vec![
+            Span::styled("Esc", Style::default().fg(Color::Cyan)),
+            Span::raw(" to go back."),
+        ]),
+    ])
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Black))
+            .padding(Padding::new(1, 0, 1, 0))
+            .title(Line::from(vec![
+                Span::styled("[", Style::default().fg(Color::Black)),
+                Span::styled(
+                    " Controls ",
+                    Style::default().fg(PURE_WHITE).add_modifier(Modifier::BOLD),
+                ),
+                Span::styled("]", Style::default().fg(Color::Black)),
+            ])),
+    )
+    .wrap(Wrap { trim: false });
+    f.render_widget(help, layout[3]);
+
+    // Options list with the help for the highlighted option below it
+    let list_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(4), Constraint::Length(select.help_height)])
+        .split(layout[4]);
+    let items: Vec<ListItem> = select
+        .options
+        .iter()
+        .enumerate()
+        .map(|(idx, option)| {
+            ListItem::new(Line::from(format!(
+                "{:>2}) {}",
+                idx + 1,
+                (select.label)(*option)
+            )))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Black))
+                .padding(Padding::new(1, 0, 1, 0))
+                .title(Line::from(vec![
+                    Span::styled("[", Style::default().fg(Color::Black)),
+                    Span::styled(
+                        format!(" {} ", select.list_title),
+                        Style::default().fg(PURE_WHITE).add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled("]", Style::default().fg(Color::Black)),
+                ])),
+        )
+        .highlight_style(
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        );
+    let mut state = ListState::default();
+    state.select(Some(cursor.min(select.options.len().saturating_sub(1))));
+    f.render_stateful_widget(list, list_layout[0], &mut state);
+
+    let info_lines = select
+        .options
+        .get(cursor)
+        .map(|option| (select.help)(*option))
+        .unwrap_or_default();
+    let info_block = Paragraph::new(info_lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Black))
+                .padding(Padding::new(1, 0, 1, 0))
+                .title(Line::from(vec![
+                    Span::styled("[", Style::default().fg(Color::Black)),
+                    Span::styled(
+                        " Info ",
+                        Style::default().fg(PURE_WHITE).add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled("]", Style::default().fg(Color::Black)),
+                ])),
+        )
+        .wrap(Wrap { trim: false });
+    f.render_widget(info_block, list_layout[1]);
+
+    // Footer text
+    let footer = Paragraph::new(Line::from(Span::styled(
+        select.footer,
+        Style::default().fg(Color::White),
+    )));
+    f.render_widget(footer, layout[5]);
+
+    // Installation summary on the right side
+    let summary_area = aligned_summary_area(summary_area, main_area, layout[3]);
+    draw_install_summary(summary_area, f, summary);
+}
+
+// AUR helper selector
+pub fn run_aur_helper_selector(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    current: AurHelper,
+    summary: &InstallSummary,
+) -> Result<SelectionAction<AurHelper>> {
+    let select = SingleSelect {
+        title: "Choose AUR Helper",
+        list_title: "AUR helpers",
+        footer: "Choose the AUR helper to install",
+        options: &[AurHelper::Yay, AurHelper::Paru, AurHelper::None],
+        label: AurHelper::label,
+        help: |_| {
+            vec![Line::from(
+                "Choosing None skips AUR packages such as the -bin browsers and editors",
+            )]
+        },
+        help_height: 5,
+    };
+    run_single_select(terminal, &select, current, summary)
+}
+
+// Install profile selector
+pub fn run_profile_selector(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    current: InstallProfile,
+    summary: &InstallSummary,
+) -> Result<SelectionAction<InstallProfile>> {
+    let select = SingleSelect {
+        title: "Choose Install Profile",
+        list_title: "Profiles",
+        footer: "Choose what to install",
+        options: &[InstallProfile::Full, InstallProfile::Minimal],
+        label: InstallProfile::label,
+        help: |_| {
+            vec![Line::from(
+                "Minimal installs only the base packages, kernel, and drivers",
+            )]
+        },
+        help_height: 5,
+    };
+    run_single_select(terminal, &select, current, summary)
+}
+
+// Btrfs subvolume layout selector
+pub fn run_btrfs_layout_selector(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    current: BtrfsLayout,
+    summary: &InstallSummary,
+) -> Result<SelectionAction<BtrfsLayout>> {
+    let select = SingleSelect {
+        title: "Choose Btrfs Layout",
+        list_title: "Layouts",
+        footer: "Choose how the root filesystem is split into subvolumes",
+        options: &[
+            BtrfsLayout::Default,
+            BtrfsLayout::Minimal,
+            BtrfsLayout::Snapper,
+        ],
+        label: BtrfsLayout::label,
+        help: |layout| {
+            layout
+                .subvolumes()
+                .iter()
+                .map(|subvol| Line::from(format!("{} -> {}", subvol.name, subvol.mountpoint)))
+                .collect()
+        },
+        help_height: 8,
+    };
+    run_single_select(terminal, &select, current, summary)
+}
+
+// CPU vulnerability mitigations selector
+pub fn run_mitigations_selector(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    current: Mitigations,
+    summary: &InstallSummary,
+) -> Result<SelectionAction<Mitigations>> {
+    let select = SingleSelect {
+        title: "CPU Mitigations",
+        list_title: "Mitigations",
+        footer: "Trade CPU vulnerability protection against performance",
+        options: &[Mitigations::Default, Mitigations::Off, Mitigations::Auto],
+        label: Mitigations::label,
+        help: |mitigations| {
+            // Disabling mitigations is the risky choice, so its description is shown in red
+            let style = if mitigations == Mitigations::Off {
+                Style::default().fg(Color::Red)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            vec![Line::from(Span::styled(mitigations.description(), style))]
+        },
+        help_height: 6,
+    };
+    run_single_select(terminal, &select, current, summary)
+}
@@ -0,0 +1,137 @@
+/////////
+/// Line-based prompts for serial consoles
+////////
+use std::io::{self, BufRead, Write};
+
+use anyhow::{Context, Result};
+
+// Smallest terminal the setup screens fit in
+const MIN_COLUMNS: u16 = 80;
+const MIN_ROWS: u16 = 24;
+
+// Serial consoles usually report TERM=dumb or no usable size; NEBULA_SERIAL forces either way
+pub fn serial_console() -> bool {
+    match std::env::var("NEBULA_SERIAL").ok().as_deref() {
+        Some("1") => return true,
+        Some("0") => return false,
+        _ => {}
+    }
+    let term = std::env::var("TERM").unwrap_or_default();
+    if term.is_empty() || term == "dumb" {
+        return true;
+    }
+    match crossterm::terminal::size() {
+        Ok((columns, rows)) => columns < MIN_COLUMNS || rows < MIN_ROWS,
+        Err(_) => true,
+    }
+}
+
+// Reads one trimmed line; a closed input aborts the setup
+fn read_line() -> Result<String> {
+    let mut line = String::new();
+    let read = io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .context("read input")?;
+    if read == 0 {
+        anyhow::bail!("Setup aborted: input closed");
+    }
+    Ok(line.trim().to_string())
+}
+
+fn print_prompt(question: &str, default: Option<&str>) -> Result<()> {
+    let mut stdout = io::stdout().lock();
+    match default.filter(|value| !value.is_empty()) {
+        Some(value) => write!(stdout, "{} [{}]: ", question, value),
+        None => write!(stdout, "{}: ", question),
+    }
+    .context("write prompt")?;
+    stdout.flush().context("flush stdout")
+}
+
+// Asks until `check` accepts the answer; an empty answer takes the default
+pub fn prompt_line(
+    question: &str,
+    default: &str,
+    check: impl Fn(&str) -> Option<&'static str>,
+) -> Result<String> {
+    loop {
+        print_prompt(question, Some(default))?;
+        let answer = read_line()?;
+        let answer = if answer.is_empty() {
+            default.to_string()
+        } else {
+            answer
+        };
+        match check(&answer) {
+            Some(message) => println!("  {}", message),
+            None => return Ok(answer),
+        }
+    }
+}
+
+pub fn prompt_yes_no(question: &str, default: bool) -> Result<bool> {
+    loop {
+        print_prompt(question, Some(if default { "Y/n" } else { "y/N" }))?;
+        match read_line()?.to_ascii_lowercase().as_str() {
+            "" => return Ok(default),
+            "y" | "yes" => return Ok(true),
+            "n" | "no" => return Ok(false),
+            _ => println!("  Answer y or n"),
+        }
+    }
+}
+
+// Numbered list; returns the index of the picked option
+pub fn prompt_choice(question: &str, options: &[String], default: usize) -> Result<usize> {
+    println!("{}:", question);
+    for (idx, option) in options.iter().enumerate() {
+        println!("  {}) {}", idx + 1, option);
+    }
+    loop {
+        print_prompt("Choice", Some(&(default + 1).to_string()))?;
+        let answer = read_line()?;
+        if answer.is_empty() {
+            return Ok(default);
+        }
+        match answer.parse::<usize>() {
+            Ok(number) if (1..=options.len()).contains(&number) => return Ok(number - 1),
+            _ => println!("  Pick a number from 1 to {}", options.len()),
+        }
+    }
+}
+
+// Reads a line with terminal echo turned off
+fn read_secret() -> Result<String> {
+    let fd = libc::STDIN_FILENO;
+    let mut original = unsafe { std::mem::zeroed::<libc::termios>() };
+    let is_tty = unsafe { libc::tcgetattr(fd, &mut original) } == 0;
+    if is_tty {
+        let mut silent = original;
+        silent.c_lflag &= !libc::ECHO;
+        unsafe { libc::tcsetattr(fd, libc::TCSANOW, &silent) };
+    }
+    let answer = read_line();
+    if is_tty {
+        unsafe { libc::tcsetattr(fd, libc::TCSANOW, &original) };
+        println!();
+    }
+    answer
+}
+
+// Asks for a new non-empty secret twice until both entries match
+pub fn prompt_new_secret(question: &str) -> Result<String> {
+    loop {
+        print_prompt(question, None)?;
+        let value = read_secret()?;
+        if value.is_empty() {
+            println!("  Cannot be empty");
+            continue;
+        }
+        print_prompt("Confirm", None)?;
+        if read_secret()? == value {
+            return Ok(value);
+        }
+        println!("  Entries do not match, try again");
+    }
+}
@@ -12,6 +12,7 @@ use ratatui::{Frame, Terminal};
 
 use super::colors::PURE_WHITE;
 use super::common::{aligned_summary_area, draw_install_summary, split_main_and_summary};
+use super::theme::draw_themed;
 use super::{InputAction, InstallSummary, NEBULA_ART};
 
 // Text input screen
@@ -38,7 +39,7 @@ pub fn run_text_input(
         }
 
         // Draw the UI
-        terminal.draw(|f| {
+        draw_themed(terminal, |f| {
             draw_text_input(
                 f.size(),
                 f,
@@ -277,7 +278,7 @@ pub fn render_text_input(
     mask: bool,
     summary: &InstallSummary,
 ) -> Result<()> {
-    terminal.draw(|f| {
+    draw_themed(terminal, |f| {
         draw_text_input(
             f.size(),
             f,
@@ -0,0 +1,113 @@
+use std::io;
+use std::sync::OnceLock;
+
+use ratatui::backend::CrosstermBackend;
+use ratatui::buffer::Buffer;
+use ratatui::style::Color;
+use ratatui::{Frame, Terminal};
+
+use super::colors::high_contrast;
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+// Glyphs and palette for the current terminal. NEBULA_ASCII=1 swaps the Nerd Font
+// glyphs for ASCII and implies NEBULA_HIGH_CONTRAST=1, for serial consoles and screen readers.
+pub struct Theme {
+    pub ascii: bool,
+    pub high_contrast: bool,
+    pub up_down: &'static str,
+    pub left_right: &'static str,
+    pub checked: &'static str, // Inside the `[ ]` of a checkbox
+    pub bullet: &'static str,
+}
+
+// Current theme, read from the environment once
+pub fn theme() -> &'static Theme {
+    THEME.get_or_init(|| {
+        let ascii = std::env::var("NEBULA_ASCII").ok().as_deref() == Some("1");
+        let high_contrast =
+            ascii || std::env::var("NEBULA_HIGH_CONTRAST").ok().as_deref() == Some("1");
+        Theme::new(ascii, high_contrast)
+    })
+}
+
+impl Theme {
+    fn new(ascii: bool, high_contrast: bool) -> Self {
+        if ascii {
+            Self {
+                ascii,
+                high_contrast,
+                up_down: "Up/Down",
+                left_right: "Left/Right",
+                checked: "x",
+                bullet: "*",
+            }
+        } else {
+            Self {
+                ascii,
+                high_contrast,
+                up_down: "󰁞/󰁆",
+                left_right: "󰁎/󰁕",
+                checked: "󰸞",
+                bullet: "●",
+            }
+        }
+    }
+
+    // A decorative Nerd Font glyph, or its ASCII stand-in
+    pub fn glyph(&self, nerd: &'static str, ascii: &'static str) -> &'static str {
+        if self.ascii {
+            ascii
+        } else {
+            nerd
+        }
+    }
+
+    // Run at the end of every draw: recolors the frame and swaps box drawing for ASCII
+    pub fn apply(&self, buf: &mut Buffer) {
+        if !self.ascii && !self.high_contrast {
+            return;
+        }
+        for cell in buf.content.iter_mut() {
+            if self.high_contrast {
+                cell.fg = high_contrast(cell.fg);
+                if cell.bg == Color::Black {
+                    cell.bg = Color::Reset;
+                }
+            }
+            if self.ascii {
+                if let Some(symbol) = ascii_symbol(cell.symbol()) {
+                    cell.set_symbol(symbol);
+                }
+            }
+        }
+    }
+}
+
+// Draws one frame and applies the theme to it; every screen renders through this
+pub fn draw_themed<F>(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    render: F,
+) -> io::Result<()>
+where
+    F: FnOnce(&mut Frame<'_>),
+{
+    terminal.draw(|f| {
+        render(f);
+        theme().apply(f.buffer_mut());
+    })?;
+    Ok(())
+}
+
+// ASCII for the borders and gauge blocks ratatui draws itself
+fn ascii_symbol(symbol: &str) -> Option<&'static str> {
+    match symbol {
+        "─" | "━" | "═" => Some("-"),
+        "│" | "┃" | "║" => Some("|"),
+        "┌" | "┐" | "└" | "┘" | "╭" | "╮" | "╰" | "╯" | "┏" | "┓" | "┗" | "┛" => {
+            Some("+")
+        }
+        "█" | "▉" | "▊" | "▋" | "▌" | "▍" | "▎" | "▏" => Some("#"),
+        _ => None,
+    }
+}
@@ -10,14 +10,16 @@ use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Padding, Paragraph, Wrap};
 use ratatui::{Frame, Terminal};
 
+use crate::timezones::{timezone_cities, timezone_regions};
 use crate::ui::colors::PURE_WHITE;
 
 use super::common::{
     aligned_summary_area, draw_install_summary, filter_items, split_main_and_summary,
 };
+use super::theme::{draw_themed, theme};
 use super::{InstallSummary, SelectionAction, NEBULA_ART};
 
-// Timezone selector
+// Timezone selector, picking a region first and then a city inside it
 pub fn run_timezone_selector(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     zones: &[String],
@@ -28,15 +30,42 @@ pub fn run_timezone_selector(
         return Ok(SelectionAction::Quit);
     }
 
+    // Start inside the region of the preselected zone so Enter confirms it
+    let regions = timezone_regions(zones);
+    let mut region: Option<String> = zones
+        .get(initial)
+        .and_then(|zone| zone.split_once('/'))
+        .map(|(region, _)| region.to_string());
+    let mut cities = region
+        .as_deref()
+        .map(|region| timezone_cities(zones, region))
+        .unwrap_or_default();
+    let mut entries = level_entries(&regions, &cities, region.is_some());
+
     // State for the search/filter
     let mut query = String::new();
-    let mut filtered = filter_items(zones, &query);
-    let mut cursor = filtered.iter().position(|idx| *idx == initial).unwrap_or(0);
+    let mut filtered = filter_items(&entries, &query);
+    let mut cursor = match &region {
+        Some(_) => cities.iter().position(|(idx, _)| *idx == initial),
+        None => zones
+            .get(initial)
+            .and_then(|zone| regions.iter().position(|r| r == zone)),
+    }
+    .unwrap_or(0);
 
     // Main loop for the timezone selection screen
     loop {
-        terminal.draw(|f| {
-            draw_timezone_selector(f.size(), f, cursor, zones, &filtered, &query, summary)
+        draw_themed(terminal, |f| {
+            draw_timezone_selector(
+                f.size(),
+                f,
+                cursor,
+                region.as_deref(),
+                &entries,
+                &filtered,
+                &query,
+                summary,
+            )
         })?;
 
         // User input
@@ -74,12 +103,40 @@ pub fn run_timezone_selector(
                     }
                     // Action controls
                     KeyCode::Enter => {
-                        if let Some(idx) = filtered.get(cursor) {
+                        let Some(entry_idx) = filtered.get(cursor).copied() else {
+                            continue;
+                        };
+                        if region.is_some() {
                             // Return the index from the *original* unfiltered list.
-                            return Ok(SelectionAction::Submit(*idx));
+                            return Ok(SelectionAction::Submit(cities[entry_idx].0));
+                        }
+                        let chosen = &regions[entry_idx];
+                        let region_cities = timezone_cities(zones, chosen);
+                        if region_cities.is_empty() {
+                            // Zones without a region, like `UTC`, are picked directly
+                            if let Some(idx) = zones.iter().position(|zone| zone == chosen) {
+                                return Ok(SelectionAction::Submit(idx));
+                            }
+                            continue;
                         }
+                        region = Some(chosen.clone());
+                        cities = region_cities;
+                        entries = level_entries(&regions, &cities, true);
+                        query.clear();
+                        filtered = filter_items(&entries, &query);
+                        cursor = 0;
+                    }
+                    KeyCode::Esc => {
+                        // Go back to the region list before leaving the step
+                        let Some(current) = region.take() else {
+                            return Ok(SelectionAction::Back);
+                        };
+                        cities.clear();
+                        entries = level_entries(&regions, &cities, false);
+                        query.clear();
+                        filtered = filter_items(&entries, &query);
+                        cursor = regions.iter().position(|r| *r == current).unwrap_or(0);
                     }
-                    KeyCode::Esc => return Ok(SelectionAction::Back),
                     KeyCode::Char('q') | KeyCode::Char('Q')
                         if key.modifiers.contains(KeyModifiers::CONTROL) =>
                     {
@@ -88,22 +145,22 @@ pub fn run_timezone_selector(
                     // Search/filter controls
                     KeyCode::Backspace => {
                         query.pop();
-                        filtered = filter_items(zones, &query);
+                        filtered = filter_items(&entries, &query);
                         cursor = 0;
                     }
                     KeyCode::Char('/') => {
                         query.clear();
-                        filtered = filter_items(zones, &query);
+                        filtered = filter_items(&entries, &query);
                         cursor = 0;
                     }
                     KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                         query.clear();
-                        filtered = filter_items(zones, &query);
+                        filtered = filter_items(&entries, &query);
                         cursor = 0;
                     }
                     KeyCode::Char(ch) if ch.is_ascii() && !ch.is_ascii_control() => {
                         query.push(ch);
-                        filtered = filter_items(zones, &query);
+                        filtered = filter_items(&entries, &query);
                         cursor = 0;
                     }
                     _ => {}
@@ -113,6 +170,15 @@ pub fn run_timezone_selector(
     }
 }
 
+// Names listed at the current level: regions, or the cities of the open region
+fn level_entries(regions: &[String], cities: &[(usize, String)], in_region: bool) -> Vec<String> {
+    if in_region {
+        cities.iter().map(|(_, city)| city.clone()).collect()
+    } else {
+        regions.to_vec()
+    }
+}
+
 // "Loading timezone..." screen while waiting for GeoIP detection
 fn draw_timezone_loading(area: Rect, f: &mut Frame<'_>, summary: &InstallSummary) {
     let (main_area, summary_area) = split_main_and_summary(area);
@@ -195,7 +261,7 @@ pub fn render_timezone_loading(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     summary: &InstallSummary,
 ) -> Result<()> {
-    terminal.draw(|f| draw_timezone_loading(f.size(), f, summary))?;
+    draw_themed(terminal, |f| draw_timezone_loading(f.size(), f, summary))?;
     Ok(())
 }
 
@@ -204,7 +270,8 @@ fn draw_timezone_selector(
     area: Rect,
     f: &mut Frame<'_>,
     cursor: usize,
-    zones: &[String],
+    region: Option<&str>,
+    entries: &[String],
     filtered: &[usize],
     query: &str,
     summary: &InstallSummary,
@@ -253,7 +320,7 @@ fn draw_timezone_selector(
     // Controls box
     let help = Paragraph::new(vec![
         Line::from(vec![
-            Span::styled("󰁞/󰁆", Style::default().fg(Color::Cyan)),
+            Span::styled(theme().up_down, Style::default().fg(Color::Cyan)),
             Span::raw(" to move, "),
             Span::styled("PgUp/PgDn", Style::default().fg(Color::Cyan)),
             Span::raw(" to scroll, "),
@@ -298,18 +365,23 @@ fn draw_timezone_selector(
     let items: Vec<ListItem> = visible
         .iter()
         .enumerate()
-        .map(|(idx, zone_idx)| {
-            let zone = zones.get(*zone_idx).map(|s| s.as_str()).unwrap_or("");
+        .map(|(idx, entry_idx)| {
+            let entry = entries.get(*entry_idx).map(|s| s.as_str()).unwrap_or("");
             let line = Line::from(vec![
                 Span::raw(format!("{:>4}) ", start + idx + 1)),
-                Span::raw(zone),
+                Span::raw(entry),
             ]);
             ListItem::new(line)
         })
         .collect();
 
-    // List of timezones
-    let title = format!("Timezones ({} / {} total)", filtered.len(), zones.len());
+    // List of regions or of the cities in the open region
+    let title = format!(
+        "{} ({} / {} total)",
+        region.unwrap_or("Regions"),
+        filtered.len(),
+        entries.len()
+    );
     let list = List::new(items)
         .block(
             Block::default()
@@ -10,10 +10,12 @@ use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
 use ratatui::{Frame, Terminal};
 
-use crate::network::WifiNetwork;
+use crate::network::{network_check, NetworkCheck, WifiNetwork};
 
 use super::colors::PURE_WHITE;
 use super::common::{aligned_summary_area, draw_install_summary, split_main_and_summary};
+use super::network::speed_test_line;
+use super::theme::{draw_themed, theme};
 use super::{InstallSummary, WifiAction, NEBULA_ART};
 
 // Wi-Fi selector
@@ -30,7 +32,7 @@ pub fn run_wifi_selector(
     // Main loop for the Wi-Fi selection screen
     loop {
         // Draw the UI
-        terminal.draw(|f| {
+        draw_themed(terminal, |f| {
             draw_wifi_selector(
                 f.size(),
                 f,
@@ -59,14 +61,16 @@ pub fn run_wifi_selector(
                         }
                     }
                     KeyCode::Down => {
-                        if cursor + 1 < networks.len() {
+                        // The last row is the hidden network entry
+                        if cursor < networks.len() {
                             cursor += 1;
                         }
                     }
                     KeyCode::Enter => {
-                        if !networks.is_empty() {
-                            return Ok(WifiAction::Submit(cursor));
+                        if cursor == networks.len() {
+                            return Ok(WifiAction::Hidden);
                         }
+                        return Ok(WifiAction::Submit(cursor));
                     }
                     KeyCode::Char('1') => {
                         if internet_ready {
@@ -82,6 +86,9 @@ pub fn run_wifi_selector(
                     _ => {}
                 }
             }
+        } else if matches!(network_check(), NetworkCheck::Done(_)) {
+            // The background internet check finished; let the caller pick up the result
+            return Ok(WifiAction::Refresh);
         } else if wifi_connected
             && !internet_ready
             && last_refresh.elapsed() >= Duration::from_secs(1)
@@ -116,7 +123,7 @@ fn draw_wifi_selector(
         Constraint::Length(3),
     ];
     let next_step_idx = if internet_ready {
-        constraints.push(Constraint::Length(3));
+        constraints.push(Constraint::Length(4));
         Some(6)
     } else {
         None
@@ -159,7 +166,7 @@ fn draw_wifi_selector(
 
     // Controls box
     let mut help_lines = vec![Line::from(vec![
-        Span::styled("󰁞/󰁆", Style::default().fg(Color::Cyan)),
+        Span::styled(theme().up_down, Style::default().fg(Color::Cyan)),
         Span::raw(" to move, "),
         Span::styled("Enter", Style::default().fg(Color::Cyan)),
         Span::raw(" to connect"),
@@ -217,33 +224,73 @@ fn draw_wifi_selector(
             .wrap(Wrap { trim: false });
         f.render_widget(searching_block, layout[4]);
     } else {
-        let items: Vec<ListItem> = networks
+        // Pad SSIDs so the signal and security columns line up
+        let ssid_width = networks
+            .iter()
+            .map(|network| network.ssid.chars().count())
+            .max()
+            .unwrap_or(0)
+            .min(32);
+        let mut items: Vec<ListItem> = networks
             .iter()
             .enumerate()
             .map(|(idx, network)| {
                 let in_use = if network.in_use { "*" } else { " " };
                 let signal = format!("{:>3}%", network.signal);
-                let security = if network.is_open() {
-                    "open".to_string()
-                } else if network.security.is_empty() {
-                    "secured".to_string()
+                let signal_color = match network.signal {
+                    0..=39 => Color::Red,
+                    40..=59 => Color::Yellow,
+                    _ => Color::Green,
+                };
+                let ssid_style = if network.in_use {
+                    Style::default()
+                        .fg(Color::Green)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().add_modifier(Modifier::BOLD)
+                };
+                let (lock, lock_color) = if network.is_open() {
+                    (theme().glyph("󰌿 ", ""), Color::DarkGray)
                 } else {
-                    network.security.clone()
+                    (theme().glyph("󰌾 ", ""), Color::White)
                 };
                 let line = Line::from(vec![
                     Span::raw(format!("{:>2}) ", idx + 1)),
                     Span::raw(in_use),
                     Span::raw(" "),
-                    Span::styled("󰤨 ", Style::default().fg(Color::LightBlue)),
-                    Span::styled(&network.ssid, Style::default().add_modifier(Modifier::BOLD)),
+                    Span::styled(
+                        if theme().ascii {
+                            String::new()
+                        } else {
+                            format!("{} ", network.signal_icon())
+                        },
+                        Style::default().fg(signal_color),
+                    ),
+                    Span::styled(
+                        format!("{:<width$}", network.ssid, width = ssid_width),
+                        ssid_style,
+                    ),
                     Span::raw("  "),
-                    Span::styled(signal, Style::default().fg(Color::Yellow)),
+                    Span::styled(signal, Style::default().fg(signal_color)),
                     Span::raw("  "),
-                    Span::styled(security, Style::default().fg(Color::White)),
+                    Span::styled(lock, Style::default().fg(lock_color)),
+                    Span::styled(network.security_label(), Style::default().fg(Color::White)),
                 ]);
                 ListItem::new(line)
             })
             .collect();
+        items.push(ListItem::new(Line::from(vec![
+            Span::raw(format!("{:>2}) ", networks.len() + 1)),
+            Span::raw("  "),
+            Span::styled(
+                theme().glyph("󰤬 ", ""),
+                Style::default().fg(Color::LightBlue),
+            ),
+            Span::styled(
+                "Connect to hidden network...",
+                Style::default().fg(Color::White),
+            ),
+        ])));
 
         let list = List::new(items).block(list_block).highlight_style(
             Style::default()
@@ -251,9 +298,7 @@ fn draw_wifi_selector(
                 .add_modifier(Modifier::BOLD),
         );
         let mut state = ListState::default();
-        if !networks.is_empty() {
-            state.select(Some(cursor.min(networks.len() - 1)));
-        }
+        state.select(Some(cursor.min(networks.len())));
         f.render_stateful_widget(list, layout[4], &mut state);
     }
 
@@ -302,6 +347,7 @@ fn draw_wifi_selector(
                 Span::styled("[1]", Style::default().fg(Color::Cyan)),
                 Span::raw(" to continue to disk selection."),
             ]));
+            next_step_lines.extend(speed_test_line());
         } else {
             next_step_lines.push(Line::from(""));
         }
@@ -343,7 +389,7 @@ pub fn render_wifi_searching(
     internet_ready: bool,
     summary: &InstallSummary,
 ) -> Result<()> {
-    terminal.draw(|f| {
+    draw_themed(terminal, |f| {
         draw_wifi_selector(
             f.size(),
             f,
@@ -370,7 +416,7 @@ pub fn render_wifi_connecting(
     summary: &InstallSummary,
     spinner: &str,
 ) -> Result<()> {
-    terminal.draw(|f| {
+    draw_themed(terminal, |f| {
         draw_wifi_selector(
             f.size(),
             f,